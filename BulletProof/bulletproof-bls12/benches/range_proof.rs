@@ -0,0 +1,74 @@
+// Bulletproof range proofs over BLS12-381: a single value at several bit
+// widths, and aggregated proofs for several values at 32 bits (the width the
+// credential attributes are proven at).
+//
+//   cargo bench -p bulletproofs_bls12 --bench range_proof
+
+use bulletproofs_bls::inner_types::Scalar;
+use bulletproofs_bls::RangeProof;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand_core::{OsRng, RngCore};
+
+use bulletproofs_bls12::bulletproof::{get_bp_gens, get_pc_gens, transcript_from_label};
+
+const LABEL: &str = "bench";
+
+fn blinding() -> Scalar {
+    loop {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let blinding = Scalar::from_be_bytes(&bytes);
+        if blinding.is_some().unwrap_u8() == 1 {
+            break blinding.unwrap();
+        }
+    }
+}
+
+// values that fit in n bits
+fn values(count: usize, n: usize) -> Vec<u64> {
+    (0..count).map(|_| if n == 64 { OsRng.next_u64() } else { OsRng.next_u64() % (1u64 << n) }).collect()
+}
+
+fn bit_widths(c: &mut Criterion) {
+    let bp_gens = get_bp_gens(64, 1);
+    let pc_gens = get_pc_gens();
+    let mut group = c.benchmark_group("bulletproof/single");
+    for n in [8, 16, 32, 64] {
+        let value = values(1, n)[0];
+        let blinding = blinding();
+        let (proof, commitment) = RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript_from_label(LABEL), value, &blinding, n).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("prove", n), &n, |b, &n| {
+            b.iter(|| RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript_from_label(LABEL), value, &blinding, n).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("verify", n), &n, |b, &n| {
+            b.iter(|| assert!(proof.verify_single(&bp_gens, &pc_gens, &mut transcript_from_label(LABEL), &commitment, n).is_ok()))
+        });
+    }
+    group.finish();
+}
+
+fn aggregation(c: &mut Criterion) {
+    let n = 32;
+    let bp_gens = get_bp_gens(64, 16);
+    let pc_gens = get_pc_gens();
+    let mut group = c.benchmark_group("bulletproof/aggregated");
+    group.sample_size(20);
+    // aggregation sizes must be powers of two
+    for m in [1, 2, 4, 8, 16] {
+        let values = values(m, n);
+        let blindings: Vec<Scalar> = (0..m).map(|_| blinding()).collect();
+        let (proof, commitments) = RangeProof::prove_multiple(&bp_gens, &pc_gens, &mut transcript_from_label(LABEL), &values, &blindings, n).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("prove", m), &m, |b, _| {
+            b.iter(|| RangeProof::prove_multiple(&bp_gens, &pc_gens, &mut transcript_from_label(LABEL), &values, &blindings, n).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("verify", m), &m, |b, _| {
+            b.iter(|| assert!(proof.verify_multiple(&bp_gens, &pc_gens, &mut transcript_from_label(LABEL), &commitments, n).is_ok()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bit_widths, aggregation);
+criterion_main!(benches);
@@ -3,6 +3,7 @@ use ark_ff::fields::PrimeField;
 use ark_bn254::Fr as FrBN;
 use std::str::FromStr;
 
+#[derive(Clone)]
 pub struct MiMCParameters<F: PrimeField> {
     pub k: F,
     pub rounds: usize,
@@ -323,9 +324,9 @@ pub fn yeild_MiMC_Parameter (k: FrBN, num_inputs: usize, _num_outputs : usize)
     }
     println!("The type of round_keys is: {}", std::any::type_name_of_val(&round_keys));
     let para = MiMCParameters::<FrBN> {
-        k: k,
+        k,
         rounds,
-        num_inputs : num_inputs,
+        num_inputs,
         num_outputs: num_inputs,
         round_keys,
     };
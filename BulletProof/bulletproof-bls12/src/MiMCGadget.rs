@@ -0,0 +1,155 @@
+use ark_ff::fields::PrimeField;
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::MiMC::{mimc_sponge, MiMCParameters};
+
+/*
+    R1CS counterpart of MiMC::mimc_feistel. It follows the native function
+    round by round, including the way it reads `round_keys`: the constant
+    added in round i is round_keys[i - 1], which already contains params.k.
+    The key is a circuit variable (it is a public input of BindHashMimc.circom),
+    so the constant part of every round key is taken relative to params.k.
+    With k == params.k the outputs are identical to the native ones.
+    Cost: 3 constraints per round (t^2, t^4, t^5).
+*/
+pub fn mimc_feistel_gadget<F: PrimeField>(
+    xL_in: &FpVar<F>,
+    xR_in: &FpVar<F>,
+    k: &FpVar<F>,
+    params: &MiMCParameters<F>,
+) -> Result<(FpVar<F>, FpVar<F>), SynthesisError> {
+    let mut xL: Vec<FpVar<F>> = Vec::with_capacity(params.rounds - 1);
+    let mut xR: Vec<FpVar<F>> = Vec::with_capacity(params.rounds - 1);
+
+    for i in 0..params.rounds {
+        let t = if i == 0 {
+            k + xL_in
+        } else if i == params.rounds - 1 {
+            k + &xL[i - 1]
+        } else {
+            // c = round_keys[i - 1] = params.k + constant
+            let c = k + (params.round_keys[i - 1] - params.k);
+            k + &xL[i - 1] + c
+        };
+        let t2 = t.square()?;
+        let t4 = t2.square()?;
+        let t5 = t4 * &t;
+
+        if i < params.rounds - 1 {
+            let aux = if i == 0 { xR_in.clone() } else { xR[i - 1].clone() };
+            let xR_next = if i == 0 { xL_in.clone() } else { xL[i - 1].clone() };
+            xL.push(aux + t5);
+            xR.push(xR_next);
+        } else {
+            let xR_out = &xR[i - 1] + t5;
+            let xL_out = xL[i - 1].clone();
+            return Ok((xL_out, xR_out));
+        }
+    }
+
+    unreachable!()
+}
+
+/*
+    R1CS counterpart of MiMC::mimc_sponge, same absorb/squeeze schedule.
+*/
+pub fn mimc_sponge_gadget<F: PrimeField>(
+    inputs: &[FpVar<F>],
+    k: &FpVar<F>,
+    params: &MiMCParameters<F>,
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    assert_eq!(inputs.len(), params.num_inputs);
+
+    let mut S_L: Vec<FpVar<F>> = Vec::with_capacity(params.num_inputs + params.num_outputs - 1);
+    let mut S_R: Vec<FpVar<F>> = Vec::with_capacity(params.num_inputs + params.num_outputs - 1);
+
+    // Process inputs
+    for i in 0..params.num_inputs {
+        let (xL_in, xR_in) = if i == 0 {
+            (inputs[0].clone(), FpVar::zero())
+        } else {
+            (&S_L[i - 1] + &inputs[i], S_R[i - 1].clone())
+        };
+
+        let (xL_out, xR_out) = mimc_feistel_gadget(&xL_in, &xR_in, k, params)?;
+        S_L.push(xL_out);
+        S_R.push(xR_out);
+    }
+
+    let mut outs = vec![S_L[params.num_inputs - 1].clone()];
+
+    // Produce additional outputs if needed
+    for i in 0..params.num_outputs - 1 {
+        let xL_in = S_L[params.num_inputs + i - 1].clone();
+        let xR_in = S_R[params.num_inputs + i - 1].clone();
+        let (xL_out, xR_out) = mimc_feistel_gadget(&xL_in, &xR_in, k, params)?;
+        S_L.push(xL_out.clone());
+        S_R.push(xR_out);
+        outs.push(xL_out);
+    }
+
+    Ok(outs)
+}
+
+/*
+    Native Groth16 version of BindHashMimc.circom: proves that `hash` is the
+    MiMC sponge (helper::hash_range_nonce_mimc) of the range proof elements,
+    the commitment limbs and a private nonce.
+    Public inputs, in order: proof elements, commitment limbs, k, hash.
+    Unlike the Circom template, the computed hash is constrained to equal `hash`.
+*/
+#[derive(Clone)]
+pub struct HashRangeNonceCircuit<F: PrimeField> {
+    pub proof: Vec<F>,
+    pub commitment_limbs: Vec<F>,
+    pub nonce: Option<F>,
+    pub k: F,
+    pub hash: F,
+    pub params: MiMCParameters<F>,
+}
+
+impl<F: PrimeField> HashRangeNonceCircuit<F> {
+    // Builds the circuit and computes `hash` natively from the same inputs
+    pub fn new(proof: Vec<F>, commitment_limbs: Vec<F>, nonce: F, params: MiMCParameters<F>) -> Self {
+        let mut inputs = proof.clone();
+        inputs.extend(commitment_limbs.iter().cloned());
+        inputs.push(nonce);
+        let hash = mimc_sponge(&inputs, &params)[0];
+        HashRangeNonceCircuit { proof, commitment_limbs, nonce: Some(nonce), k: params.k, hash, params }
+    }
+
+    pub fn public_inputs(&self) -> Vec<F> {
+        let mut inputs = self.proof.clone();
+        inputs.extend(self.commitment_limbs.iter().cloned());
+        inputs.push(self.k);
+        inputs.push(self.hash);
+        inputs
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for HashRangeNonceCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let mut elements = Vec::with_capacity(self.params.num_inputs);
+        for p in self.proof.iter() {
+            elements.push(FpVar::new_input(cs.clone(), || Ok(*p))?);
+        }
+        for c in self.commitment_limbs.iter() {
+            elements.push(FpVar::new_input(cs.clone(), || Ok(*c))?);
+        }
+        let k = FpVar::new_input(cs.clone(), || Ok(self.k))?;
+        let hash = FpVar::new_input(cs.clone(), || Ok(self.hash))?;
+        elements.push(FpVar::new_witness(cs.clone(), || {
+            self.nonce.ok_or(SynthesisError::AssignmentMissing)
+        })?);
+
+        // only the first output is used, skip the squeezing rounds
+        let params = MiMCParameters { num_outputs: 1, ..self.params };
+        let outs = mimc_sponge_gadget(&elements, &k, &params)?;
+        outs[0].enforce_equal(&hash)
+    }
+}
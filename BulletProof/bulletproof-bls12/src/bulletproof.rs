@@ -23,6 +23,8 @@ use ark_bn254::Fr as FrBN;
 use ark_ff::UniformRand;
 use crate::helper::hash_range_nonce_mimc;
 use ark_bls12_381::{G1Projective, Fr as FrBLS};
+use accumulator_impl::replay::VerifierChallenge;
+use accumulator_impl::transcript::{new_transcript, TranscriptProtocol};
 use std::ops::Mul;
 use ark_serialize::CanonicalSerialize;
 use ark_ec::CurveGroup;
@@ -112,29 +114,41 @@ pub fn example_proof_1_vals() {
     Generate the Bulletproof Gen
 */
 pub fn get_bp_gens( gens_capacity: usize, party_capacity: usize) ->  BulletproofGens {
-    let bp_gens = BulletproofGens::new(gens_capacity,party_capacity); // usuall set to 64, and a power of 2^k (k can be any)
-    bp_gens
+    BulletproofGens::new(gens_capacity,party_capacity) // usuall set to 64, and a power of 2^k (k can be any)
 }
 
 /*
     Generating the PedersenGen Parameter for BulletProof
 */
 pub fn get_pc_gens() ->  PedersenGens {
-    let pc_gens = PedersenGens::default();
-    pc_gens
+    PedersenGens::default()
 }
 
 
 /*
     Generating the Transcript from a String
+    Starts from the crate-wide protocol and version tags of
+    accumulator_impl::transcript with the "bulletproof-range" proof type,
+    then the label; Merlin appends the statement (n, commitment) itself.
     Return A Transcript Object 
 */
 pub fn transcript_from_label(label: &str) -> Transcript {
-    let mut transcript = Transcript::new(b"default"); // temporary static label
+    let mut transcript = new_transcript(b"bulletproof-range");
     transcript.append_message(b"label", label.as_bytes());
     transcript
 }
 
+/*
+    Transcript bound to a verifier's challenge (verifier identity + nonce),
+    so a range proof made for one verifier session does not verify in another.
+    Prover and verifier must both use it.
+*/
+pub fn transcript_for_challenge(label: &str, challenge: &VerifierChallenge) -> Transcript {
+    let mut transcript = transcript_from_label(label);
+    transcript.append_session(challenge);
+    transcript
+}
+
 /*
     Return A RangeProof for a specific threshold given the secret values
 */
@@ -201,8 +215,12 @@ pub fn verify_range_proof (proof : RangeProof, verifier_transcript: &mut Transcr
 
 /*
     Doing the Bulletproof Off-Chain Verification and
-    return a tuple (nonce, hash, proof, commitment_bytes) for doing on-chain verification
+    return a tuple (nonce, hash, proof, commitment_bytes) for doing on-chain verification.
+    The nonce is the verifier's challenge as a field element, the same challenge
+    the transcript was made with (transcript_for_challenge), so the hash is tied
+    to that session instead of a random value chosen after the fact.
 */
+#[allow(clippy::too_many_arguments)]
 pub fn verifies_off_chain(
     proof: &RangeProof,
     verifier_transcript: &mut Transcript, 
@@ -212,6 +230,7 @@ pub fn verifies_off_chain(
     pc_gens: &PedersenGens, 
     commitment_secret: &G1Projective,
     mimc_params: & MiMCParameters<FrBN>,
+    challenge: &VerifierChallenge,
 ) -> (FrBN, FrBN, RangeProof, Vec<u8>) 
 {
     if verify_range_proof(proof.clone(), verifier_transcript, n, committed_value, bp_gens, pc_gens) {
@@ -222,9 +241,8 @@ pub fn verifies_off_chain(
             .serialize_uncompressed(&mut commitment_bytes)
             .unwrap();
 
-        let mut rng = rand::thread_rng();
-        let nonce: FrBN = FrBN::rand(&mut rng);
-        let hash_range_nonce_pr = hash_range_nonce_mimc(&proof, &nonce, &commitment_bytes,mimc_params); // FIX WITH MiMC
+        let nonce: FrBN = challenge.to_field::<FrBN>();
+        let hash_range_nonce_pr = hash_range_nonce_mimc(proof, &nonce, &commitment_bytes,mimc_params); // FIX WITH MiMC
 
         (nonce, hash_range_nonce_pr, proof.clone(), commitment_bytes)
     } else {
@@ -253,11 +271,13 @@ pub fn example_multi_message() {
     let number_msg = 4; // let this be power of 2 (to have success proof)
     let bp_gens = BulletproofGens::new(64,16);
 
-    let mut msg_values: Vec<u64> = Vec::new(); // the message // proof all the values in range 0 to 2^32
-    msg_values.push(19u64);
-    msg_values.push(15_112_004u64); // DOB
-    msg_values.push(21u64);          // Age
-    msg_values.push(75u64);          // Score
+    // the message // proof all the values in range 0 to 2^32
+    let msg_values: Vec<u64> = vec![
+        19u64,
+        15_112_004u64, // DOB
+        21u64,         // Age
+        75u64,         // Score
+    ];
    
     let mut blind_values = Vec::<Scalar>::new(); // the message 
     for _ in 0..number_msg {
@@ -1,14 +1,9 @@
-<<<<<<< HEAD
 pub mod helper;
 pub mod check;
+#[allow(non_snake_case)]
 pub mod MiMC;
+#[allow(non_snake_case)]
+pub mod MiMCGadget;
+#[allow(non_snake_case)]
 pub mod inputData;
 pub mod bulletproof;
-=======
-<<<<<<< HEAD
-pub mod inner;
-=======
-pub mod helper;
-pub mod check;
->>>>>>> origin/main
->>>>>>> main
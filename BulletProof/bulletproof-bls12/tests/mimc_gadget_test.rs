@@ -0,0 +1,86 @@
+use ark_bn254::{Bn254, Fr as FrBN};
+use ark_groth16::Groth16;
+use ark_r1cs_std::{alloc::AllocVar, fields::fp::FpVar, R1CSVar};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use ark_std::UniformRand;
+use std::str::FromStr;
+
+use bulletproofs_bls12::MiMC::{mimc_sponge, yeild_MiMC_Parameter};
+use bulletproofs_bls12::MiMCGadget::{mimc_sponge_gadget, HashRangeNonceCircuit};
+
+fn fr(s: &serde_json::Value) -> FrBN {
+    FrBN::from_str(s.as_str().unwrap()).unwrap()
+}
+
+// proof elements, commitment limbs, nonce and hash written by the Rust side
+// for BindHashMimc.circom
+fn circom_input() -> (Vec<FrBN>, Vec<FrBN>, FrBN, FrBN) {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/input.json");
+    let v: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    let proof = v["proof"].as_array().unwrap().iter().map(fr).collect();
+    let limbs = v["commitment_bytes"].as_array().unwrap().iter().map(fr).collect();
+    (proof, limbs, fr(&v["nonce"]), fr(&v["hash"]))
+}
+
+#[test]
+fn gadget_matches_native_sponge() {
+    let rng = &mut StdRng::seed_from_u64(0u64);
+    let k = FrBN::rand(rng);
+    let params = yeild_MiMC_Parameter(k, 3, 3);
+    let inputs: Vec<FrBN> = (0..3).map(|_| FrBN::rand(rng)).collect();
+
+    let cs = ConstraintSystem::<FrBN>::new_ref();
+    let input_vars: Vec<FpVar<FrBN>> = inputs
+        .iter()
+        .map(|x| FpVar::new_witness(cs.clone(), || Ok(*x)).unwrap())
+        .collect();
+    let k_var = FpVar::new_input(cs.clone(), || Ok(k)).unwrap();
+    let outs = mimc_sponge_gadget(&input_vars, &k_var, &params).unwrap();
+
+    let native = mimc_sponge(&inputs, &params);
+    assert_eq!(outs.len(), native.len());
+    for (out, expected) in outs.iter().zip(native.iter()) {
+        assert_eq!(out.value().unwrap(), *expected);
+    }
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn hash_range_nonce_circuit_reproduces_rust_hash() {
+    let (proof, limbs, nonce, hash) = circom_input();
+    let params = yeild_MiMC_Parameter(FrBN::from(0u64), proof.len() + limbs.len() + 1, 1);
+    let circuit = HashRangeNonceCircuit::new(proof, limbs, nonce, params);
+    assert_eq!(circuit.hash, hash);
+
+    let cs = ConstraintSystem::<FrBN>::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(cs.num_instance_variables(), 1 + 20 + 4 + 2);
+
+    let mut wrong = circuit;
+    wrong.hash += FrBN::from(1u64);
+    let cs = ConstraintSystem::<FrBN>::new_ref();
+    wrong.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn hash_range_nonce_groth16() {
+    let rng = &mut StdRng::seed_from_u64(1u64);
+    let k = FrBN::rand(rng);
+    let proof_elems: Vec<FrBN> = (0..20).map(|_| FrBN::rand(rng)).collect();
+    let limbs: Vec<FrBN> = (0..4).map(|_| FrBN::rand(rng)).collect();
+    let params = yeild_MiMC_Parameter(k, 25, 1);
+    let circuit = HashRangeNonceCircuit::new(proof_elems, limbs, FrBN::rand(rng), params);
+    let public_inputs = circuit.public_inputs();
+
+    let (pk, vk) = Groth16::<Bn254>::setup(circuit.clone(), rng).unwrap();
+    let proof = Groth16::<Bn254>::prove(&pk, circuit, rng).unwrap();
+    assert!(Groth16::<Bn254>::verify(&vk, &public_inputs, &proof).unwrap());
+
+    let mut tampered = public_inputs;
+    tampered[0] += FrBN::from(1u64);
+    assert!(!Groth16::<Bn254>::verify(&vk, &tampered, &proof).unwrap());
+}
@@ -0,0 +1,61 @@
+// Issuer and verifier side of the accumulator: issuing a credential with its
+// witness, removing an element (update_acc) and checking a witness.
+// Witness updates are in the witness_update bench.
+//
+//   cargo bench -p accumulator_impl --bench accumulator
+//   cargo run -p accumulator_impl --example bench_report > results.json
+
+use ark_bls12_381::Fr;
+use ark_std::UniformRand;
+use bbs_plus::prelude::SecretKey;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::thread_rng;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::helper::verify_witness;
+use accumulator_impl::issuer::Issuer;
+
+fn messages(count: usize) -> Vec<Fr> {
+    let mut rng = thread_rng();
+    (0..count).map(|_| Fr::rand(&mut rng)).collect()
+}
+
+fn issuance(c: &mut Criterion) {
+    let mut group = c.benchmark_group("accumulator/gen_witness_n_cred");
+    for count in [1, 5, 10] {
+        // the signature also covers Cx
+        let issuer = Issuer::with_message_count(1, count as u32 + 1);
+        let acc = ECAccumulator::new(issuer.get_g1());
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, &count| {
+            b.iter_batched(|| messages(count), |messages| issuer.gen_witness_n_cred(&acc, messages), BatchSize::SmallInput)
+        });
+    }
+    group.finish();
+}
+
+fn update_acc(c: &mut Criterion) {
+    let issuer = Issuer::new();
+    let sk = SecretKey(Fr::rand(&mut thread_rng()));
+    c.bench_function("accumulator/update_acc", |b| {
+        b.iter_batched(
+            || (ECAccumulator::new(issuer.get_g1()), Fr::rand(&mut thread_rng())),
+            |(mut acc, x)| {
+                acc.update_acc(&x, &sk);
+                acc
+            },
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn verify(c: &mut Criterion) {
+    let issuer = Issuer::new();
+    let acc = ECAccumulator::new(issuer.get_g1());
+    let cred = issuer.gen_witness_n_cred(&acc, messages(5));
+    c.bench_function("accumulator/verify_witness", |b| {
+        b.iter(|| verify_witness(acc.get_alpha(), cred.get_x_val(), cred.get_witness(), issuer.get_g2(), issuer.get_j()))
+    });
+}
+
+criterion_group!(benches, issuance, update_acc, verify);
+criterion_main!(benches);
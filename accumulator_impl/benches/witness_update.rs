@@ -0,0 +1,104 @@
+// Witness refresh after m revocations: one update at a time against the
+// batched suffix-product fold, and a population of holders serially against
+// par_batch_update_witnesses.
+//
+//   cargo bench -p accumulator_impl --bench witness_update
+
+use ark_bls12_381::{Fr, G1Projective};
+use ark_std::UniformRand;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::thread_rng;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::credential::{par_batch_update_witnesses, Credential};
+use accumulator_impl::issuer::Issuer;
+
+fn messages() -> Vec<Fr> {
+    let mut rng = thread_rng();
+    (0..5).map(|_| Fr::rand(&mut rng)).collect()
+}
+
+// m revocations of credentials outside the population, as (alpha_i, delta_i)
+fn update_log(issuer: &Issuer, acc: &mut ECAccumulator, m: usize) -> Vec<(G1Projective, Fr)> {
+    (0..m)
+        .map(|_| {
+            let stranger = issuer.gen_witness_n_cred(&*acc, messages());
+            let update = issuer.revoke_a_cred(acc, &stranger).unwrap();
+            (update.new_alpha, update.delta)
+        })
+        .collect()
+}
+
+fn single_holder(c: &mut Criterion) {
+    let mut group = c.benchmark_group("witness_update/single_holder");
+    for m in [16, 64, 256] {
+        let issuer = Issuer::new();
+        let mut acc = ECAccumulator::new(issuer.get_g1());
+        let cred = issuer.gen_witness_n_cred(&acc, messages());
+        let log = update_log(&issuer, &mut acc, m);
+
+        group.bench_with_input(BenchmarkId::new("one_at_a_time", m), &log, |b, log| {
+            b.iter_batched(
+                || cred.clone_secret(),
+                |mut cred| {
+                    for (alpha, delta) in log.iter() {
+                        cred.update_witness(*delta, *alpha);
+                    }
+                    cred
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("batched", m), &log, |b, log| {
+            b.iter_batched(
+                || cred.clone_secret(),
+                |mut cred| {
+                    assert!(cred.batch_update_witness(log));
+                    cred
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn population(c: &mut Criterion) {
+    let mut group = c.benchmark_group("witness_update/population");
+    group.sample_size(10);
+    let m = 32;
+    for n in [100, 1000] {
+        let issuer = Issuer::new();
+        let mut acc = ECAccumulator::new(issuer.get_g1());
+        let holders: Vec<Credential> = (0..n).map(|_| issuer.gen_witness_n_cred(&acc, messages())).collect();
+        let log = update_log(&issuer, &mut acc, m);
+        let copies = || holders.iter().map(|c| c.clone_secret()).collect::<Vec<_>>();
+
+        group.bench_with_input(BenchmarkId::new("serial", n), &log, |b, log| {
+            b.iter_batched(
+                copies,
+                |mut creds| {
+                    for cred in creds.iter_mut() {
+                        cred.batch_update_witness(log);
+                    }
+                    creds
+                },
+                BatchSize::LargeInput,
+            )
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", n), &log, |b, log| {
+            b.iter_batched(
+                copies,
+                |mut creds| {
+                    par_batch_update_witnesses(&mut creds, log);
+                    creds
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, single_holder, population);
+criterion_main!(benches);
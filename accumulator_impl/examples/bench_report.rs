@@ -0,0 +1,81 @@
+// Collects the latest criterion results of every bench in the workspace
+// (accumulator_impl, prover, bulletproofs_bls12) into one JSON array, one
+// entry per benchmark with its mean, confidence interval, median and standard
+// deviation in nanoseconds, so the result tables can be regenerated.
+//
+//   cargo bench --workspace
+//   cargo run -p accumulator_impl --example bench_report [target/criterion] > results.json
+
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize)]
+struct BenchResult {
+    id: String, // group/function/parameter, as criterion prints it
+    group: String,
+    function: Option<String>,
+    parameter: Option<String>,
+    mean_ns: f64,
+    mean_lower_ns: f64,
+    mean_upper_ns: f64,
+    median_ns: f64,
+    std_dev_ns: f64,
+}
+
+fn read_json(path: &Path) -> Result<Value, Box<dyn Error>> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+fn estimate(estimates: &Value, name: &str, field: &str) -> Result<f64, Box<dyn Error>> {
+    let value = match field {
+        "point_estimate" => &estimates[name]["point_estimate"],
+        bound => &estimates[name]["confidence_interval"][bound],
+    };
+    value.as_f64().ok_or_else(|| format!("estimates.json has no {}.{}", name, field).into())
+}
+
+// criterion keeps the last run of each benchmark in <id>/new/
+fn collect(dir: &Path, results: &mut Vec<BenchResult>) -> Result<(), Box<dyn Error>> {
+    let latest = dir.join("new");
+    if latest.join("benchmark.json").is_file() {
+        let benchmark = read_json(&latest.join("benchmark.json"))?;
+        let estimates = read_json(&latest.join("estimates.json"))?;
+        let text = |key: &str| benchmark[key].as_str().map(String::from);
+        results.push(BenchResult {
+            id: text("full_id").ok_or("benchmark.json has no full_id")?,
+            group: text("group_id").ok_or("benchmark.json has no group_id")?,
+            function: text("function_id"),
+            parameter: text("value_str"),
+            mean_ns: estimate(&estimates, "mean", "point_estimate")?,
+            mean_lower_ns: estimate(&estimates, "mean", "lower_bound")?,
+            mean_upper_ns: estimate(&estimates, "mean", "upper_bound")?,
+            median_ns: estimate(&estimates, "median", "point_estimate")?,
+            std_dev_ns: estimate(&estimates, "std_dev", "point_estimate")?,
+        });
+    }
+    let mut children: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_dir() && !path.ends_with("new") && !path.ends_with("base") && !path.ends_with("report"))
+        .collect();
+    children.sort();
+    for child in children {
+        collect(&child, results)?;
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let root = std::env::args().nth(1).map(PathBuf::from).unwrap_or_else(|| {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../target/criterion")
+    });
+    let mut results = Vec::new();
+    collect(&root, &mut results).map_err(|e| format!("{}: {}", root.display(), e))?;
+    if results.is_empty() {
+        return Err(format!("no criterion results under {}, run cargo bench first", root.display()).into());
+    }
+    println!("{}", serde_json::to_string_pretty(&results)?);
+    Ok(())
+}
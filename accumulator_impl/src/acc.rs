@@ -5,12 +5,15 @@ use rand::thread_rng;
 use num_bigint::{BigUint, RandBigInt};
 use bbs_plus::prelude::SecretKey;
 use std::ops::Mul;
+use ark_ff::Zero;
 
 
 
+#[derive(Clone)]
 pub struct ECAccumulator {
     pub alpha: G1Projective, // the accumulated value
-    pub deltas: Vec<Fr> // List of revoked x (revoked credential)
+    pub deltas: Vec<Fr>, // List of revoked x (revoked credential)
+    pub epoch: u64 // bumped on every change of alpha; presentations name the epoch they were made at
 }
 
 impl ECAccumulator {
@@ -25,7 +28,7 @@ impl ECAccumulator {
 
         let deltas = Vec::new();
 
-        ECAccumulator { alpha: alpha_0, deltas }
+        ECAccumulator { alpha: alpha_0, deltas, epoch: 0 }
     }
 
     pub fn update_acc(&mut self, x: &Fr, sk: &SecretKey<Fr>) {
@@ -36,6 +39,7 @@ impl ECAccumulator {
 
         self.deltas.push(*x);
         self.alpha = self.alpha.mul(inv);
+        self.epoch += 1;
     }
     
     pub fn gen_witness(&self, x: &Fr, sk: &SecretKey<Fr>) -> G1Projective {
@@ -44,6 +48,22 @@ impl ECAccumulator {
         self.alpha.mul(inv)
     }
 
+    // Adds (or re-admits) x: alpha' = alpha * (x + sk). Returns the old alpha,
+    // which holders need for Credential::update_witness_on_add
+    pub fn add(&mut self, x: &Fr, sk: &SecretKey<Fr>) -> G1Projective {
+        let old_alpha = self.alpha;
+        self.deltas.retain(|delta| delta != x);
+        self.alpha = self.alpha.mul(*x + sk.0);
+        self.epoch += 1;
+        old_alpha
+    }
+
+    // Adds several elements one after another. Returns (old alpha, x) for
+    // every step, in order, for Credential::batch_update_witness_on_add
+    pub fn add_batch(&mut self, xs: &[Fr], sk: &SecretKey<Fr>) -> Vec<(G1Projective, Fr)> {
+        xs.iter().map(|x| (self.add(x, sk), *x)).collect()
+    }
+
     pub fn get_alpha(&self) -> &G1Projective {
         &self.alpha
     }
@@ -51,5 +71,111 @@ impl ECAccumulator {
     pub fn get_deltas(&self) -> &Vec<Fr> {
         &self.deltas
     }
+
+    pub fn get_epoch(&self) -> u64 {
+        self.epoch
+    }
    
-}
\ No newline at end of file
+}
+
+
+
+// Universal accumulator (positive + negative proofs), used as a deny-list:
+// the revoked elements y_i are accumulated, V = P * prod(y_i + sk), and a holder
+// whose x is not in the set proves it with a non-membership witness (W, d):
+//   d = prod(y_i - x) != 0,   W = (V - P * d) * (x + sk)^-1
+//   check: e(W, g2 * x + j) * e(P * d, g2) == e(V, g2)
+pub struct UniversalAccumulator {
+    pub alpha: G1Projective, // V, the accumulated value
+    pub base: G1Projective, // P = g1 * u0, value of the empty set (public)
+    pub members: Vec<Fr> // accumulated (revoked) elements
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NonMemWitness {
+    pub w: G1Projective,
+    pub d: Fr,
+}
+
+impl UniversalAccumulator {
+    pub fn new(g1: &G1Projective) -> Self {
+        let q = BigUint::from(Fr::MODULUS);
+        let mut rng = thread_rng();
+        let u_0 = rng.gen_biguint_below(&q);
+        let u_0_fr = Fr::from_le_bytes_mod_order(&u_0.to_bytes_le());
+
+        let base = *g1 * u_0_fr;
+
+        UniversalAccumulator { alpha: base, base, members: Vec::new() }
+    }
+
+    // V' = V * (y + sk). Returns the old value, which holders need to update
+    pub fn add(&mut self, y: &Fr, sk: &SecretKey<Fr>) -> Option<G1Projective> {
+        if self.members.contains(y) {
+            return None;
+        }
+        let old_alpha = self.alpha;
+        self.alpha = self.alpha.mul(*y + sk.0);
+        self.members.push(*y);
+        Some(old_alpha)
+    }
+
+    // V' = V * (y + sk)^-1. Returns the new value, which holders need to update
+    pub fn delete(&mut self, y: &Fr, sk: &SecretKey<Fr>) -> Option<G1Projective> {
+        let pos = self.members.iter().position(|m| m == y)?;
+        let inv = (*y + sk.0).inverse()?;
+        self.members.swap_remove(pos);
+        self.alpha = self.alpha.mul(inv);
+        Some(self.alpha)
+    }
+
+    // Membership witness, same form as ECAccumulator::gen_witness
+    pub fn gen_witness(&self, x: &Fr, sk: &SecretKey<Fr>) -> Option<G1Projective> {
+        if !self.members.contains(x) {
+            return None;
+        }
+        let inv = (*x + sk.0).inverse()?;
+        Some(self.alpha.mul(inv))
+    }
+
+    // None if x is accumulated (d would be 0)
+    pub fn gen_non_mem_witness(&self, x: &Fr, sk: &SecretKey<Fr>) -> Option<NonMemWitness> {
+        let d = self.members.iter().fold(Fr::from(1u64), |acc, y| acc * (*y - x));
+        if d.is_zero() {
+            return None;
+        }
+        let inv = (*x + sk.0).inverse()?;
+        let w = (self.alpha - self.base.mul(d)).mul(inv);
+        Some(NonMemWitness { w, d })
+    }
+
+    pub fn get_alpha(&self) -> &G1Projective {
+        &self.alpha
+    }
+
+    pub fn get_base(&self) -> &G1Projective {
+        &self.base
+    }
+
+    pub fn get_members(&self) -> &Vec<Fr> {
+        &self.members
+    }
+}
+
+impl NonMemWitness {
+    // After y was added to the accumulator whose value was `old_alpha`:
+    //   d' = d * (y - x),   W' = W * (y - x) + V_old
+    pub fn update_on_add(&mut self, x: &Fr, y: &Fr, old_alpha: &G1Projective) {
+        let diff = *y - x;
+        self.d *= diff;
+        self.w = self.w.mul(diff) + old_alpha;
+    }
+
+    // After y was deleted, giving the new value `new_alpha`:
+    //   d' = d / (y - x),   W' = (W - V_new) / (y - x)
+    pub fn update_on_delete(&mut self, x: &Fr, y: &Fr, new_alpha: &G1Projective) {
+        let inv = (*y - x).inverse().expect("deleted element equals x");
+        self.d *= inv;
+        self.w = (self.w - new_alpha).mul(inv);
+    }
+}
@@ -1,25 +1,37 @@
-use ark_bls12_381::{Bls12_381, G1Projective};
+use ark_bls12_381::{Bls12_381, G1Affine, G1Projective};
 use bbs_plus::signature::SignatureG1;
 use ark_bls12_381::Fr;
-use zeroize::Zeroize;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 use std::ops::Mul;
-use ark_ff::Field;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{batch_inversion, Field};
 use std::ops::Neg;
-use ark_ff::One;
-#[derive(Clone)]
+use ark_ff::{One, Zero};
+use rayon::prelude::*;
+use crate::revocation::RevocationScheme;
+use crate::validity::ValidityPeriod;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use std::error::Error;
+use std::fmt;
+use crate::secret::Secret;
 
-pub struct Credential {
-    signature: SignatureG1<Bls12_381>, // signature = Sign(messsage, C(x_Val))
+// W is the revocation witness of the scheme the credential was issued under
+// (RevocationScheme::Witness); the default is the ECAccumulator witness.
+// x, r and the witness are wiped on drop; copies need clone_secret()
+pub struct Credential<W: Zeroize = G1Projective> {
+    signature: SignatureG1<Bls12_381>, // signature = Sign(messsage, x_Val)
     messages: Vec<Fr>,
-    x_val: Fr, // the secret values to prove if inside the accumulator
-    r: Fr, // secret for yeild the Commitment
-    witness: G1Projective
+    x_val: Secret<Fr>, // the secret values to prove if inside the accumulator
+    r: Secret<Fr>, // secret for yeild the Commitment
+    witness: Secret<W>,
+    key_id: u32, // issuer key version that signed the credential
+    validity: Option<ValidityPeriod>, // signed valid_from/valid_until, if the schema has them
 
 }
 
-impl Credential {
-    pub fn new(sig: SignatureG1<Bls12_381>, mes: Vec<Fr>, x_val: Fr, r:Fr, witness:G1Projective) -> Self {
-        Self {signature: sig, messages:mes, x_val:x_val, r:r, witness:witness}
+impl<W: Zeroize> Credential<W> {
+    pub fn new(sig: SignatureG1<Bls12_381>, mes: Vec<Fr>, x_val: Fr, r:Fr, witness:W, key_id: u32) -> Self {
+        Self {signature: sig, messages:mes, x_val:Secret::new(x_val), r:Secret::new(r), witness:Secret::new(witness), key_id, validity:None}
     }
 
     pub fn get_signature(&self) -> &SignatureG1<Bls12_381> {
@@ -31,59 +43,238 @@ impl Credential {
     }
 
     pub fn get_x_val(&self) -> &Fr {
-        &self.x_val
+        self.x_val.expose()
     }
 
-    pub fn get_witness(&self) -> &G1Projective {
-        &self.witness
+    pub fn get_witness(&self) -> &W {
+        self.witness.expose()
     }
 
     pub fn get_r(&self) -> &Fr {
-        &self.r
+        self.r.expose()
+    }
+
+    // What the issuer signed: the messages, then x
+    pub fn signed_messages(&self) -> Secret<Vec<Fr>> {
+        let mut signed = self.messages.clone();
+        signed.push(*self.x_val.expose());
+        Secret::new(signed)
+    }
+
+    pub fn get_key_id(&self) -> u32 {
+        self.key_id
+    }
+
+    pub fn get_validity(&self) -> Option<ValidityPeriod> {
+        self.validity
+    }
+
+    pub(crate) fn set_validity(&mut self, validity: Option<ValidityPeriod>) {
+        self.validity = validity;
     }
 
+    // Applies an update published by the scheme; false if it revoked this credential
+    pub fn update_revocation_witness<S: RevocationScheme<Witness = W>>(&mut self, params: &S::VerifyParams, update: &S::Update) -> bool {
+        S::update_witness(params, self.x_val.expose(), self.witness.expose_mut(), update)
+    }
+}
+
+// Signature, messages, x, r, witness, key id and validity, in that order
+impl<W: Zeroize + CanonicalSerialize> Credential<W> {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.signature.serialize_compressed(&mut bytes).unwrap();
+        self.messages.serialize_compressed(&mut bytes).unwrap();
+        self.x_val.expose().serialize_compressed(&mut bytes).unwrap();
+        self.r.expose().serialize_compressed(&mut bytes).unwrap();
+        self.witness.expose().serialize_compressed(&mut bytes).unwrap();
+        self.key_id.serialize_compressed(&mut bytes).unwrap();
+        let validity = self.validity.map(|v| (v.valid_from, v.valid_until));
+        validity.is_some().serialize_compressed(&mut bytes).unwrap();
+        let (from, until) = validity.unwrap_or((0, 0));
+        from.serialize_compressed(&mut bytes).unwrap();
+        until.serialize_compressed(&mut bytes).unwrap();
+        bytes
+    }
+}
+
+impl<W: Zeroize + CanonicalDeserialize> Credential<W> {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut reader = bytes;
+        let signature = SignatureG1::deserialize_compressed(&mut reader)?;
+        let messages = Vec::<Fr>::deserialize_compressed(&mut reader)?;
+        let x_val = Secret::new(Fr::deserialize_compressed(&mut reader)?);
+        let r = Secret::new(Fr::deserialize_compressed(&mut reader)?);
+        let witness = Secret::new(W::deserialize_compressed(&mut reader)?);
+        let key_id = u32::deserialize_compressed(&mut reader)?;
+        let has_validity = bool::deserialize_compressed(&mut reader)?;
+        let from = u64::deserialize_compressed(&mut reader)?;
+        let until = u64::deserialize_compressed(&mut reader)?;
+        if !reader.is_empty() {
+            return Err("trailing bytes after credential".into());
+        }
+        let validity = match has_validity {
+            true => Some(ValidityPeriod::new(from, until).ok_or("empty validity period")?),
+            false => None,
+        };
+        Ok(Self { signature, messages, x_val, r, witness, key_id, validity })
+    }
+}
+
+impl<W: Zeroize + Clone> Credential<W> {
+    // Explicit copy, secrets included
+    pub fn clone_secret(&self) -> Self {
+        Self {
+            signature: self.signature.clone(),
+            messages: self.messages.clone(),
+            x_val: self.x_val.clone_secret(),
+            r: self.r.clone_secret(),
+            witness: self.witness.clone_secret(),
+            key_id: self.key_id,
+            validity: self.validity,
+        }
+    }
+}
+
+impl<W: Zeroize> Credential<W> {
+    // Wipes the credential now instead of when it is dropped
+    pub fn destructor(&mut self) {
+        self.x_val.zeroize();
+        self.r.zeroize();
+        self.witness.zeroize();
+        self.messages.zeroize();
+        self.signature.zeroize();
+    }
+}
+
+// x, r and the witness wipe themselves; the signature and messages are wiped here
+impl<W: Zeroize> Drop for Credential<W> {
+    fn drop(&mut self) {
+        self.messages.zeroize();
+        self.signature.zeroize();
+    }
+}
+
+impl<W: Zeroize> ZeroizeOnDrop for Credential<W> {}
+
+impl<W: Zeroize> fmt::Debug for Credential<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credential")
+            .field("key_id", &self.key_id)
+            .field("messages", &self.messages.len())
+            .field("validity", &self.validity)
+            .field("x_val", &self.x_val)
+            .field("r", &self.r)
+            .field("witness", &self.witness)
+            .finish_non_exhaustive()
+    }
+}
+
+// ECAccumulator-only updates, working on the raw (alpha, delta) pairs
+impl Credential {
     pub fn update_witness(&mut self, delta: Fr, new_alpha: G1Projective) {
 
         // Equation 11 of Flamini, 2025
-        let old_witness = self.witness.clone();
+        let old_witness = *self.witness.expose();
         let term1 = old_witness + (new_alpha).neg();
-        let term2_inv = (delta - self.x_val).inverse().expect("delta - x invertible");
+        let term2_inv = (delta - self.x_val.expose()).inverse().expect("delta - x invertible");
         let new_witness = term1 * term2_inv;
-        self.witness = new_witness;
+        *self.witness.expose_mut() = new_witness;
     }
 
+    // Update for multiples alpha & witness - for long-outdated holder. Same
+    // result as update_witness for each (alpha_i, delta_i) in turn; false, with
+    // the witness unchanged, if one of them revokes this credential
     pub fn batch_update_witness(
         &mut self,
         updates: &[(G1Projective, Fr)],      // Vec of (alpha_i, delta_i)
-    ) {
-        // Update for multiples alpha & witness - for long-outdated holder
-        let mut updated_witness = self.witness.clone();
-
-        for (i, (alpha_i, _delta_i)) in updates.iter().enumerate() {
-            // Compute product of (delta_j - x) from j = i to m
-            let mut prod_inv = Fr::one();
-            for (_alpha_j, delta_j) in updates.iter().skip(i) {
-                let term = *delta_j - self.x_val;
-                prod_inv *= term.inverse().unwrap(); // assumes delta_j != x
-            }
+    ) -> bool {
+        self.apply_update_log(&UpdateLog::new(updates))
+    }
 
+    pub(crate) fn apply_update_log(&mut self, log: &UpdateLog) -> bool {
+        match log.fold(self.x_val.expose(), self.witness.expose()) {
+            Some(witness) => {
+                *self.witness.expose_mut() = witness;
+                true
+            }
+            None => false,
+        }
+    }
 
-            // In multiplicative pairing-friendly G1, we use scalar multiplication with -1:
-            let alpha_inv_point = alpha_i.mul(Fr::from(-1));
+    pub fn update_witness_on_add(&mut self, y: Fr, old_alpha: G1Projective) {
+        // alpha' = alpha * (y + sk)  =>  w' = w * (y - x) + alpha
+        // For y == x (the holder is re-admitted) this gives w' = alpha
+        let old_witness = *self.witness.expose();
+        *self.witness.expose_mut() = old_witness.mul(y - self.x_val.expose()) + old_alpha;
+    }
 
-            // Apply: w = w * alpha_i^{-1 * prod_inv}
-            let scalar = prod_inv; // scalar in Fr
-            updated_witness += alpha_inv_point.mul(scalar);
+    pub fn batch_update_witness_on_add(
+        &mut self,
+        updates: &[(G1Projective, Fr)],      // Vec of (old alpha_i, y_i), in order of addition
+    ) {
+        for (old_alpha, y) in updates.iter() {
+            self.update_witness_on_add(*y, *old_alpha);
         }
+    }
+}
+
+// A run of (alpha_i, delta_i) updates, prepared once for any number of
+// holders. Unrolling w_i = (w_{i-1} - alpha_i) / (delta_i - x) over m updates
+//
+//   w_m = w_0 * s_1 - sum_i alpha_i * s_i,   s_i = prod_{k >= i} 1 / (delta_k - x)
+//
+// so a holder needs one batched inversion, m suffix products and one MSM of
+// size m + 1, instead of the m^2 inversions of recomputing every product.
+pub(crate) struct UpdateLog {
+    alphas: Vec<G1Affine>,
+    deltas: Vec<Fr>,
+}
 
-        self.witness = updated_witness
+impl UpdateLog {
+    pub(crate) fn new(updates: &[(G1Projective, Fr)]) -> Self {
+        let alphas: Vec<G1Projective> = updates.iter().map(|(alpha, _)| *alpha).collect();
+        UpdateLog {
+            alphas: G1Projective::normalize_batch(&alphas),
+            deltas: updates.iter().map(|(_, delta)| *delta).collect(),
+        }
     }
 
-    pub fn destructor(&mut self) {
-        self.x_val.zeroize();
-        self.r.zeroize();
-        self.witness.zeroize();
-        self.messages.zeroize();
-        self.signature.zeroize();
+    // None if some delta_i is x, i.e. the log revokes x
+    fn fold(&self, x: &Fr, witness: &G1Projective) -> Option<G1Projective> {
+        if self.deltas.is_empty() {
+            return Some(*witness);
+        }
+        let mut inverses: Vec<Fr> = self.deltas.iter().map(|delta| *delta - x).collect();
+        if inverses.iter().any(|d| d.is_zero()) {
+            return None;
+        }
+        batch_inversion(&mut inverses);
+
+        // scalars[0] = s_1 for w_0, scalars[i] = -s_i for alpha_i
+        let mut scalars = vec![Fr::zero(); inverses.len() + 1];
+        let mut suffix = Fr::one();
+        for (i, inverse) in inverses.iter().enumerate().rev() {
+            suffix *= inverse;
+            scalars[i + 1] = -suffix;
+        }
+        scalars[0] = suffix;
+        let mut bases = Vec::with_capacity(scalars.len());
+        bases.push(witness.into_affine());
+        bases.extend_from_slice(&self.alphas);
+        let updated = G1Projective::msm(&bases, &scalars).expect("as many bases as scalars");
+
+        // the scalars are functions of x
+        inverses.zeroize();
+        scalars.zeroize();
+        Some(updated)
     }
 }
+
+// Credential::batch_update_witness for a whole population against one update
+// log, e.g. every credential a wallet or a test holds, across all cores.
+// Returns, per credential, false if the log revoked it
+pub fn par_batch_update_witnesses(creds: &mut [Credential], updates: &[(G1Projective, Fr)]) -> Vec<bool> {
+    let log = UpdateLog::new(updates);
+    creds.par_iter_mut().map(|cred| cred.apply_update_log(&log)).collect()
+}
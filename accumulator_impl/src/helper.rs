@@ -1,15 +1,6 @@
 use ark_ff::fields::PrimeField;
-<<<<<<< HEAD
 use ark_bls12_381::Fr as F;
 use ark_bls12_381::{Bls12_381, G1Affine, G2Affine, G1Projective, G2Projective, Fr, Fq2, Fq12, Fq};
-=======
-<<<<<<< HEAD
-use ark_bls12_381::{Bls12_381, G1Affine, G2Affine, G1Projective, G2Projective, Fr};
-=======
-use ark_bls12_381::Fr as F;
-use ark_bls12_381::{Bls12_381, G1Affine, G2Affine, G1Projective, G2Projective, Fr, Fq2, Fq12, Fq};
->>>>>>> origin/main
->>>>>>> main
 use ark_ec::pairing::{Pairing, PairingOutput};
 use ark_ec::{CurveGroup};
 use sha2::{Digest, Sha256}; // to map commitment -> Fr
@@ -17,11 +8,6 @@ use bbs_plus::setup::{SignatureParamsG1,KeypairG2};
 use rand::thread_rng;
 use std::ops::Mul;
 use ark_serialize::{CanonicalSerialize};
-<<<<<<< HEAD
-=======
-<<<<<<< HEAD
-=======
->>>>>>> main
 use ark_sponge::{
     poseidon::{PoseidonSponge, PoseidonConfig},
     CryptographicSponge,
@@ -31,10 +17,6 @@ use ark_std::Zero;
 use ark_ff::BigInteger;
 use ark_bn254::Fr as FrBN;
 
-<<<<<<< HEAD
-=======
->>>>>>> origin/main
->>>>>>> main
 
 pub fn compute_pairing(
     z: G1Affine,
@@ -51,14 +33,18 @@ pub fn compute_commitment_and_field(x: &Fr, g: &G1Projective, h: &G1Projective,
     // let mut rng = OsRng;
     // let r = Fr::rand(&mut rng);                 // blinding
     let cx_point = g.mul(x) + h.mul(r);        // Pedersen commitment in G1
+    let cx_fr = commitment_to_field(&cx_point);
+
+    (cx_point, cx_fr) // send cx_fr for verification
+}
 
+// Cx_fr = HashToFr(serialize(Cx_point))
+pub fn commitment_to_field(cx_point: &G1Projective) -> Fr {
     // Serialize the group point (to bytes) and hash to field
     let mut bytes = Vec::new();
     cx_point.into_affine().serialize_uncompressed(&mut bytes).unwrap(); // byte array (needs ark-serialize trait)
     let hash = Sha256::digest(&bytes);
-    let cx_fr = Fr::from_le_bytes_mod_order(&hash);
-
-    (cx_point, cx_fr) // send cx_fr for verification
+    Fr::from_le_bytes_mod_order(&hash)
 }
 
 pub fn verify_witness(alpha: &G1Projective,
@@ -74,16 +60,43 @@ pub fn verify_witness(alpha: &G1Projective,
         lhs == rhs
 }
 
+// Non-membership check for the universal accumulator (acc::UniversalAccumulator):
+//   d != 0  and  e(W, g2 * x + j) * e(P * d, g2) == e(V, g2)
+pub fn verify_non_mem_witness(alpha: &G1Projective,
+    base: &G1Projective,
+    x: &Fr,
+    witness: &G1Projective,
+    d: &Fr,
+    g2: &G2Projective,
+    j: &G2Projective
+
+    ) -> bool {
+        if d.is_zero() {
+            return false;
+        }
+        let g2xj = (*g2 * x) + j;
+        let lhs = Bls12_381::multi_pairing(
+            [witness.into_affine(), (*base * d).into_affine()],
+            [g2xj.into_affine(), g2.into_affine()],
+        );
+        let rhs = Bls12_381::pairing(alpha.into_affine(), g2.into_affine());
+        lhs == rhs
+}
+
 pub fn generate_bbs_param_keypair() -> (SignatureParamsG1::<Bls12_381>, KeypairG2::<Bls12_381>){
 
-    let mut rng = thread_rng();
     let _message_count = 6; // X and Cx
     // X:  [Name, DOB, Age, Score, Candidate_ID]
     // Cx: commitment of x
     // total message: 6 = 5 + 1
+    generate_bbs_param_keypair_for(_message_count)
+}
 
-    let _message_count = 6;
-    let params = SignatureParamsG1::<Bls12_381>::generate_using_rng(&mut rng, _message_count);
+// Same, sized for a schema: see CredentialSchema::message_count
+pub fn generate_bbs_param_keypair_for(message_count: u32) -> (SignatureParamsG1::<Bls12_381>, KeypairG2::<Bls12_381>){
+
+    let mut rng = thread_rng();
+    let params = SignatureParamsG1::<Bls12_381>::generate_using_rng(&mut rng, message_count);
 
     // Generate the KeyPair (SK-G1, PK-G2): sk: x , pk: g2^x
     let keypair_g2 = KeypairG2::<Bls12_381>::generate_using_rng(&mut rng, &params);
@@ -92,7 +105,7 @@ pub fn generate_bbs_param_keypair() -> (SignatureParamsG1::<Bls12_381>, KeypairG
     // sk = keypair_g2.secret_key;
     // pk = keypair_g2.public_key;
 
-    return (params, keypair_g2);
+    (params, keypair_g2)
 }
 
 
@@ -100,20 +113,11 @@ pub fn print_type_of<T>(_: &T) {
     println!("{}", std::any::type_name::<T>());
 }
 
-<<<<<<< HEAD
-=======
-<<<<<<< HEAD
-=======
->>>>>>> main
 pub fn sha256_to_words(digest: &[u8]) -> [u32; 8] {
     assert_eq!(digest.len(), 32);
     let mut out = [0u32; 8];
-    for i in 0..8 {
-        let base = i*4;
-        out[i] = ((digest[base] as u32) << 24)
-               | ((digest[base+1] as u32) << 16)
-               | ((digest[base+2] as u32) << 8)
-               |  (digest[base+3] as u32);
+    for (word, bytes) in out.iter_mut().zip(digest.chunks_exact(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
     }
     out
 }
@@ -167,7 +171,7 @@ pub fn create_poseidon_config() -> PoseidonConfig<F> {
     }
 }
 
-pub fn create_poseidon_config_FrBN() -> PoseidonConfig<FrBN> {
+pub fn create_poseidon_config_fr_bn() -> PoseidonConfig<FrBN> {
     let full_rounds = 8;
     let partial_rounds = 57;
     let alpha = 5;
@@ -270,7 +274,7 @@ pub fn poseidon_hash_g1(g: &G1Projective, pos_config: &PoseidonConfig<Fr>) -> Fr
 
     // Initialize sponge for the scalar field Fr
     // let pos_config = create_poseidon_config(); 
-    let mut sponge = PoseidonSponge::<Fr>::new(&pos_config);
+    let mut sponge = PoseidonSponge::<Fr>::new(pos_config);
 
     // Absorb field elements
     sponge.absorb(&x);
@@ -288,7 +292,7 @@ pub fn poseidon_hash_g2(g: &G2Projective, pos_config: &PoseidonConfig<Fr>) -> Fr
 
     // Initialize sponge for the scalar field Fr
     // let pos_config = create_poseidon_config(); 
-    let mut sponge = PoseidonSponge::<Fr>::new(&pos_config);
+    let mut sponge = PoseidonSponge::<Fr>::new(pos_config);
 
     // Absorb field elements
     sponge.absorb(&x.c0);
@@ -333,8 +337,7 @@ pub fn map_bls_to_bn254(x_bls_bytes: &[u8]) -> FrBN {
     let mut hasher = Sha256::new();
     hasher.update(x_bls_bytes);
     let hash_bytes = hasher.finalize();
-    let x_bn = FrBN::from_le_bytes_mod_order(&hash_bytes);
-    x_bn
+    FrBN::from_le_bytes_mod_order(&hash_bytes)
 }
 
 
@@ -342,9 +345,4 @@ pub fn fr_to_bytes(f: Fr) -> Vec<u8> {
     let mut bytes = Vec::new();
     f.serialize_uncompressed(&mut bytes).unwrap(); // serialize into canonical byte form
     bytes
-<<<<<<< HEAD
-}
-=======
 }
->>>>>>> origin/main
->>>>>>> main
@@ -3,21 +3,24 @@ use ark_bls12_381::Fr;
 use ark_bls12_381::{G1Projective, G2Projective, Bls12_381};
 use bbs_plus::prelude::*;
 use crate::helper::*;
-<<<<<<< HEAD
+use crate::acc::ECAccumulator;
+use crate::keystore::IssuerPublicKeys;
+use crate::replay::VerifierChallenge;
+use crate::revocation::RevocationScheme;
+use crate::secret::Secret;
+use crate::schema::CredentialSchema;
+use crate::validity::{ExpiryProof, ValidityError};
+use crate::transcript::{new_transcript, session_transcript, MEMBERSHIP_PROOF};
+use crate::zkp::{gen_zkp, ZKProof};
 use rand::thread_rng;
 use ark_std::UniformRand;
 use std::ops::Mul;
-=======
-<<<<<<< HEAD
-=======
-use rand::thread_rng;
-use ark_std::UniformRand;
-use std::ops::Mul;
->>>>>>> origin/main
->>>>>>> main
 
-pub struct Holder {
-    cred: Credential,
+// Cw, Csigma, Crho, Cx, x*sigma, x*rho, sigma, rho
+pub type ZkpCommitments = (G1Projective, G1Projective, G1Projective, G1Projective, Secret<Fr>, Secret<Fr>, Secret<Fr>, Secret<Fr>);
+
+pub struct Holder<S: RevocationScheme = ECAccumulator> {
+    cred: Credential<S::Witness>,
     // Issuer sign [C(x_val), message] - C(x_val) : Perdersent Commitment
 
 }
@@ -25,76 +28,118 @@ pub struct Holder {
 impl Holder {
     pub fn new(cred: Credential) -> Self {
         
-        Self {cred: cred}
+        Self {cred}
     }
 
-    pub fn get_cred(&self) -> &Credential {
-        &self.cred
-    }
-
-
-    // g, h is the public parameter from issuer
+    // pk, param are the issuer's signing keys
     pub fn verify_mem(&self ,
-        g: &G1Projective, 
-        h: &G1Projective, 
         pk: &PublicKeyG2<Bls12_381>,
         param: &SignatureParamsG1<Bls12_381>,
         alpha: &G1Projective,
         g2: &G2Projective,
         j: &G2Projective,
     ) -> bool {
-        let (_cx_point, cx_fr) = compute_commitment_and_field(&self.cred.get_x_val(),g, h, &self.cred.get_r());
-        let mut message = self.cred.get_message().clone();  // Vec<Fr>
-        message.push(cx_fr);
-        let is_valid = self.cred.get_signature().verify(&message, pk.clone(), param.clone()).is_ok();
-        if is_valid {
-            println!("✅ Signature is valid over X || Cx");
-        } else {
-            println!("❌ Signature verification failed");
-            return false
-        }
-
-        return verify_witness(alpha, &self.cred.get_x_val(), self.cred.get_witness(), g2, j);
-
+        self.verify_signature(pk, param)
+            && verify_witness(alpha, self.cred.get_x_val(), self.cred.get_witness(), g2, j)
     }
 
-    pub fn batch_update_witness(&mut self, updates: &[(G1Projective, Fr)]) {
-        self.cred.batch_update_witness(updates);
+    // false if one of the updates revoked this holder
+    pub fn batch_update_witness(&mut self, updates: &[(G1Projective, Fr)]) -> bool {
+        self.cred.batch_update_witness(updates)
     }
 
-    pub fn update_witness(&mut self, delta: Fr, new_alpha: G1Projective) {
-        self.cred.update_witness(delta, new_alpha);
+    pub fn update_witness_on_add(&mut self, y: Fr, old_alpha: G1Projective) {
+        self.cred.update_witness_on_add(y, old_alpha);
     }
-<<<<<<< HEAD
-=======
-<<<<<<< HEAD
-}
-=======
->>>>>>> main
 
+    pub fn batch_update_witness_on_add(&mut self, updates: &[(G1Projective, Fr)]) {
+        self.cred.batch_update_witness_on_add(updates);
+    }
 
-    pub fn prepare_ZKP(&self, 
+    pub fn prepare_zkp(&self, 
         g: &G1Projective, 
         h: &G1Projective, 
         k: &G1Projective,
         z: &G1Projective
-    ) -> (G1Projective, G1Projective, G1Projective, G1Projective, Fr, Fr, Fr, Fr) {
+    ) -> ZkpCommitments {
+        // the blinding factors and their products with x are as secret as x
         let mut rng = thread_rng();
-        let sigma = Fr::rand(& mut rng);
-        let rho = Fr::rand(&mut rng);
+        let sigma = Secret::new(Fr::rand(& mut rng));
+        let rho = Secret::new(Fr::rand(&mut rng));
 
-        let Cw = self.cred.get_witness() + z.mul(sigma + rho);
-        let Csigma = h.mul(sigma);
-        let Crho = k.mul(rho);
-        let (Cx_point, Cx_fr) = compute_commitment_and_field(&self.cred.get_x_val(),g, h, &self.cred.get_r());
-        let x_times_sig = *self.cred.get_x_val() * sigma;
-        let x_times_rho = *self.cred.get_x_val() * rho;
+        let c_w = self.cred.get_witness() + z.mul(*sigma.expose() + rho.expose());
+        let c_sigma = h.mul(sigma.expose());
+        let c_rho = k.mul(rho.expose());
+        let (c_x, _) = compute_commitment_and_field(self.cred.get_x_val(), g, h, self.cred.get_r());
+        let x_times_sig = Secret::new(*self.cred.get_x_val() * sigma.expose());
+        let x_times_rho = Secret::new(*self.cred.get_x_val() * rho.expose());
 
-        (Cw, Csigma, Crho, Cx_point, x_times_sig, x_times_rho, sigma, rho)
+        (c_w, c_sigma, c_rho, c_x, x_times_sig, x_times_rho, sigma, rho)
     }
-<<<<<<< HEAD
-}   
-=======
-}   
->>>>>>> origin/main
->>>>>>> main
+
+    // Zero-knowledge membership proof against the accumulator at epoch,
+    // bound to the verifier's challenge; check it with zkp::verify_zkp on
+    // transcript::session_transcript(MEMBERSHIP_PROOF, keys, epoch, challenge)
+    pub fn prove_membership(&self,
+        keys: &IssuerPublicKeys,
+        acc: &ECAccumulator,
+        challenge: &VerifierChallenge,
+    ) -> (G1Projective, ZKProof) {
+        let mut transcript = session_transcript(MEMBERSHIP_PROOF, keys, acc.get_epoch(), challenge);
+        gen_zkp(keys, acc.get_alpha(), self.cred.get_x_val(), self.cred.get_r(), self.cred.get_witness(), &mut transcript)
+    }
+}
+
+impl<S: RevocationScheme> Holder<S> {
+    // Holder::new is kept for the ECAccumulator; other backends use this one
+    pub fn from_cred(cred: Credential<S::Witness>) -> Self {
+        Self {cred}
+    }
+
+    pub fn get_cred(&self) -> &Credential<S::Witness> {
+        &self.cred
+    }
+
+    // pk, param are the issuer's signing keys, state/params those of the revocation scheme
+    pub fn verify_cred(&self ,
+        pk: &PublicKeyG2<Bls12_381>,
+        param: &SignatureParamsG1<Bls12_381>,
+        state: &S::PublicState,
+        params: &S::VerifyParams,
+    ) -> bool {
+        if !self.verify_signature(pk, param) {
+            return false
+        }
+        let transcript = new_transcript(MEMBERSHIP_PROOF);
+        S::prove(params, state, self.cred.get_x_val(), self.cred.get_witness(), &mut transcript.clone())
+            .is_some_and(|proof| S::verify(params, state, &proof, &mut transcript.clone()))
+    }
+
+    fn verify_signature(&self,
+        pk: &PublicKeyG2<Bls12_381>,
+        param: &SignatureParamsG1<Bls12_381>,
+    ) -> bool {
+        let message = self.cred.signed_messages();  // Vec<Fr>
+        self.cred.get_signature().verify(message.expose(), pk.clone(), param.clone()).is_ok()
+    }
+
+    // false if the update revoked this holder
+    pub fn update_witness(&mut self, params: &S::VerifyParams, update: &S::Update) -> bool {
+        self.cred.update_revocation_witness::<S>(params, update)
+    }
+
+    // Zero-knowledge proof that now lies in the credential's validity period,
+    // now being the verifier's reference time (see validity.rs)
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_validity(&self,
+        schema: &CredentialSchema,
+        pk: &PublicKeyG2<Bls12_381>,
+        param: &SignatureParamsG1<Bls12_381>,
+        g: &G1Projective,
+        h: &G1Projective,
+        now: u64,
+        challenge: &VerifierChallenge,
+    ) -> Result<ExpiryProof, ValidityError> {
+        ExpiryProof::new(&self.cred, schema, pk, param, g, h, now, challenge)
+    }
+}
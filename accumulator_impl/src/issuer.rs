@@ -3,16 +3,24 @@ use bbs_plus::prelude::*;
 use crate::helper::*;
 use crate::acc::*;
 use crate::credential::*;
+use crate::revocation::{RevocationError, RevocationScheme};
+use crate::keystore::IssuerPublicKeys;
+use crate::schema::{AttributeValue, CredentialSchema, SchemaError};
+use crate::validity::{validity_from_messages, ValidityPeriod, VALID_FROM, VALID_UNTIL};
+use std::collections::BTreeMap;
 use rand::thread_rng;
 use ark_std::UniformRand;
 use bbs_plus::setup::SecretKey;
+use dock_crypto_utils::signature::MultiMessageSignatureParams;
 use ark_ec::Group;
 use ark_ff::BigInteger;
 use zeroize::Zeroize;
+use crate::secret::Secret;
 
 
 pub struct Issuer {
-    sk: SecretKey<Fr>,
+    key_id: u32, // version of the keys, recorded in every credential issued
+    sk: Secret<SecretKey<Fr>>, // wiped on drop
     pk: PublicKeyG2<Bls12_381>,
     sig_param: SignatureParamsG1::<Bls12_381>,
     // Parameter for accumulator (public)
@@ -29,9 +37,29 @@ pub struct Issuer {
 
 }
 
+impl Default for Issuer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Issuer {
     pub fn new() -> Self {
-        let (param, keypair_g2)  = generate_bbs_param_keypair();
+        Self::with_key_id(1)
+    }
+
+    // Fresh keys under the given version, see IssuerKeystore::rotate
+    pub fn with_key_id(key_id: u32) -> Self {
+        Self::with_message_count(key_id, 6)
+    }
+
+    // Keys for credentials of a schema
+    pub fn for_schema(schema: &CredentialSchema) -> Self {
+        Self::with_message_count(1, schema.message_count() as u32)
+    }
+
+    pub fn with_message_count(key_id: u32, message_count: u32) -> Self {
+        let (param, keypair_g2)  = generate_bbs_param_keypair_for(message_count);
         let g1 = G1Projective::generator();
         let g2 = G2Projective::generator();
         let j = g2 * keypair_g2.secret_key.0;
@@ -41,11 +69,37 @@ impl Issuer {
         let h = G1Projective::rand(&mut rng);
         let k = G1Projective::rand(&mut rng);
         let z = G1Projective::rand(&mut rng);
-        Issuer{sk: keypair_g2.secret_key.clone(), pk: keypair_g2.public_key.clone(), sig_param:param,
-        g1: g1, g2: g2, j: j,
+        Issuer{key_id, sk: Secret::new(keypair_g2.secret_key.clone()), pk: keypair_g2.public_key.clone(), sig_param:param,
+        g1, g2, j,
         g: Some(g), h: Some(h), k: Some(k), z: Some(z)}
     }
 
+    pub(crate) fn from_keys(public: IssuerPublicKeys, sk: SecretKey<Fr>) -> Self {
+        Issuer{key_id: public.key_id, sk: Secret::new(sk), pk: public.pk, sig_param: public.sig_param,
+        g1: public.g1, g2: public.g2, j: public.j,
+        g: Some(public.g), h: Some(public.h), k: Some(public.k), z: Some(public.z)}
+    }
+
+    pub(crate) fn get_sk(&self) -> &SecretKey<Fr> {
+        self.sk.expose()
+    }
+
+    pub fn get_key_id(&self) -> u32 {
+        self.key_id
+    }
+
+    // Everything a verifier needs for credentials of this key version
+    pub fn public_keys(&self) -> IssuerPublicKeys {
+        let (g, h, k, z) = self.get_g_h_k_z();
+        IssuerPublicKeys {
+            key_id: self.key_id,
+            pk: self.pk.clone(),
+            sig_param: self.sig_param.clone(),
+            g1: self.g1, g2: self.g2, j: self.j,
+            g: *g, h: *h, k: *k, z: *z,
+        }
+    }
+
     pub fn get_pk(&self) -> &PublicKeyG2<Bls12_381> {
         &self.pk
     }
@@ -74,13 +128,14 @@ impl Issuer {
         )
     }
     
-    pub fn gen_witness_n_cred(&self, accummulator: &ECAccumulator, messages: Vec<Fr>) -> Credential {
+    // Works with any revocation backend; the ECAccumulator is the default one
+    pub fn gen_witness_n_cred<S: RevocationScheme>(&self, scheme: &S, messages: Vec<Fr>) -> Credential<S::Witness> {
 
         // Uniform sampling the secret values x in D = Z_p* / {sk} & the X as well
         let mut rng = thread_rng();
         let x = loop {
             let candidate = Fr::rand(&mut rng);
-                let sum = candidate + self.sk.0;
+                let sum = candidate + self.sk.expose().0;
                 if !sum.0.is_zero() {
                     break candidate;
                 }
@@ -88,51 +143,83 @@ impl Issuer {
         
         let r = loop {
             let candidate = Fr::rand(&mut rng);
-                let sum = candidate + self.sk.0;
+                let sum = candidate + self.sk.expose().0;
                 if !sum.0.is_zero() {
                     break candidate;
                 }
         };
 
-        let (_cx, cx_fr) = compute_commitment_and_field(
-            &x,
-            self.g.as_ref().expect("g not set"),
-            self.h.as_ref().expect("h not set"),
-            &r
-        );
         // Generating the witness
-        let witness_x_t = &accummulator.gen_witness(&x, &self.sk);
+        let witness_x_t = scheme.gen_witness(&x, S::issuer_key(self.sk.expose())).expect("fresh x is not revoked");
 
-        // Sign the X || Cx
-        let mut msg_and_cx = messages.clone();
+        // Sign the X || x
+        let mut msg_and_x = Secret::new(messages.clone());
         let mut rng = thread_rng();
-        msg_and_cx.push(cx_fr); // X || cx
+        msg_and_x.expose_mut().push(x); // X || x
         let signature = SignatureG1::new(
             &mut rng,
-            &msg_and_cx,       // your Vec<Fr>
-            &self.sk,          // &SecretKey<Fr>
+            msg_and_x.expose(),       // your Vec<Fr>
+            self.sk.expose(),          // &SecretKey<Fr>
             &self.sig_param,    // &SignatureParamsG1<Bls12_381>
             
         ).expect("failed to sign");
 
         // Return the credential contain the witness
-        let credential = Credential::new(
+        Credential::new(
             signature,
             messages,
             x,
             r,
-            *witness_x_t
-        );
+            witness_x_t,
+            self.key_id
+        )
+    }
 
-        return credential;
+    // Encodes the attribute values with the schema (schema id first) and issues
+    pub fn issue_with_schema<S: RevocationScheme>(&self, scheme: &S, schema: &CredentialSchema, values: &BTreeMap<String, AttributeValue>) -> Result<Credential<S::Witness>, SchemaError> {
+        let supported = self.sig_param.supported_message_count();
+        if supported != schema.message_count() {
+            return Err(SchemaError::MessageCountMismatch { expected: schema.message_count(), got: supported });
+        }
+        let messages = schema.encode(values)?;
+        let validity = validity_from_messages(schema, &messages);
+        let mut cred = self.gen_witness_n_cred(scheme, messages);
+        cred.set_validity(validity);
+        Ok(cred)
+    }
 
+    // Same, for a schema with validity; the period fills valid_from/valid_until
+    pub fn issue_with_validity<S: RevocationScheme>(&self, scheme: &S, schema: &CredentialSchema, values: &BTreeMap<String, AttributeValue>, period: ValidityPeriod) -> Result<Credential<S::Witness>, SchemaError> {
+        let mut values = values.clone();
+        values.insert(VALID_FROM.to_string(), AttributeValue::Integer(period.valid_from));
+        values.insert(VALID_UNTIL.to_string(), AttributeValue::Integer(period.valid_until));
+        self.issue_with_schema(scheme, schema, &values)
     }
 
-    pub fn revoke_a_cred(&self, accumulator: &mut ECAccumulator, cred: &Credential) -> Fr{
-        accumulator.update_acc(cred.get_x_val(), &self.sk);
-        return cred.get_x_val().clone();
+    // Returns the update every other holder applies with Holder::update_witness
+    pub fn revoke_a_cred<S: RevocationScheme>(&self, scheme: &mut S, cred: &Credential<S::Witness>) -> Result<S::Update, RevocationError> {
+        self.revoke_handle(scheme, cred.get_x_val())
+    }
+
+    // Same, from the revocation handle x alone (e.g. kept in a CredentialRegistry)
+    pub fn revoke_handle<S: RevocationScheme>(&self, scheme: &mut S, x: &Fr) -> Result<S::Update, RevocationError> {
+        scheme.revoke(x, S::issuer_key(self.sk.expose()))
     }
     
+    // Suspended credentials can be reinstated: x is added back to the accumulator.
+    // Returns the old alpha, which is also the reinstated holder's new witness
+    pub fn reinstate_a_cred(&self, accumulator: &mut ECAccumulator, cred: &Credential) -> G1Projective {
+        self.reinstate_handle(accumulator, cred.get_x_val())
+    }
+
+    pub fn reinstate_handle(&self, accumulator: &mut ECAccumulator, x: &Fr) -> G1Projective {
+        accumulator.add(x, self.sk.expose())
+    }
+
+    pub fn add_to_acc(&self, accumulator: &mut ECAccumulator, xs: &[Fr]) -> Vec<(G1Projective, Fr)> {
+        accumulator.add_batch(xs, self.sk.expose())
+    }
+
     pub fn destructor(&mut self) {
         self.sk.zeroize(); // wipe-out the values of sk when done-session from memory to prevenet key exposure
     }
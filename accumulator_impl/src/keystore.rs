@@ -0,0 +1,288 @@
+// Encrypted on-disk store for issuer keys, so a restarted issuer keeps the
+// keys its credentials were signed with. Every key version is one record in
+// `<dir>/issuer_keys.json`:
+//
+//   public   pk, BBS+ parameters, accumulator and Pedersen generators, in the
+//            clear so verifiers can load them without the passphrase
+//   secret   sk sealed with XChaCha20-Poly1305 under a key derived from the
+//            passphrase with Argon2id; the key id and public part are bound
+//            in as associated data
+//
+// Rotation adds a new version and makes it current. Old versions stay, so
+// credentials signed before the rotation still verify (Credential::get_key_id
+// names the version) and can still be revoked with load_version.
+
+use ark_bls12_381::{Bls12_381, Fr, G1Projective, G2Projective};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use argon2::{Algorithm, Argon2, Params, Version};
+use bbs_plus::prelude::*;
+use dock_crypto_utils::signature::MultiMessageSignatureParams;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::{thread_rng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroize;
+
+use crate::issuer::Issuer;
+
+pub const KEYSTORE_FILE: &str = "issuer_keys.json";
+
+// Argon2id cost; the default is the OWASP recommendation (19 MiB, 2 passes)
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub m_cost: u32, // KiB
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams { m_cost: 19 * 1024, t_cost: 2, p_cost: 1 }
+    }
+}
+
+// Public half of an issuer key version
+#[derive(Clone, Debug, PartialEq)]
+pub struct IssuerPublicKeys {
+    pub key_id: u32,
+    pub pk: PublicKeyG2<Bls12_381>,
+    pub sig_param: SignatureParamsG1<Bls12_381>,
+    pub g1: G1Projective,
+    pub g2: G2Projective,
+    pub j: G2Projective,
+    pub g: G1Projective,
+    pub h: G1Projective,
+    pub k: G1Projective,
+    pub z: G1Projective,
+}
+
+impl IssuerPublicKeys {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.pk.serialize_compressed(&mut bytes).unwrap();
+        self.sig_param.serialize_compressed(&mut bytes).unwrap();
+        for p in [self.g1, self.g, self.h, self.k, self.z] {
+            p.serialize_compressed(&mut bytes).unwrap();
+        }
+        for p in [self.g2, self.j] {
+            p.serialize_compressed(&mut bytes).unwrap();
+        }
+        bytes
+    }
+
+    // Issuer id used by holders (hex SHA-256 of the public keys); every key
+    // version has its own
+    pub fn fingerprint(&self) -> String {
+        hex::encode(Sha256::digest(self.to_bytes()))
+    }
+
+    pub fn from_bytes(key_id: u32, bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        let mut reader = bytes;
+        let pk = PublicKeyG2::deserialize_compressed(&mut reader)?;
+        let sig_param = SignatureParamsG1::deserialize_compressed(&mut reader)?;
+        let mut g1s = Vec::new();
+        for _ in 0..5 {
+            g1s.push(G1Projective::deserialize_compressed(&mut reader)?);
+        }
+        let g2 = G2Projective::deserialize_compressed(&mut reader)?;
+        let j = G2Projective::deserialize_compressed(&mut reader)?;
+        if !reader.is_empty() {
+            return Err("trailing bytes after issuer public keys".into());
+        }
+        Ok(IssuerPublicKeys { key_id, pk, sig_param, g1: g1s[0], g2, j, g: g1s[1], h: g1s[2], k: g1s[3], z: g1s[4] })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct KeyRecord {
+    key_id: u32,
+    created: u64, // unix seconds
+    public: String, // hex of IssuerPublicKeys::to_bytes
+    kdf: KdfParams,
+    salt: String,
+    nonce: String,
+    secret: String, // hex of the sealed sk
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    current: Option<u32>,
+    keys: Vec<KeyRecord>,
+}
+
+pub(crate) fn derive_key(passphrase: &str, salt: &[u8], kdf: &KdfParams) -> Result<[u8; 32], Box<dyn Error>> {
+    let params = Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, Some(32)).map_err(|e| e.to_string())?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+fn associated_data(key_id: u32, public: &[u8]) -> Vec<u8> {
+    let mut aad = b"vac-issuer-key".to_vec();
+    aad.extend_from_slice(&key_id.to_le_bytes());
+    aad.extend_from_slice(public);
+    aad
+}
+
+pub struct IssuerKeystore {
+    dir: PathBuf,
+    kdf: KdfParams,
+}
+
+impl IssuerKeystore {
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        Self::with_kdf(dir, KdfParams::default())
+    }
+
+    // KDF cost for versions saved from now on; stored ones keep their own
+    pub fn with_kdf(dir: impl AsRef<Path>, kdf: KdfParams) -> Result<Self, Box<dyn Error>> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(IssuerKeystore { dir: dir.as_ref().to_path_buf(), kdf })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn read_manifest(&self) -> Result<Manifest, Box<dyn Error>> {
+        let path = self.dir.join(KEYSTORE_FILE);
+        if !path.exists() {
+            return Ok(Manifest::default());
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    // Written next to the manifest and renamed over it, never half-written
+    fn write_manifest(&self, manifest: &Manifest) -> Result<(), Box<dyn Error>> {
+        let tmp = self.dir.join(format!("{}.tmp", KEYSTORE_FILE));
+        std::fs::write(&tmp, serde_json::to_string_pretty(manifest)?)?;
+        std::fs::rename(tmp, self.dir.join(KEYSTORE_FILE))?;
+        Ok(())
+    }
+
+    fn record(manifest: &Manifest, key_id: u32) -> Result<&KeyRecord, Box<dyn Error>> {
+        manifest
+            .keys
+            .iter()
+            .find(|r| r.key_id == key_id)
+            .ok_or_else(|| format!("no issuer key with id {}", key_id).into())
+    }
+
+    pub fn key_ids(&self) -> Result<Vec<u32>, Box<dyn Error>> {
+        Ok(self.read_manifest()?.keys.iter().map(|r| r.key_id).collect())
+    }
+
+    pub fn current_key_id(&self) -> Result<Option<u32>, Box<dyn Error>> {
+        Ok(self.read_manifest()?.current)
+    }
+
+    // Stores the issuer's keys under its key id; the newest version becomes current
+    pub fn save(&self, issuer: &Issuer, passphrase: &str) -> Result<(), Box<dyn Error>> {
+        let mut manifest = self.read_manifest()?;
+        let key_id = issuer.get_key_id();
+        if manifest.keys.iter().any(|r| r.key_id == key_id) {
+            return Err(format!("issuer key {} is already stored", key_id).into());
+        }
+
+        let public = issuer.public_keys().to_bytes();
+        let mut rng = thread_rng();
+        let mut salt = [0u8; 16];
+        let mut nonce = [0u8; 24];
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut nonce);
+
+        let mut key = derive_key(passphrase, &salt, &self.kdf)?;
+        let mut sk_bytes = Vec::new();
+        issuer.get_sk().serialize_compressed(&mut sk_bytes)?;
+        let aad = associated_data(key_id, &public);
+        let sealed = XChaCha20Poly1305::new(Key::from_slice(&key))
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: &sk_bytes, aad: &aad })
+            .map_err(|_| "failed to encrypt the issuer key")?;
+        key.zeroize();
+        sk_bytes.zeroize();
+
+        let created = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        manifest.keys.push(KeyRecord {
+            key_id,
+            created,
+            public: hex::encode(&public),
+            kdf: self.kdf,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            secret: hex::encode(sealed),
+        });
+        if manifest.current.is_none_or(|current| key_id > current) {
+            manifest.current = Some(key_id);
+        }
+        self.write_manifest(&manifest)
+    }
+
+    pub fn public_keys(&self, key_id: u32) -> Result<IssuerPublicKeys, Box<dyn Error>> {
+        let manifest = self.read_manifest()?;
+        let record = Self::record(&manifest, key_id)?;
+        IssuerPublicKeys::from_bytes(key_id, &hex::decode(&record.public)?)
+    }
+
+    // A wrong passphrase or a tampered record both fail the AEAD check
+    pub fn load_version(&self, key_id: u32, passphrase: &str) -> Result<Issuer, Box<dyn Error>> {
+        let manifest = self.read_manifest()?;
+        let record = Self::record(&manifest, key_id)?;
+        let public = hex::decode(&record.public)?;
+        let nonce = hex::decode(&record.nonce)?;
+        if nonce.len() != 24 {
+            return Err("bad nonce length in keystore".into());
+        }
+
+        let mut key = derive_key(passphrase, &hex::decode(&record.salt)?, &record.kdf)?;
+        let aad = associated_data(key_id, &public);
+        let opened = XChaCha20Poly1305::new(Key::from_slice(&key))
+            .decrypt(XNonce::from_slice(&nonce), Payload { msg: &hex::decode(&record.secret)?, aad: &aad });
+        key.zeroize();
+        let mut sk_bytes = opened.map_err(|_| format!("cannot decrypt issuer key {}: wrong passphrase or corrupted keystore", key_id))?;
+        let sk = SecretKey::<Fr>::deserialize_compressed(&sk_bytes[..]);
+        sk_bytes.zeroize();
+        let sk = sk?;
+
+        let public = IssuerPublicKeys::from_bytes(key_id, &public)?;
+        if public.j != public.g2 * sk.0 || public.pk.0 != public.sig_param.g2 * sk.0 {
+            return Err(format!("issuer key {} does not match its public keys", key_id).into());
+        }
+        Ok(Issuer::from_keys(public, sk))
+    }
+
+    pub fn load(&self, passphrase: &str) -> Result<Issuer, Box<dyn Error>> {
+        let current = self.current_key_id()?.ok_or("keystore is empty")?;
+        self.load_version(current, passphrase)
+    }
+
+    // Current issuer, or a fresh version 1 on first start
+    pub fn load_or_create(&self, passphrase: &str) -> Result<Issuer, Box<dyn Error>> {
+        match self.current_key_id()? {
+            Some(current) => self.load_version(current, passphrase),
+            None => {
+                let issuer = Issuer::with_key_id(1);
+                self.save(&issuer, passphrase)?;
+                Ok(issuer)
+            }
+        }
+    }
+
+    // New key version, stored and made current; older versions are kept.
+    // The new signature parameters take as many messages as the current ones
+    pub fn rotate(&self, passphrase: &str) -> Result<Issuer, Box<dyn Error>> {
+        let next = self.key_ids()?.into_iter().max().unwrap_or(0) + 1;
+        let message_count = match self.current_key_id()? {
+            Some(current) => self.public_keys(current)?.sig_param.supported_message_count() as u32,
+            None => 6,
+        };
+        let issuer = Issuer::with_message_count(next, message_count);
+        self.save(&issuer, passphrase)?;
+        Ok(issuer)
+    }
+}
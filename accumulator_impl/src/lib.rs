@@ -4,3 +4,15 @@ pub mod helper;
 pub mod issuer;
 pub mod credential;
 pub mod holder;
+pub mod revocation;
+pub mod threshold;
+pub mod keystore;
+pub mod registry;
+pub mod schema;
+pub mod validity;
+pub mod wallet;
+pub mod secret;
+pub mod presentation;
+pub mod vc;
+pub mod replay;
+pub mod transcript;
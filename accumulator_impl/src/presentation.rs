@@ -0,0 +1,641 @@
+// Presentation exchange between a verifier and a holder.
+//
+// The verifier sends a PresentationRequest:
+//
+//   verifier    the verifier's identity
+//   nonce       fresh random value; every proof in the answer is bound to it
+//               and the verifier (replay.rs), and each is accepted once
+//   issuers     accepted issuers (IssuerPublicKeys::fingerprint); empty = any
+//   schemas     accepted schema ids (CredentialSchema::id); empty = any
+//   reveal      attributes to disclose
+//   predicates  ranges that Integer / Date attributes must lie in, proven
+//               without disclosing the attribute
+//   epoch       accumulator epoch the non-revocation proof has to be made at
+//   scope       if set, the holder also sends its pseudonym for that scope
+//
+// The holder answers with a Presentation of one wallet credential. Its proof
+// holds:
+//
+//   pok         BBS+ proof of knowledge of the signature, revealing only the
+//               schema id and the requested attributes; the holder secret x,
+//               the last signed message, stays hidden
+//   cx          a fresh commitment Cx = g*x + h*l
+//   link        proof that the x in Cx is the signed x, on the BBS+ response
+//               for x
+//   membership  zkp.rs proof that the x in Cx is accumulated at the epoch
+//   ranges      validity.rs range proofs, one per predicate bound, linked to
+//               the BBS+ responses of the attributes
+//   pseudonym   nym = H(scope)*x with a proof that it uses the x in Cx: the
+//               same credential always gives the same nym within a scope
+//
+// All of it is proven on one transcript (transcript.rs) over the issuer keys,
+// the epoch, the verifier challenge and the schema id, in the order
+// membership, pseudonym, then the link, the BBS+ proof and the range proofs,
+// which share a challenge.
+//
+// x is never shown and Cx is new every time, so presentations of the same
+// credential are linkable only through the attributes they disclose, or
+// through the nym within one scope.
+//
+// A verifier with a queue of presentations uses validate_batch, which checks
+// all their membership pairing equations at once.
+//
+// Both messages have JSON and CBOR encodings. Signed values, group elements
+// and proofs in them are hex of their canonical compressed encodings.
+
+use ark_bls12_381::{g1, Bls12_381, Fr, G1Projective};
+use ark_ec::hashing::curve_maps::wb::WBMap;
+use ark_ec::hashing::map_to_curve_hasher::MapToCurveBasedHasher;
+use ark_ec::hashing::HashToCurve;
+use ark_ff::field_hashers::DefaultFieldHasher;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::UniformRand;
+use bbs_plus::proof::{PoKOfSignatureG1Proof, PoKOfSignatureG1Protocol};
+use dock_crypto_utils::signature::{MessageOrBlinding, MultiMessageSignatureParams};
+use merlin::Transcript;
+use rand::thread_rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+
+use crate::acc::ECAccumulator;
+use crate::credential::Credential;
+use crate::helper::verify_witness;
+use crate::keystore::IssuerPublicKeys;
+use crate::replay::{ReplayCache, ReplayError, VerifierChallenge};
+use crate::schema::{AttributeType, AttributeValue, CredentialSchema};
+use crate::secret::Secret;
+use crate::transcript::{session_transcript, TranscriptProtocol};
+use crate::validity::{to_u64, Bound, RangeProof, RangeProtocol};
+use crate::wallet::{CredentialQuery, Wallet};
+use crate::zkp::{check_zkp, find_invalid, gen_zkp, PairingCheck, ZKProof};
+
+// Range an attribute must lie in, both ends included
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Predicate {
+    pub attribute: String,
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+impl Predicate {
+    fn bounds(&self) -> Vec<Bound> {
+        self.min.map(Bound::AtLeast).into_iter().chain(self.max.map(Bound::AtMost)).collect()
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PresentationRequest {
+    pub verifier: String,
+    pub nonce: String, // hex
+    pub issuers: Vec<String>,
+    pub schemas: Vec<String>,
+    pub reveal: Vec<String>,
+    pub predicates: Vec<Predicate>,
+    pub epoch: Option<u64>, // None: any epoch the verifier has the accumulator value of
+    pub scope: Option<String>,
+}
+
+// Accumulator value at an epoch, as the issuer published it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AccumulatorState {
+    pub epoch: u64,
+    pub alpha: G1Projective,
+}
+
+impl From<&ECAccumulator> for AccumulatorState {
+    fn from(acc: &ECAccumulator) -> Self {
+        AccumulatorState { epoch: acc.get_epoch(), alpha: *acc.get_alpha() }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Presentation {
+    pub verifier: String,
+    pub nonce: String,
+    pub issuer: String,
+    pub schema_id: String,
+    pub epoch: u64,
+    pub revealed: BTreeMap<String, String>, // attribute -> hex of the signed Fr
+    pub predicates: Vec<Predicate>,
+    pub pseudonym: Option<String>, // hex of nym
+    pub(crate) proof: String, // hex of PresentationProof
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PresentationError {
+    NonceMismatch,
+    WrongVerifier,
+    Replay(ReplayError),
+    IssuerNotAccepted,
+    SchemaNotAccepted,
+    // credential or presentation made under another schema or issuer key
+    WrongSchema,
+    WrongIssuer,
+    UnknownAttribute(String),
+    NotComparable(String), // predicate on an attribute that is not Integer or Date
+    PredicateNotSatisfied(String),
+    RevealMismatch,
+    PredicateMismatch,
+    EpochMismatch { required: u64, got: u64 },
+    PseudonymMismatch,
+    Revoked, // the witness does not verify against the accumulator state
+    NoMatchingCredential,
+    Malformed,
+    InvalidProof,
+}
+
+// With s_x the BBS+ response for x: g*s_x + h*s_l == t + Cx*c
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+struct LinkProof {
+    t: G1Projective,
+    s_l: Fr,
+}
+
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+struct PseudonymProof {
+    nym: G1Projective,
+    c: Fr,
+    s_x: Fr,
+    s_l: Fr,
+}
+
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+struct PresentationProof {
+    pok: PoKOfSignatureG1Proof<Bls12_381>,
+    cx: G1Projective,
+    link: LinkProof,
+    membership: ZKProof,
+    ranges: Vec<RangeProof>, // per predicate, min before max
+    pseudonym: Option<PseudonymProof>,
+}
+
+fn to_cbor<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(value, &mut bytes).unwrap();
+    bytes
+}
+
+fn from_cbor<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Box<dyn Error>> {
+    Ok(ciborium::de::from_reader(bytes)?)
+}
+
+fn fr_to_hex(f: &Fr) -> String {
+    let mut bytes = Vec::new();
+    f.serialize_compressed(&mut bytes).unwrap();
+    hex::encode(bytes)
+}
+
+fn fr_from_hex(s: &str) -> Option<Fr> {
+    Fr::deserialize_compressed(&hex::decode(s).ok()?[..]).ok()
+}
+
+// Base of the pseudonyms in a scope; hashed to the curve, so nobody knows
+// its discrete log to another scope's base
+fn scope_base(scope: &str) -> G1Projective {
+    let hasher = MapToCurveBasedHasher::<G1Projective, DefaultFieldHasher<Sha256, 128>, WBMap<g1::Config>>::new(
+        b"vac-pseudonym-scope",
+    )
+    .unwrap();
+    hasher.hash(scope.as_bytes()).unwrap().into()
+}
+
+fn link_contribution(transcript: &mut Transcript, cx: &G1Projective, t: &G1Projective) {
+    transcript.domain_sep(b"link");
+    transcript.append_serialized(b"cx", cx);
+    transcript.append_serialized(b"t", t);
+}
+
+fn pseudonym_challenge(
+    transcript: &mut Transcript,
+    scope: &str,
+    points: [&G1Projective; 5], // base, nym, Cx, t_nym, t_cx
+) -> Fr {
+    transcript.domain_sep(b"pseudonym");
+    transcript.append_message(b"scope", scope.as_bytes());
+    for (label, p) in [&b"base"[..], b"nym", b"cx", b"t_nym", b"t_cx"].into_iter().zip(points) {
+        transcript.append_serialized(label, p);
+    }
+    transcript.challenge_scalar(b"c")
+}
+
+impl PseudonymProof {
+    // Schnorr proof of (x, l) with nym = base*x and Cx = g*x + h*l
+    fn new(keys: &IssuerPublicKeys, scope: &str, cx: &G1Projective, x: &Fr, l: &Fr, transcript: &mut Transcript) -> Self {
+        let mut rng = thread_rng();
+        let base = scope_base(scope);
+        let nym = base * x;
+        let v = Secret::new([Fr::rand(&mut rng), Fr::rand(&mut rng)]);
+        let [v_x, v_l] = v.expose();
+        let t_nym = base * v_x;
+        let t_cx = keys.g * v_x + keys.h * v_l;
+        let c = pseudonym_challenge(transcript, scope, [&base, &nym, cx, &t_nym, &t_cx]);
+        PseudonymProof { nym, c, s_x: *v_x - c * x, s_l: *v_l - c * l }
+    }
+
+    fn verify(&self, keys: &IssuerPublicKeys, scope: &str, cx: &G1Projective, transcript: &mut Transcript) -> bool {
+        let base = scope_base(scope);
+        let t_nym = base * self.s_x + self.nym * self.c;
+        let t_cx = keys.g * self.s_x + keys.h * self.s_l + *cx * self.c;
+        self.c == pseudonym_challenge(transcript, scope, [&base, &self.nym, cx, &t_nym, &t_cx])
+    }
+}
+
+// What every proof of the presentation is bound to
+fn transcript(issuer: &IssuerPublicKeys, challenge: &VerifierChallenge, epoch: u64, schema: &CredentialSchema) -> Transcript {
+    let mut transcript = session_transcript(b"presentation", issuer, epoch, challenge);
+    transcript.append_message(b"schema", schema.id().as_bytes());
+    transcript
+}
+
+impl PresentationRequest {
+    // Request for a challenge (ReplayCache::issue) that accepts anything; fill in the rest
+    pub fn new(challenge: &VerifierChallenge) -> Self {
+        PresentationRequest { verifier: challenge.verifier.clone(), nonce: challenge.nonce.clone(), ..Default::default() }
+    }
+
+    pub fn challenge(&self) -> VerifierChallenge {
+        VerifierChallenge { verifier: self.verifier.clone(), nonce: self.nonce.clone() }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_cbor(&self) -> Vec<u8> {
+        to_cbor(self)
+    }
+
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        from_cbor(bytes)
+    }
+
+    // Wallet query for the credentials that may answer the request
+    pub fn query(&self) -> CredentialQuery {
+        CredentialQuery { issuers: self.issuers.clone(), schemas: self.schemas.clone(), ..Default::default() }
+    }
+
+    // Verifier side: checks that the presentation answers this request and
+    // that its proofs hold. issuer and schema are the verifier's own copies of
+    // the ones the presentation names; state is the issuer's accumulator at
+    // the presentation's epoch
+    pub fn validate(
+        &self,
+        presentation: &Presentation,
+        issuer: &IssuerPublicKeys,
+        schema: &CredentialSchema,
+        state: &AccumulatorState,
+    ) -> Result<(), PresentationError> {
+        if !self.check(presentation, issuer, schema, state)?.holds(issuer) {
+            return Err(PresentationError::InvalidProof);
+        }
+        Ok(())
+    }
+
+    // validate for many presentations of credentials from one issuer under
+    // one schema, at one accumulator state, each with its own request. The
+    // membership pairing equations are checked as one batch (zkp::batch_holds),
+    // bisected if it fails. Results are in the order of `batch`
+    pub fn validate_batch(
+        batch: &[(&PresentationRequest, &Presentation)],
+        issuer: &IssuerPublicKeys,
+        schema: &CredentialSchema,
+        state: &AccumulatorState,
+    ) -> Vec<Result<(), PresentationError>> {
+        let mut results = Vec::with_capacity(batch.len());
+        let mut checks = Vec::new();
+        let mut positions = Vec::new();
+        for (request, presentation) in batch {
+            match request.check(presentation, issuer, schema, state) {
+                Ok(check) => {
+                    positions.push(results.len());
+                    checks.push(check);
+                    results.push(Ok(()));
+                }
+                Err(e) => results.push(Err(e)),
+            }
+        }
+        for i in find_invalid(issuer, &checks) {
+            results[positions[i]] = Err(PresentationError::InvalidProof);
+        }
+        results
+    }
+
+    // validate up to the membership proof's pairing equation, which it returns
+    fn check(
+        &self,
+        presentation: &Presentation,
+        issuer: &IssuerPublicKeys,
+        schema: &CredentialSchema,
+        state: &AccumulatorState,
+    ) -> Result<PairingCheck, PresentationError> {
+        if presentation.verifier != self.verifier {
+            return Err(PresentationError::WrongVerifier);
+        }
+        if presentation.nonce != self.nonce {
+            return Err(PresentationError::NonceMismatch);
+        }
+        if !self.issuers.is_empty() && !self.issuers.contains(&presentation.issuer) {
+            return Err(PresentationError::IssuerNotAccepted);
+        }
+        if !self.schemas.is_empty() && !self.schemas.contains(&presentation.schema_id) {
+            return Err(PresentationError::SchemaNotAccepted);
+        }
+        if presentation.issuer != issuer.fingerprint() {
+            return Err(PresentationError::WrongIssuer);
+        }
+        if presentation.schema_id != schema.id() || issuer.sig_param.supported_message_count() != schema.message_count() {
+            return Err(PresentationError::WrongSchema);
+        }
+        let wanted: BTreeSet<&String> = self.reveal.iter().collect();
+        if presentation.revealed.keys().collect::<BTreeSet<_>>() != wanted {
+            return Err(PresentationError::RevealMismatch);
+        }
+        if presentation.predicates != self.predicates {
+            return Err(PresentationError::PredicateMismatch);
+        }
+        let required = self.epoch.unwrap_or(presentation.epoch);
+        if presentation.epoch != required || state.epoch != required {
+            return Err(PresentationError::EpochMismatch { required, got: presentation.epoch });
+        }
+
+        let bytes = hex::decode(&presentation.proof).map_err(|_| PresentationError::Malformed)?;
+        let proof = PresentationProof::deserialize_compressed(&bytes[..]).map_err(|_| PresentationError::Malformed)?;
+        let mut transcript = transcript(issuer, &self.challenge(), presentation.epoch, schema);
+
+        let membership = check_zkp(issuer, &state.alpha, &proof.cx, &proof.membership, &mut transcript)
+            .ok_or(PresentationError::InvalidProof)?;
+        match (&self.scope, &proof.pseudonym, &presentation.pseudonym) {
+            (None, None, None) => {}
+            (Some(scope), Some(nym_proof), Some(nym)) => {
+                let mut nym_bytes = Vec::new();
+                nym_proof.nym.serialize_compressed(&mut nym_bytes).unwrap();
+                if *nym != hex::encode(nym_bytes) || !nym_proof.verify(issuer, scope, &proof.cx, &mut transcript) {
+                    return Err(PresentationError::PseudonymMismatch);
+                }
+            }
+            _ => return Err(PresentationError::PseudonymMismatch),
+        }
+        link_contribution(&mut transcript, &proof.cx, &proof.link.t);
+
+        // signed messages the verifier sees; x, the last one, never is
+        let x_idx = schema.message_count() - 1;
+        let mut revealed = BTreeMap::from([(0, schema.id_fr())]);
+        for (name, value) in presentation.revealed.iter() {
+            let idx = schema.message_index(name).ok_or_else(|| PresentationError::UnknownAttribute(name.clone()))?;
+            revealed.insert(idx, fr_from_hex(value).ok_or(PresentationError::Malformed)?);
+        }
+        let mut bounds = Vec::new();
+        for predicate in self.predicates.iter() {
+            let idx = comparable_index(schema, &predicate.attribute)?;
+            if revealed.contains_key(&idx) {
+                return Err(PresentationError::PredicateMismatch);
+            }
+            bounds.extend(predicate.bounds().into_iter().map(|bound| (idx, bound)));
+        }
+        if proof.ranges.len() != bounds.len() {
+            return Err(PresentationError::Malformed);
+        }
+
+        let mut pok_bytes = Vec::new();
+        proof
+            .pok
+            .challenge_contribution(&revealed, &issuer.sig_param, &mut pok_bytes)
+            .map_err(|_| PresentationError::InvalidProof)?;
+        transcript.domain_sep(b"bbs-pok");
+        transcript.append_message(b"pok", &pok_bytes);
+        for (range, (_, bound)) in proof.ranges.iter().zip(bounds.iter()) {
+            range.challenge_contribution(&mut transcript, *bound);
+        }
+        let c = transcript.challenge_scalar(b"c");
+        if proof.pok.verify(&revealed, &c, issuer.pk.clone(), issuer.sig_param.clone()).is_err() {
+            return Err(PresentationError::InvalidProof);
+        }
+        let revealed_ids: BTreeSet<usize> = revealed.keys().copied().collect();
+        let s_x = proof.pok.get_resp_for_message(x_idx, &revealed_ids).map_err(|_| PresentationError::InvalidProof)?;
+        if issuer.g * s_x + issuer.h * proof.link.s_l != proof.link.t + proof.cx * c {
+            return Err(PresentationError::InvalidProof);
+        }
+        for (range, (idx, bound)) in proof.ranges.iter().zip(bounds) {
+            let s_m = proof.pok.get_resp_for_message(idx, &revealed_ids).map_err(|_| PresentationError::InvalidProof)?;
+            if !range.verify(&issuer.g, &issuer.h, bound, &c, s_m) {
+                return Err(PresentationError::InvalidProof);
+            }
+        }
+        Ok(membership)
+    }
+
+    // validate, then redeems the request's challenge in the verifier's replay
+    // cache: a second presentation for the same challenge is refused
+    pub fn validate_once(
+        &self,
+        presentation: &Presentation,
+        issuer: &IssuerPublicKeys,
+        schema: &CredentialSchema,
+        state: &AccumulatorState,
+        cache: &mut ReplayCache,
+        now: u64,
+    ) -> Result<(), PresentationError> {
+        self.validate(presentation, issuer, schema, state)?;
+        cache.redeem(&self.challenge(), now).map_err(PresentationError::Replay)
+    }
+}
+
+// Message index of a predicate attribute, which must be Integer or Date
+fn comparable_index(schema: &CredentialSchema, name: &str) -> Result<usize, PresentationError> {
+    let attr = schema
+        .attributes
+        .iter()
+        .find(|a| a.name == name)
+        .ok_or_else(|| PresentationError::UnknownAttribute(name.to_string()))?;
+    match attr.kind {
+        AttributeType::Integer | AttributeType::Date => Ok(schema.message_index(name).unwrap()),
+        _ => Err(PresentationError::NotComparable(name.to_string())),
+    }
+}
+
+impl Presentation {
+    // Holder side, for one credential issued by `issuer` under `schema`. state
+    // is the accumulator the witness is up to date with
+    pub fn create(
+        request: &PresentationRequest,
+        cred: &Credential,
+        issuer: &IssuerPublicKeys,
+        schema: &CredentialSchema,
+        state: &AccumulatorState,
+    ) -> Result<Self, PresentationError> {
+        let issuer_id = issuer.fingerprint();
+        if !request.issuers.is_empty() && !request.issuers.contains(&issuer_id) {
+            return Err(PresentationError::IssuerNotAccepted);
+        }
+        if !request.schemas.is_empty() && !request.schemas.contains(&schema.id()) {
+            return Err(PresentationError::SchemaNotAccepted);
+        }
+        if cred.get_key_id() != issuer.key_id {
+            return Err(PresentationError::WrongIssuer);
+        }
+        if let Some(required) = request.epoch {
+            if state.epoch != required {
+                return Err(PresentationError::EpochMismatch { required, got: state.epoch });
+            }
+        }
+        if !verify_witness(&state.alpha, cred.get_x_val(), cred.get_witness(), &issuer.g2, &issuer.j) {
+            return Err(PresentationError::Revoked);
+        }
+
+        let signed = cred.signed_messages();
+        let signed = signed.expose();
+        if schema.check_messages(signed).is_err() {
+            return Err(PresentationError::WrongSchema);
+        }
+
+        let x = cred.get_x_val();
+        let x_idx = signed.len() - 1;
+        let mut revealed = BTreeMap::from([(0, signed[0])]);
+        let mut revealed_values = BTreeMap::new();
+        for name in request.reveal.iter() {
+            let idx = schema.message_index(name).ok_or_else(|| PresentationError::UnknownAttribute(name.clone()))?;
+            revealed.insert(idx, signed[idx]);
+            revealed_values.insert(name.clone(), fr_to_hex(&signed[idx]));
+        }
+
+        let mut rng = thread_rng();
+        // l opens Cx; v_x, v_l blind x in the BBS+ proof and l in the link
+        let v = Secret::new([Fr::rand(&mut rng), Fr::rand(&mut rng), Fr::rand(&mut rng)]);
+        let [l, v_x, v_l] = v.expose();
+        let mut blindings = BTreeMap::from([(x_idx, *v_x)]);
+        let mut ranges = Vec::new();
+        for predicate in request.predicates.iter() {
+            let idx = comparable_index(schema, &predicate.attribute)?;
+            if revealed.contains_key(&idx) {
+                return Err(PresentationError::PredicateMismatch);
+            }
+            let value = to_u64(&signed[idx]).ok_or_else(|| PresentationError::NotComparable(predicate.attribute.clone()))?;
+            let blinding = *blindings.entry(idx).or_insert_with(|| Fr::rand(&mut rng));
+            for bound in predicate.bounds() {
+                let distance = match bound {
+                    Bound::AtLeast(min) => value.checked_sub(min),
+                    Bound::AtMost(max) => max.checked_sub(value),
+                };
+                let distance = distance.ok_or_else(|| PresentationError::PredicateNotSatisfied(predicate.attribute.clone()))?;
+                ranges.push(RangeProtocol::init(&issuer.g, &issuer.h, distance, bound, &blinding));
+            }
+        }
+
+        let messages_and_blindings = signed.iter().enumerate().map(|(i, m)| {
+            if revealed.contains_key(&i) {
+                MessageOrBlinding::RevealMessage(m)
+            } else if let Some(blinding) = blindings.get(&i) {
+                MessageOrBlinding::BlindMessageWithConcreteBlinding { message: m, blinding: *blinding }
+            } else {
+                MessageOrBlinding::BlindMessageRandomly(m)
+            }
+        });
+        let pok = PoKOfSignatureG1Protocol::init(&mut rng, cred.get_signature(), &issuer.sig_param, messages_and_blindings)
+            .map_err(|_| PresentationError::InvalidProof)?;
+
+        let mut transcript = transcript(issuer, &request.challenge(), state.epoch, schema);
+        let (cx, membership) = gen_zkp(issuer, &state.alpha, x, l, cred.get_witness(), &mut transcript);
+        let pseudonym = request.scope.as_ref().map(|scope| PseudonymProof::new(issuer, scope, &cx, x, l, &mut transcript));
+        let link_t = issuer.g * v_x + issuer.h * v_l;
+        link_contribution(&mut transcript, &cx, &link_t);
+
+        let mut pok_bytes = Vec::new();
+        pok.challenge_contribution(&revealed, &issuer.sig_param, &mut pok_bytes)
+            .map_err(|_| PresentationError::InvalidProof)?;
+        transcript.domain_sep(b"bbs-pok");
+        transcript.append_message(b"pok", &pok_bytes);
+        for range in ranges.iter() {
+            range.challenge_contribution(&mut transcript);
+        }
+        let c = transcript.challenge_scalar(b"c");
+        let nym = pseudonym.as_ref().map(|p| {
+            let mut bytes = Vec::new();
+            p.nym.serialize_compressed(&mut bytes).unwrap();
+            hex::encode(bytes)
+        });
+        let proof = PresentationProof {
+            pok: pok.gen_proof(&c).map_err(|_| PresentationError::InvalidProof)?,
+            cx,
+            link: LinkProof { t: link_t, s_l: *v_l + c * l },
+            membership,
+            ranges: ranges.into_iter().map(|r| r.gen_proof(&c)).collect(),
+            pseudonym,
+        };
+        let mut proof_bytes = Vec::new();
+        proof.serialize_compressed(&mut proof_bytes).unwrap();
+
+        Ok(Presentation {
+            verifier: request.verifier.clone(),
+            nonce: request.nonce.clone(),
+            issuer: issuer_id,
+            schema_id: schema.id(),
+            epoch: state.epoch,
+            revealed: revealed_values,
+            predicates: request.predicates.clone(),
+            pseudonym: nym,
+            proof: hex::encode(proof_bytes),
+        })
+    }
+
+    // Answers the request with the first wallet credential (in id order) from
+    // `issuer` under `schema` that satisfies it
+    pub fn from_wallet(
+        wallet: &Wallet,
+        request: &PresentationRequest,
+        issuer: &IssuerPublicKeys,
+        schema: &CredentialSchema,
+        state: &AccumulatorState,
+    ) -> Result<Self, PresentationError> {
+        let query = CredentialQuery { issuers: vec![issuer.fingerprint()], schemas: vec![schema.id()], ..Default::default() };
+        let mut last = PresentationError::NoMatchingCredential;
+        for entry in wallet.query(&query) {
+            match Presentation::create(request, entry.get_cred(), issuer, schema, state) {
+                Ok(presentation) => return Ok(presentation),
+                Err(e) => last = e,
+            }
+        }
+        Err(last)
+    }
+
+    // Challenge the presentation answers
+    pub fn challenge(&self) -> VerifierChallenge {
+        VerifierChallenge { verifier: self.verifier.clone(), nonce: self.nonce.clone() }
+    }
+
+    // Signed encoding of a revealed attribute
+    pub fn revealed_value(&self, attribute: &str) -> Option<Fr> {
+        fr_from_hex(self.revealed.get(attribute)?)
+    }
+
+    // Whether the revealed attribute is `value` under `schema`
+    pub fn reveals(&self, schema: &CredentialSchema, attribute: &str, value: &AttributeValue) -> bool {
+        match (self.revealed_value(attribute), schema.encode_value(attribute, value)) {
+            (Some(signed), Ok(encoded)) => signed == encoded,
+            _ => false,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_cbor(&self) -> Vec<u8> {
+        to_cbor(self)
+    }
+
+    pub fn from_cbor(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        from_cbor(bytes)
+    }
+}
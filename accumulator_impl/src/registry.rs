@@ -0,0 +1,341 @@
+// Issuer-side record of every credential issued, so revocation works from a
+// database record instead of the holder's Credential object. Stored in an
+// embedded sled database with three trees:
+//
+//   records   credential id -> CredentialRecord (JSON): the revocation
+//             handle x, key version, metadata and status
+//   index     "<name>\0<value>\0<credential id>" -> (), metadata lookups
+//   audit     big-endian sequence number -> AuditEntry (JSON). Entries are
+//             hash-chained, verify_audit_log detects entries edited or
+//             removed anywhere before the last one
+//
+// x is as sensitive as the credential itself: whoever learns it can link
+// presentations. Keep the database next to the issuer keystore.
+
+use ark_bls12_381::{Fr, G1Projective};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sled::transaction::{ConflictableTransactionResult, Transactional};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+use zeroize::Zeroize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::acc::ECAccumulator;
+use crate::credential::Credential;
+use crate::issuer::Issuer;
+use crate::revocation::RevocationScheme;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CredentialStatus {
+    Active,
+    Revoked,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CredentialRecord {
+    pub id: String,
+    pub key_id: u32,
+    handle: String, // hex of x
+    pub metadata: BTreeMap<String, String>,
+    pub status: CredentialStatus,
+    pub issued_at: u64,
+    pub updated_at: u64,
+}
+
+impl CredentialRecord {
+    // The revocation handle x
+    pub fn get_handle(&self) -> Result<Fr, Box<dyn Error>> {
+        Ok(Fr::deserialize_compressed(&hex::decode(&self.handle)?[..])?)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditAction {
+    Issued,
+    Revoked,
+    Reinstated,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub at: u64,
+    pub action: AuditAction,
+    pub credential_id: String,
+    pub key_id: u32,
+    pub prev: String, // hash of the previous entry, "" for the first one
+}
+
+impl AuditEntry {
+    pub fn hash(&self) -> String {
+        hex::encode(Sha256::digest(serde_json::to_vec(self).unwrap()))
+    }
+}
+
+// A registry id with its credential, and registry ids with their updates
+pub type Issued<W> = (String, Credential<W>);
+pub type Revoked<U> = Vec<(String, U)>;
+
+// Revocations applied to a copy of the scheme; neither the scheme nor the
+// registry changes until CredentialRegistry::commit
+pub struct PendingRevocation<S: RevocationScheme> {
+    staged: S,
+    records: Vec<CredentialRecord>,
+    pub updates: Revoked<S::Update>,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn index_key(name: &str, value: &str, id: &str) -> Vec<u8> {
+    [name.as_bytes(), b"\0", value.as_bytes(), b"\0", id.as_bytes()].concat()
+}
+
+pub struct CredentialRegistry {
+    db: sled::Db,
+    records: sled::Tree,
+    index: sled::Tree,
+    audit: sled::Tree,
+}
+
+impl CredentialRegistry {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let db = sled::open(path)?;
+        Ok(CredentialRegistry {
+            records: db.open_tree("records")?,
+            index: db.open_tree("index")?,
+            audit: db.open_tree("audit")?,
+            db,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    // seq and prev of the next audit entry
+    fn audit_head(&self) -> Result<(u64, String), Box<dyn Error>> {
+        Ok(match self.audit.last()? {
+            Some((_, last)) => {
+                let last: AuditEntry = serde_json::from_slice(&last)?;
+                (last.seq + 1, last.hash())
+            }
+            None => (0, String::new()),
+        })
+    }
+
+    // Stores the records, their metadata index entries and one audit entry
+    // each in a single transaction
+    fn put_all(&self, action: AuditAction, records: &[CredentialRecord]) -> Result<(), Box<dyn Error>> {
+        let (mut seq, mut prev) = self.audit_head()?;
+        let mut rows = Vec::new();
+        for record in records {
+            let entry = AuditEntry { seq, at: now(), action, credential_id: record.id.clone(), key_id: record.key_id, prev };
+            prev = entry.hash();
+            seq += 1;
+            let keys: Vec<Vec<u8>> = record.metadata.iter().map(|(name, value)| index_key(name, value, &record.id)).collect();
+            rows.push((record.id.as_bytes(), serde_json::to_vec(record)?, keys, entry.seq.to_be_bytes(), serde_json::to_vec(&entry)?));
+        }
+        (&self.records, &self.index, &self.audit)
+            .transaction(|(records, index, audit)| -> ConflictableTransactionResult<(), sled::Error> {
+                for (id, record, keys, seq, entry) in rows.iter() {
+                    records.insert(*id, record.clone())?;
+                    for key in keys {
+                        index.insert(&key[..], Vec::<u8>::new())?;
+                    }
+                    audit.insert(&seq[..], entry.clone())?;
+                }
+                Ok(())
+            })
+            .map_err(|e| format!("cannot store {:?} records: {}", action, e))?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    // Records an issued credential and returns its id
+    pub fn register<W: Zeroize>(&self, cred: &Credential<W>, metadata: BTreeMap<String, String>) -> Result<String, Box<dyn Error>> {
+        let id = format!("cred-{:08}", self.db.generate_id()?);
+        let mut handle = Vec::new();
+        cred.get_x_val().serialize_compressed(&mut handle)?;
+        let record = CredentialRecord {
+            id: id.clone(),
+            key_id: cred.get_key_id(),
+            handle: hex::encode(handle),
+            metadata,
+            status: CredentialStatus::Active,
+            issued_at: now(),
+            updated_at: now(),
+        };
+        self.put_all(AuditAction::Issued, &[record])?;
+        Ok(id)
+    }
+
+    // Issuer::gen_witness_n_cred followed by register
+    pub fn issue<S: RevocationScheme>(
+        &self,
+        issuer: &Issuer,
+        scheme: &S,
+        messages: Vec<Fr>,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<Issued<S::Witness>, Box<dyn Error>> {
+        let cred = issuer.gen_witness_n_cred(scheme, messages);
+        let id = self.register(&cred, metadata)?;
+        Ok((id, cred))
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<CredentialRecord>, Box<dyn Error>> {
+        match self.records.get(id.as_bytes())? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn get_existing(&self, id: &str) -> Result<CredentialRecord, Box<dyn Error>> {
+        self.get(id)?.ok_or_else(|| format!("unknown credential {}", id).into())
+    }
+
+    // Credentials whose metadata has name = value, e.g. ("candidate_id", "C-042")
+    pub fn find_by_attribute(&self, name: &str, value: &str) -> Result<Vec<CredentialRecord>, Box<dyn Error>> {
+        let prefix = [name.as_bytes(), b"\0", value.as_bytes(), b"\0"].concat();
+        let mut records = Vec::new();
+        for entry in self.index.scan_prefix(&prefix) {
+            let (key, _) = entry?;
+            let id = String::from_utf8(key[prefix.len()..].to_vec())?;
+            records.push(self.get_existing(&id)?);
+        }
+        Ok(records)
+    }
+
+    fn check_issuer(record: &CredentialRecord, issuer: &Issuer) -> Result<(), Box<dyn Error>> {
+        if record.key_id != issuer.get_key_id() {
+            return Err(format!("credential {} was issued under key {}, not {}", record.id, record.key_id, issuer.get_key_id()).into());
+        }
+        Ok(())
+    }
+
+    // Revokes the records on a copy of the scheme
+    fn prepare<S: RevocationScheme + Clone>(
+        issuer: &Issuer,
+        scheme: &S,
+        mut records: Vec<CredentialRecord>,
+    ) -> Result<PendingRevocation<S>, Box<dyn Error>> {
+        let mut staged = scheme.clone();
+        let mut updates = Vec::new();
+        for record in records.iter_mut() {
+            Self::check_issuer(record, issuer)?;
+            let update = issuer
+                .revoke_handle(&mut staged, &record.get_handle()?)
+                .map_err(|e| format!("cannot revoke {}: {:?}", record.id, e))?;
+            record.status = CredentialStatus::Revoked;
+            record.updated_at = now();
+            updates.push((record.id.clone(), update));
+        }
+        Ok(PendingRevocation { staged, records, updates })
+    }
+
+    pub fn prepare_revoke<S: RevocationScheme + Clone>(
+        &self,
+        issuer: &Issuer,
+        scheme: &S,
+        id: &str,
+    ) -> Result<PendingRevocation<S>, Box<dyn Error>> {
+        let record = self.get_existing(id)?;
+        if record.status == CredentialStatus::Revoked {
+            return Err(format!("credential {} is already revoked", id).into());
+        }
+        Self::prepare(issuer, scheme, vec![record])
+    }
+
+    // Every active credential with name = value, in id order
+    pub fn prepare_revoke_by_attribute<S: RevocationScheme + Clone>(
+        &self,
+        issuer: &Issuer,
+        scheme: &S,
+        name: &str,
+        value: &str,
+    ) -> Result<PendingRevocation<S>, Box<dyn Error>> {
+        let active = self.find_by_attribute(name, value)?.into_iter().filter(|r| r.status == CredentialStatus::Active).collect();
+        Self::prepare(issuer, scheme, active)
+    }
+
+    // Stores the revoked records and their audit entries in one transaction,
+    // then moves `scheme` to the staged state. On error both are unchanged
+    pub fn commit<S: RevocationScheme>(&self, pending: PendingRevocation<S>, scheme: &mut S) -> Result<Revoked<S::Update>, Box<dyn Error>> {
+        if !pending.records.is_empty() {
+            self.put_all(AuditAction::Revoked, &pending.records)?;
+        }
+        *scheme = pending.staged;
+        Ok(pending.updates)
+    }
+
+    // Revokes by credential id; the returned update is what holders apply
+    pub fn revoke<S: RevocationScheme + Clone>(
+        &self,
+        issuer: &Issuer,
+        scheme: &mut S,
+        id: &str,
+    ) -> Result<S::Update, Box<dyn Error>> {
+        let pending = self.prepare_revoke(issuer, scheme, id)?;
+        let (_, update) = self.commit(pending, scheme)?.remove(0);
+        Ok(update)
+    }
+
+    // Revokes every active credential with name = value, all or nothing
+    pub fn revoke_by_attribute<S: RevocationScheme + Clone>(
+        &self,
+        issuer: &Issuer,
+        scheme: &mut S,
+        name: &str,
+        value: &str,
+    ) -> Result<Revoked<S::Update>, Box<dyn Error>> {
+        let pending = self.prepare_revoke_by_attribute(issuer, scheme, name, value)?;
+        self.commit(pending, scheme)
+    }
+
+    // Re-admits a revoked credential; returns the old alpha (see Issuer::reinstate_a_cred)
+    pub fn reinstate(&self, issuer: &Issuer, accumulator: &mut ECAccumulator, id: &str) -> Result<G1Projective, Box<dyn Error>> {
+        let mut record = self.get_existing(id)?;
+        if record.status != CredentialStatus::Revoked {
+            return Err(format!("credential {} is not revoked", id).into());
+        }
+        Self::check_issuer(&record, issuer)?;
+        let mut staged = accumulator.clone();
+        let old_alpha = issuer.reinstate_handle(&mut staged, &record.get_handle()?);
+
+        record.status = CredentialStatus::Active;
+        record.updated_at = now();
+        self.put_all(AuditAction::Reinstated, &[record])?;
+        *accumulator = staged;
+        Ok(old_alpha)
+    }
+
+    pub fn audit_log(&self) -> Result<Vec<AuditEntry>, Box<dyn Error>> {
+        let mut entries = Vec::new();
+        for entry in self.audit.iter() {
+            let (_, bytes) = entry?;
+            entries.push(serde_json::from_slice(&bytes)?);
+        }
+        Ok(entries)
+    }
+
+    // false if an entry was edited, removed or reordered (dropping the tail
+    // is not detectable here, compare len() with a copy kept elsewhere)
+    pub fn verify_audit_log(&self) -> Result<bool, Box<dyn Error>> {
+        let mut prev = String::new();
+        for (seq, entry) in self.audit_log()?.iter().enumerate() {
+            if entry.seq != seq as u64 || entry.prev != prev {
+                return Ok(false);
+            }
+            prev = entry.hash();
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,113 @@
+// Verifier sessions: the challenge every proof is bound to, and the cache a
+// verifier keeps so that each challenge is answered at most once.
+//
+// A VerifierChallenge is the verifier's identity plus a fresh 256-bit nonce.
+// Its to_bytes() encoding is absorbed into the Fiat-Shamir challenge of every
+// proof made for it (membership, BBS+ PoK, range and expiry proofs, the
+// Bulletproof transcript), so a proof made for one verifier or one session
+// fails anywhere else.
+//
+// The ReplayCache only accepts nonces it issued itself, each once, until they
+// expire. Redeemed nonces are remembered until their expiry, so a replay is
+// reported as such; after that it is an unknown nonce and still rejected.
+
+use ark_ff::PrimeField;
+use rand::{thread_rng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierChallenge {
+    pub verifier: String, // verifier identity, e.g. its URL
+    pub nonce: String, // hex
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReplayError {
+    WrongVerifier,
+    UnknownNonce, // never issued here, or long expired
+    Expired,
+    Replayed,
+}
+
+impl VerifierChallenge {
+    // Fresh nonce for `verifier`; verifiers normally get it from ReplayCache::issue
+    pub fn new(verifier: &str) -> Self {
+        let mut nonce = [0u8; 32];
+        thread_rng().fill_bytes(&mut nonce);
+        VerifierChallenge { verifier: verifier.to_string(), nonce: hex::encode(nonce) }
+    }
+
+    // Length-prefixed, so no two challenges share an encoding
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = b"vac-verifier-challenge".to_vec();
+        for part in [&self.verifier, &self.nonce] {
+            bytes.extend_from_slice(&(part.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(part.as_bytes());
+        }
+        bytes
+    }
+
+    // The challenge as a field element, for circuits that take the nonce as an input
+    pub fn to_field<F: PrimeField>(&self) -> F {
+        F::from_le_bytes_mod_order(&Sha256::digest(self.to_bytes()))
+    }
+}
+
+pub struct ReplayCache {
+    verifier: String,
+    ttl: u64, // seconds a challenge stays answerable
+    pending: BTreeMap<String, u64>, // nonce -> expiry
+    redeemed: BTreeMap<String, u64>,
+}
+
+impl ReplayCache {
+    pub fn new(verifier: &str, ttl: u64) -> Self {
+        ReplayCache { verifier: verifier.to_string(), ttl, pending: BTreeMap::new(), redeemed: BTreeMap::new() }
+    }
+
+    pub fn verifier(&self) -> &str {
+        &self.verifier
+    }
+
+    pub fn ttl(&self) -> u64 {
+        self.ttl
+    }
+
+    // New challenge, answerable until now + ttl
+    pub fn issue(&mut self, now: u64) -> VerifierChallenge {
+        self.prune(now);
+        let challenge = VerifierChallenge::new(&self.verifier);
+        self.pending.insert(challenge.nonce.clone(), now + self.ttl);
+        challenge
+    }
+
+    // Accepts a challenge once; call it for every proof received, after
+    // checking the proof itself
+    pub fn redeem(&mut self, challenge: &VerifierChallenge, now: u64) -> Result<(), ReplayError> {
+        if challenge.verifier != self.verifier {
+            return Err(ReplayError::WrongVerifier);
+        }
+        if self.redeemed.contains_key(&challenge.nonce) {
+            return Err(ReplayError::Replayed);
+        }
+        let expiry = self.pending.remove(&challenge.nonce).ok_or(ReplayError::UnknownNonce)?;
+        if now > expiry {
+            return Err(ReplayError::Expired);
+        }
+        self.redeemed.insert(challenge.nonce.clone(), expiry);
+        Ok(())
+    }
+
+    // Forgets expired challenges
+    pub fn prune(&mut self, now: u64) {
+        self.pending.retain(|_, expiry| *expiry >= now);
+        self.redeemed.retain(|_, expiry| *expiry >= now);
+    }
+
+    // Issued challenges not yet answered
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
@@ -0,0 +1,604 @@
+// Sparse Merkle tree over BLS12-381 Fr, hashed with Poseidon, as the second
+// revocation backend next to the pairing accumulator. Keys are derived from
+// the credential identifier x in the same field, key = H(KEY, x) (key_from_x);
+// the low `depth` bits of a key select its leaf. Occupied leaves hold
+// H(LEAF, key), empty ones 0, and inner nodes H(NODE, left, right); an empty
+// subtree of height l hashes to empty[l] = H(NODE, empty[l-1], empty[l-1]).
+// The tags keep a leaf from passing for an inner node and a key for either.
+//
+// A key that is not in the tree is proven absent either by an empty leaf or
+// by the (different) key occupying its slot.
+//
+// A slot holds one key, so a key whose slot is taken cannot be revoked. Since
+// key_from_x slots are uniform, with n revoked keys the chance that any two
+// share a slot is at most n^2 / 2^(depth+1): below 2^-25 for a million
+// revocations at DEFAULT_DEPTH. Shallower trees are for tests only.
+//
+// Holders never show their key or path. gen_non_membership_zkp proves with
+// LegoGroth16 that the key derived from the x in a fresh Cx = g*x + h*l is not
+// in the tree:
+//
+//   - MerkleNonMembershipCircuit has public inputs root and a challenge from
+//     the caller's transcript; x is its one committed witness, the key, the
+//     occupant and the path are private
+//   - the proof carries D = b_x*x + b_v*v, the commitment to x the verifying
+//     key defines, and a Schnorr proof that D and Cx open to the same x
+//
+// Cx and D are fresh for every proof, so two proofs for the same x are
+// unlinkable. The verifying key comes from a trusted setup per depth
+// (MerkleZkParams::setup); whoever knows its trapdoor can forge proofs.
+
+use ark_bls12_381::{Bls12_381, Fr, G1Projective};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_r1cs_std::{
+    alloc::AllocVar,
+    boolean::Boolean,
+    eq::EqGadget,
+    fields::{fp::FpVar, FieldVar},
+    select::CondSelectGadget,
+    ToBitsGadget,
+};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_sponge::{
+    constraints::CryptographicSpongeVar,
+    poseidon::{constraints::PoseidonSpongeVar, find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge},
+    CryptographicSponge,
+};
+use ark_std::UniformRand;
+use legogroth16::{
+    create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
+    PreparedVerifyingKey, Proof, ProvingKey,
+};
+use merlin::Transcript;
+use rand::{thread_rng, Rng};
+use std::collections::HashMap;
+use zeroize::Zeroize;
+
+use crate::secret::Secret;
+use crate::transcript::TranscriptProtocol;
+use crate::zkp::commit;
+
+pub const DEFAULT_DEPTH: usize = 64;
+
+// Domain tags, absorbed before the inputs
+const KEY: u64 = 1;
+const LEAF: u64 = 2;
+const NODE: u64 = 3;
+
+// Poseidon over BLS12-381 Fr with Grain LFSR constants (rate 3, alpha 5, 8
+// full and 56 partial rounds), so a tagged node fits one permutation.
+// helper::create_poseidon_config cannot be used here: its identity MDS never
+// mixes the state, so H(NODE, l, r) would not depend on r.
+pub fn merkle_poseidon_config() -> PoseidonConfig<Fr> {
+    let (full_rounds, partial_rounds, alpha, rate) = (8, 56, 5, 3);
+    let (ark, mds) = find_poseidon_ark_and_mds::<Fr>(
+        Fr::MODULUS_BIT_SIZE as u64,
+        rate,
+        full_rounds as u64,
+        partial_rounds as u64,
+        0,
+    );
+    PoseidonConfig { full_rounds, partial_rounds, alpha, ark, mds, rate, capacity: 1 }
+}
+
+pub fn poseidon_hash(inputs: &[Fr], config: &PoseidonConfig<Fr>) -> Fr {
+    let mut sponge = PoseidonSponge::<Fr>::new(config);
+    sponge.absorb(&inputs.to_vec());
+    sponge.squeeze_field_elements(1)[0]
+}
+
+// Credential x -> tree key
+pub fn key_from_x(x: &Fr, config: &PoseidonConfig<Fr>) -> Fr {
+    poseidon_hash(&[Fr::from(KEY), *x], config)
+}
+
+pub fn hash_leaf(key: &Fr, config: &PoseidonConfig<Fr>) -> Fr {
+    poseidon_hash(&[Fr::from(LEAF), *key], config)
+}
+
+pub fn hash_node(left: &Fr, right: &Fr, config: &PoseidonConfig<Fr>) -> Fr {
+    poseidon_hash(&[Fr::from(NODE), *left, *right], config)
+}
+
+fn key_index(key: &Fr, depth: usize) -> u64 {
+    let bits = key.into_bigint().to_bits_le();
+    bits.iter().take(depth).enumerate().fold(0u64, |acc, (i, b)| acc | ((*b as u64) << i))
+}
+
+// Siblings from the leaf up to the child of the root
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize, Zeroize)]
+pub struct MerklePath {
+    pub index: u64,
+    pub siblings: Vec<Fr>,
+}
+
+impl MerklePath {
+    pub fn compute_root(&self, leaf: Fr, config: &PoseidonConfig<Fr>) -> Fr {
+        *self.nodes(leaf, config).last().unwrap()
+    }
+
+    // All nodes on the path, from the leaf (level 0) to the root
+    fn nodes(&self, leaf: Fr, config: &PoseidonConfig<Fr>) -> Vec<Fr> {
+        let mut nodes = Vec::with_capacity(self.siblings.len() + 1);
+        let mut node = leaf;
+        nodes.push(node);
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            node = if (self.index >> level) & 1 == 0 {
+                hash_node(&node, sibling, config)
+            } else {
+                hash_node(sibling, &node, config)
+            };
+            nodes.push(node);
+        }
+        nodes
+    }
+}
+
+// What verifiers need besides the root. The depth is the tree's, never the
+// length of a path the prover sent
+#[derive(Clone, Debug)]
+pub struct MerkleParams {
+    pub depth: usize,
+    pub config: PoseidonConfig<Fr>,
+}
+
+impl MerkleParams {
+    pub fn new(depth: usize) -> Self {
+        MerkleParams { depth, config: merkle_poseidon_config() }
+    }
+}
+
+// MerkleParams plus what the zero-knowledge proof needs: the generators of Cx
+// and the LegoGroth16 keys of MerkleNonMembershipCircuit at that depth
+#[derive(Clone, Debug)]
+pub struct MerkleZkParams {
+    pub tree: MerkleParams,
+    pub g: G1Projective,
+    pub h: G1Projective,
+    pub pk: ProvingKey<Bls12_381>,
+    pub pvk: PreparedVerifyingKey<Bls12_381>,
+}
+
+impl MerkleZkParams {
+    // Trusted setup; the issuer runs it once per tree and publishes the result
+    pub fn setup<R: Rng>(tree: MerkleParams, rng: &mut R) -> Self {
+        let circuit = MerkleNonMembershipCircuit::blank(&tree);
+        // one committed witness: x
+        let pk = generate_random_parameters::<Bls12_381, _, _>(circuit, 1, rng)
+            .expect("the circuit synthesizes without a witness");
+        let pvk = prepare_verifying_key(&pk.vk);
+        MerkleZkParams { tree, g: G1Projective::rand(rng), h: G1Projective::rand(rng), pk, pvk }
+    }
+
+    // Bases of D: b_x for x and b_v for the blinding v
+    fn commitment_key(&self) -> (G1Projective, G1Projective) {
+        let key = self.pk.vk.get_commitment_key_for_witnesses();
+        (key[0].into(), key[1].into())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MembershipProof {
+    pub path: MerklePath,
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize, Zeroize)]
+pub struct NonMembershipProof {
+    pub path: MerklePath,
+    // key occupying the slot, None if the leaf is empty
+    pub occupant: Option<Fr>,
+}
+
+// Zero-knowledge non-membership proof for the x in Cx; responses are
+// s = v - c*secret, as in zkp.rs
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MerkleZkProof {
+    pub snark: Proof<Bls12_381>,
+    pub c: Fr,
+    pub s_x: Fr,
+    pub s_v: Fr,
+    pub s_l: Fr,
+}
+
+// Published on every insert/remove so holders can refresh their paths
+#[derive(Clone, Debug, PartialEq)]
+pub struct MerkleUpdate {
+    pub path: MerklePath,
+    pub leaf: Fr,
+    pub new_root: Fr,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MerkleError {
+    ZeroKey,
+    AlreadyPresent,
+    NotPresent,
+    // another key already uses the slot (index = low `depth` bits of the key)
+    SlotTaken,
+}
+
+#[derive(Clone)]
+pub struct SparseMerkleTree {
+    depth: usize,
+    config: PoseidonConfig<Fr>,
+    leaves: HashMap<u64, Fr>, // index -> key
+    nodes: HashMap<(usize, u64), Fr>, // (level, index) -> hash, only non-empty subtrees
+    empty: Vec<Fr>,
+}
+
+impl SparseMerkleTree {
+    pub fn new(depth: usize) -> Self {
+        Self::with_config(depth, merkle_poseidon_config())
+    }
+
+    pub fn with_config(depth: usize, config: PoseidonConfig<Fr>) -> Self {
+        assert!(depth > 0 && depth <= 64, "depth must be in 1..=64");
+        let mut empty = vec![Fr::zero()];
+        for level in 0..depth {
+            empty.push(hash_node(&empty[level], &empty[level], &config));
+        }
+        SparseMerkleTree { depth, config, leaves: HashMap::new(), nodes: HashMap::new(), empty }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn config(&self) -> &PoseidonConfig<Fr> {
+        &self.config
+    }
+
+    pub fn params(&self) -> MerkleParams {
+        MerkleParams { depth: self.depth, config: self.config.clone() }
+    }
+
+    pub fn root(&self) -> Fr {
+        self.node(self.depth, 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    pub fn contains(&self, key: &Fr) -> bool {
+        self.leaves.get(&key_index(key, self.depth)) == Some(key)
+    }
+
+    pub fn leaf_hash(&self, key: &Fr) -> Fr {
+        hash_leaf(key, &self.config)
+    }
+
+    fn node(&self, level: usize, index: u64) -> Fr {
+        self.nodes.get(&(level, index)).copied().unwrap_or(self.empty[level])
+    }
+
+    fn path(&self, index: u64) -> MerklePath {
+        let siblings = (0..self.depth).map(|level| self.node(level, (index >> level) ^ 1)).collect();
+        MerklePath { index, siblings }
+    }
+
+    fn set_leaf(&mut self, index: u64, leaf: Fr) -> MerkleUpdate {
+        let path = self.path(index);
+        for (level, node) in path.nodes(leaf, &self.config).into_iter().enumerate() {
+            let idx = index.checked_shr(level as u32).unwrap_or(0);
+            if node == self.empty[level] {
+                self.nodes.remove(&(level, idx));
+            } else {
+                self.nodes.insert((level, idx), node);
+            }
+        }
+        MerkleUpdate { path, leaf, new_root: self.root() }
+    }
+
+    pub fn insert(&mut self, key: Fr) -> Result<MerkleUpdate, MerkleError> {
+        if key.is_zero() {
+            return Err(MerkleError::ZeroKey);
+        }
+        let index = key_index(&key, self.depth);
+        match self.leaves.get(&index) {
+            Some(k) if *k == key => return Err(MerkleError::AlreadyPresent),
+            Some(_) => return Err(MerkleError::SlotTaken),
+            None => {}
+        }
+        self.leaves.insert(index, key);
+        let leaf = self.leaf_hash(&key);
+        Ok(self.set_leaf(index, leaf))
+    }
+
+    pub fn remove(&mut self, key: &Fr) -> Result<MerkleUpdate, MerkleError> {
+        if !self.contains(key) {
+            return Err(MerkleError::NotPresent);
+        }
+        let index = key_index(key, self.depth);
+        self.leaves.remove(&index);
+        Ok(self.set_leaf(index, Fr::zero()))
+    }
+
+    pub fn prove_membership(&self, key: &Fr) -> Option<MembershipProof> {
+        if !self.contains(key) {
+            return None;
+        }
+        Some(MembershipProof { path: self.path(key_index(key, self.depth)) })
+    }
+
+    pub fn prove_non_membership(&self, key: &Fr) -> Option<NonMembershipProof> {
+        if key.is_zero() || self.contains(key) {
+            return None;
+        }
+        let index = key_index(key, self.depth);
+        Some(NonMembershipProof { path: self.path(index), occupant: self.leaves.get(&index).copied() })
+    }
+}
+
+pub fn verify_membership(
+    root: &Fr,
+    key: &Fr,
+    proof: &MembershipProof,
+    params: &MerkleParams,
+) -> bool {
+    let config = &params.config;
+    proof.path.siblings.len() == params.depth
+        && proof.path.index == key_index(key, params.depth)
+        && proof.path.compute_root(hash_leaf(key, config), config) == *root
+}
+
+pub fn verify_non_membership(
+    root: &Fr,
+    key: &Fr,
+    proof: &NonMembershipProof,
+    params: &MerkleParams,
+) -> bool {
+    let (depth, config) = (params.depth, &params.config);
+    if key.is_zero() || proof.path.siblings.len() != depth || proof.path.index != key_index(key, depth) {
+        return false;
+    }
+    let leaf = match proof.occupant {
+        None => Fr::zero(),
+        Some(other) => {
+            if other == *key || other.is_zero() || key_index(&other, depth) != proof.path.index {
+                return false;
+            }
+            hash_leaf(&other, config)
+        }
+    };
+    proof.path.compute_root(leaf, config) == *root
+}
+
+// Refreshes a path after another leaf changed. Returns false if the update
+// touched this very leaf (the proof has to be regenerated by the tree owner).
+pub fn update_path(path: &mut MerklePath, update: &MerkleUpdate, config: &PoseidonConfig<Fr>) -> bool {
+    if path.index == update.path.index || path.siblings.len() != update.path.siblings.len() {
+        return false;
+    }
+    // the paths meet just above the highest differing bit; below it they are disjoint
+    let diff = path.index ^ update.path.index;
+    let level = 63 - diff.leading_zeros() as usize;
+    let nodes = update.path.nodes(update.leaf, config);
+    path.siblings[level] = nodes[level];
+    true
+}
+
+/*
+    Zero-knowledge non-membership
+*/
+
+// Public input of the circuit: pins the proof to the caller's transcript,
+// which already holds the verifier session
+fn binding_challenge(transcript: &mut Transcript, root: &Fr, commitment: &G1Projective) -> Fr {
+    transcript.domain_sep(b"merkle-non-membership");
+    transcript.append_serialized(b"root", root);
+    transcript.append_serialized(b"cx", commitment);
+    transcript.challenge_scalar(b"binding")
+}
+
+fn challenge(transcript: &mut Transcript, snark: &Proof<Bls12_381>, t_d: &G1Projective, t_x: &G1Projective) -> Fr {
+    transcript.append_serialized(b"snark", snark);
+    transcript.append_serialized(b"t_d", t_d);
+    transcript.append_serialized(b"t_x", t_x);
+    transcript.challenge_scalar(b"c")
+}
+
+// Holder side: witness is the non-membership path of key_from_x(x) under
+// root. Returns a fresh Cx with the proof, None if the witness does not
+// satisfy the circuit (x revoked, stale path, wrong depth)
+pub fn gen_non_membership_zkp(
+    params: &MerkleZkParams,
+    root: &Fr,
+    x: &Fr,
+    witness: &NonMembershipProof,
+    transcript: &mut Transcript,
+) -> Option<(G1Projective, MerkleZkProof)> {
+    let mut rng = thread_rng();
+    let l = Secret::new(Fr::rand(&mut rng));
+    let commitment = commit(&params.g, &params.h, x, l.expose());
+    let binding = binding_challenge(transcript, root, &commitment);
+
+    // v blinds x in D; v_x, v_v, v_l are the Schnorr blindings
+    let v: Secret<[Fr; 4]> = Secret::new([(); 4].map(|_| Fr::rand(&mut rng)));
+    let [v, v_x, v_v, v_l] = v.expose();
+    let circuit = MerkleNonMembershipCircuit {
+        root: *root,
+        challenge: binding,
+        x: *x,
+        proof: witness.clone(),
+        config: params.tree.config.clone(),
+    };
+    let snark = create_random_proof(circuit, *v, &params.pk, &mut rng).ok()?;
+
+    let (b_x, b_v) = params.commitment_key();
+    let t_d = b_x * v_x + b_v * v_v;
+    let t_x = params.g * v_x + params.h * v_l;
+    let c = challenge(transcript, &snark, &t_d, &t_x);
+    Some((
+        commitment,
+        MerkleZkProof {
+            snark,
+            c,
+            s_x: *v_x - c * x,
+            s_v: *v_v - c * v,
+            s_l: *v_l - c * l.expose(),
+        },
+    ))
+}
+
+// Verifier side, against the root it trusts
+pub fn verify_non_membership_zkp(
+    params: &MerkleZkParams,
+    root: &Fr,
+    commitment: &G1Projective,
+    proof: &MerkleZkProof,
+    transcript: &mut Transcript,
+) -> bool {
+    let binding = binding_challenge(transcript, root, commitment);
+    let (b_x, b_v) = params.commitment_key();
+    let c = proof.c;
+    let t_d = proof.snark.d * c + b_x * proof.s_x + b_v * proof.s_v;
+    let t_x = *commitment * c + params.g * proof.s_x + params.h * proof.s_l;
+    c == challenge(transcript, &proof.snark, &t_d, &t_x)
+        && verify_proof(&params.pvk, &proof.snark, &[*root, binding]).is_ok()
+}
+
+/*
+    R1CS gadgets
+*/
+
+pub fn poseidon_hash_var(
+    cs: ConstraintSystemRef<Fr>,
+    inputs: &[FpVar<Fr>],
+    config: &PoseidonConfig<Fr>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut sponge = PoseidonSpongeVar::<Fr>::new(cs, config);
+    sponge.absorb(&inputs.to_vec())?;
+    Ok(sponge.squeeze_field_elements(1)?.remove(0))
+}
+
+fn tagged_hash_var(
+    cs: ConstraintSystemRef<Fr>,
+    tag: u64,
+    inputs: &[&FpVar<Fr>],
+    config: &PoseidonConfig<Fr>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut tagged = vec![FpVar::constant(Fr::from(tag))];
+    tagged.extend(inputs.iter().map(|v| (*v).clone()));
+    poseidon_hash_var(cs, &tagged, config)
+}
+
+pub fn key_from_x_var(
+    cs: ConstraintSystemRef<Fr>,
+    x: &FpVar<Fr>,
+    config: &PoseidonConfig<Fr>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    tagged_hash_var(cs, KEY, &[x], config)
+}
+
+// Root from a leaf, the index bits (little endian) and the siblings
+pub fn compute_root_var(
+    cs: ConstraintSystemRef<Fr>,
+    leaf: &FpVar<Fr>,
+    index_bits: &[Boolean<Fr>],
+    siblings: &[FpVar<Fr>],
+    config: &PoseidonConfig<Fr>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut node = leaf.clone();
+    for (bit, sibling) in index_bits.iter().zip(siblings.iter()) {
+        let left = FpVar::conditionally_select(bit, sibling, &node)?;
+        let right = FpVar::conditionally_select(bit, &node, sibling)?;
+        node = tagged_hash_var(cs.clone(), NODE, &[&left, &right], config)?;
+    }
+    Ok(node)
+}
+
+pub fn enforce_membership_var(
+    cs: ConstraintSystemRef<Fr>,
+    root: &FpVar<Fr>,
+    key: &FpVar<Fr>,
+    siblings: &[FpVar<Fr>],
+    config: &PoseidonConfig<Fr>,
+) -> Result<(), SynthesisError> {
+    let bits = key.to_bits_le()?;
+    let leaf = tagged_hash_var(cs.clone(), LEAF, &[key], config)?;
+    let computed = compute_root_var(cs, &leaf, &bits[..siblings.len()], siblings, config)?;
+    computed.enforce_equal(root)
+}
+
+// `occupant` is only meaningful when `is_empty` is false
+pub fn enforce_non_membership_var(
+    cs: ConstraintSystemRef<Fr>,
+    root: &FpVar<Fr>,
+    key: &FpVar<Fr>,
+    is_empty: &Boolean<Fr>,
+    occupant: &FpVar<Fr>,
+    siblings: &[FpVar<Fr>],
+    config: &PoseidonConfig<Fr>,
+) -> Result<(), SynthesisError> {
+    let depth = siblings.len();
+    let occupied = is_empty.not();
+    key.enforce_not_equal(&FpVar::zero())?;
+
+    // an occupant must be a different key sharing the slot
+    let key_bits = key.to_bits_le()?;
+    let occupant_bits = occupant.to_bits_le()?;
+    for (k, o) in key_bits.iter().zip(occupant_bits.iter()).take(depth) {
+        o.conditional_enforce_equal(k, &occupied)?;
+    }
+    occupant.conditional_enforce_not_equal(key, &occupied)?;
+    occupant.conditional_enforce_not_equal(&FpVar::zero(), &occupied)?;
+
+    let occupant_leaf = tagged_hash_var(cs.clone(), LEAF, &[occupant], config)?;
+    let leaf = FpVar::conditionally_select(is_empty, &FpVar::zero(), &occupant_leaf)?;
+    let computed = compute_root_var(cs, &leaf, &key_bits[..depth], siblings, config)?;
+    computed.enforce_equal(root)
+}
+
+// LegoGroth16 statement "the key of the committed x is not in the tree with
+// this root". Public inputs: root, challenge. x is the first witness, the one
+// the proof commits to in D; key_from_x(x), the occupant and the path are
+// private
+#[derive(Clone)]
+pub struct MerkleNonMembershipCircuit {
+    pub root: Fr,
+    pub challenge: Fr,
+    pub x: Fr,
+    pub proof: NonMembershipProof,
+    pub config: PoseidonConfig<Fr>,
+}
+
+impl MerkleNonMembershipCircuit {
+    // Right shape, no meaningful values: for the setup
+    pub fn blank(params: &MerkleParams) -> Self {
+        MerkleNonMembershipCircuit {
+            root: Fr::zero(),
+            challenge: Fr::zero(),
+            x: Fr::zero(),
+            proof: NonMembershipProof {
+                path: MerklePath { index: 0, siblings: vec![Fr::zero(); params.depth] },
+                occupant: None,
+            },
+            config: params.config.clone(),
+        }
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for MerkleNonMembershipCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let root = FpVar::new_input(cs.clone(), || Ok(self.root))?;
+        let challenge = FpVar::new_input(cs.clone(), || Ok(self.challenge))?;
+        let x = FpVar::new_witness(cs.clone(), || Ok(self.x))?;
+        // the challenge plays no part in the statement; a constraint on it
+        // keeps it from being dropped as an unused input
+        let _ = challenge.square()?;
+
+        let key = key_from_x_var(cs.clone(), &x, &self.config)?;
+        let is_empty = Boolean::new_witness(cs.clone(), || Ok(self.proof.occupant.is_none()))?;
+        let occupant = FpVar::new_witness(cs.clone(), || Ok(self.proof.occupant.unwrap_or(Fr::zero())))?;
+        let siblings = self.proof.path.siblings.iter()
+            .map(|s| FpVar::new_witness(cs.clone(), || Ok(*s)))
+            .collect::<Result<Vec<_>, _>>()?;
+        enforce_non_membership_var(cs, &root, &key, &is_empty, &occupant, &siblings, &self.config)
+    }
+}
@@ -0,0 +1,179 @@
+// Common interface of the revocation backends, so the pairing accumulator and
+// the Merkle tree can be swapped and compared.
+//
+//   ECAccumulator    allow-list: holders keep a membership witness, revoking
+//                    removes x from the accumulator
+//   SparseMerkleTree deny-list: revoked identifiers are inserted, holders keep
+//                    a non-membership path for their own identifier
+//
+// Elements are always the credential secret x (BLS12-381 Fr); the Merkle
+// backend maps them to tree keys with merkle::key_from_x. Both prove in zero
+// knowledge for a fresh commitment Cx to x, never showing x itself.
+
+pub mod merkle;
+
+use ark_bls12_381::{Fr, G1Projective};
+use ark_ff::Field;
+use ark_std::UniformRand;
+use bbs_plus::prelude::SecretKey;
+use merlin::Transcript;
+use rand::thread_rng;
+use zeroize::Zeroize;
+
+use crate::acc::ECAccumulator;
+use crate::keystore::IssuerPublicKeys;
+use crate::zkp::{gen_zkp, verify_zkp, ZKProof};
+use merkle::{
+    gen_non_membership_zkp, key_from_x, update_path, verify_non_membership_zkp, MerkleError,
+    MerkleUpdate, MerkleZkParams, MerkleZkProof, NonMembershipProof, SparseMerkleTree,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RevocationError {
+    AlreadyRevoked,
+    // Merkle tree only: another identifier occupies the slot (see merkle.rs
+    // for the collision bound)
+    SlotTaken,
+}
+
+pub trait RevocationScheme {
+    // Issuer-only trapdoor needed to issue witnesses and revoke
+    type SecretKey;
+    // What a holder keeps to show its credential is not revoked; secret,
+    // wiped with the credential
+    type Witness: Clone + Zeroize;
+    // What the issuer publishes when a credential is revoked
+    type Update: Clone;
+    // The value verifiers check against (alpha, Merkle root)
+    type PublicState: Clone + PartialEq;
+    // What a holder hands a verifier: Cx with a zero-knowledge proof that
+    // the x in it is not revoked
+    type Proof: Clone;
+    // Public parameters needed for witness updates and verification
+    type VerifyParams;
+
+    // The scheme's trapdoor, taken from the issuer's BBS+ key
+    fn issuer_key(sk: &SecretKey<Fr>) -> &Self::SecretKey;
+
+    fn public_state(&self) -> Self::PublicState;
+
+    // None if x cannot be given a witness (e.g. already revoked)
+    fn gen_witness(&self, x: &Fr, sk: &Self::SecretKey) -> Option<Self::Witness>;
+
+    fn revoke(&mut self, x: &Fr, sk: &Self::SecretKey) -> Result<Self::Update, RevocationError>;
+
+    // Public witness update; false if the update revoked x itself (or, for
+    // the Merkle tree, touched x's leaf) and the witness is no longer usable
+    fn update_witness(params: &Self::VerifyParams, x: &Fr, witness: &mut Self::Witness, update: &Self::Update) -> bool;
+
+    // Prover and verifier start from the same transcript (transcript::session_transcript).
+    // None if the witness does not show x unrevoked under state and the
+    // scheme can tell (the Merkle prover checks its circuit)
+    fn prove(params: &Self::VerifyParams, state: &Self::PublicState, x: &Fr, witness: &Self::Witness, transcript: &mut Transcript) -> Option<Self::Proof>;
+
+    fn verify(params: &Self::VerifyParams, state: &Self::PublicState, proof: &Self::Proof, transcript: &mut Transcript) -> bool;
+}
+
+// Published accumulator update: the revoked delta and the new alpha
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccRevocation {
+    pub delta: Fr,
+    pub new_alpha: G1Projective,
+}
+
+impl RevocationScheme for ECAccumulator {
+    type SecretKey = SecretKey<Fr>;
+    type Witness = G1Projective;
+    type Update = AccRevocation;
+    type PublicState = G1Projective;
+    type Proof = (G1Projective, ZKProof); // (Cx, proof), see zkp.rs
+    type VerifyParams = IssuerPublicKeys;
+
+    fn issuer_key(sk: &SecretKey<Fr>) -> &SecretKey<Fr> {
+        sk
+    }
+
+    fn public_state(&self) -> G1Projective {
+        self.alpha
+    }
+
+    fn gen_witness(&self, x: &Fr, sk: &SecretKey<Fr>) -> Option<G1Projective> {
+        if self.deltas.contains(x) {
+            return None;
+        }
+        Some(ECAccumulator::gen_witness(self, x, sk))
+    }
+
+    fn revoke(&mut self, x: &Fr, sk: &SecretKey<Fr>) -> Result<AccRevocation, RevocationError> {
+        if self.deltas.contains(x) {
+            return Err(RevocationError::AlreadyRevoked);
+        }
+        self.update_acc(x, sk);
+        Ok(AccRevocation { delta: *x, new_alpha: self.alpha })
+    }
+
+    fn update_witness(_keys: &IssuerPublicKeys, x: &Fr, witness: &mut G1Projective, update: &AccRevocation) -> bool {
+        // Equation 11 of Flamini, 2025
+        match (update.delta - x).inverse() {
+            Some(inv) => {
+                *witness = (*witness - update.new_alpha) * inv;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Cx is a fresh commitment, so two proofs for the same x are unlinkable;
+    // Holder::prove_membership proves for the Cx inside the BBS+ signature
+    fn prove(keys: &IssuerPublicKeys, state: &G1Projective, x: &Fr, witness: &G1Projective, transcript: &mut Transcript) -> Option<(G1Projective, ZKProof)> {
+        let l = Fr::rand(&mut thread_rng());
+        Some(gen_zkp(keys, state, x, &l, witness, transcript))
+    }
+
+    fn verify(keys: &IssuerPublicKeys, state: &G1Projective, proof: &(G1Projective, ZKProof), transcript: &mut Transcript) -> bool {
+        verify_zkp(keys, state, &proof.0, &proof.1, transcript)
+    }
+}
+
+impl RevocationScheme for SparseMerkleTree {
+    // no trapdoor: the tree is public, only the issuer holds the writable copy
+    type SecretKey = ();
+    type Witness = NonMembershipProof;
+    type Update = MerkleUpdate;
+    type PublicState = Fr;
+    type Proof = (G1Projective, MerkleZkProof); // (Cx, proof), see merkle.rs
+    type VerifyParams = MerkleZkParams;
+
+    fn issuer_key(_sk: &SecretKey<Fr>) -> &() {
+        &()
+    }
+
+    fn public_state(&self) -> Fr {
+        self.root()
+    }
+
+    fn gen_witness(&self, x: &Fr, _sk: &()) -> Option<NonMembershipProof> {
+        self.prove_non_membership(&key_from_x(x, self.config()))
+    }
+
+    fn revoke(&mut self, x: &Fr, _sk: &()) -> Result<MerkleUpdate, RevocationError> {
+        self.insert(key_from_x(x, self.config())).map_err(|e| match e {
+            MerkleError::SlotTaken => RevocationError::SlotTaken,
+            _ => RevocationError::AlreadyRevoked,
+        })
+    }
+
+    fn update_witness(params: &MerkleZkParams, _x: &Fr, witness: &mut NonMembershipProof, update: &MerkleUpdate) -> bool {
+        // a change in our own slot (x itself, or a colliding key) needs a fresh proof
+        update_path(&mut witness.path, update, &params.tree.config)
+    }
+
+    // Neither the key nor the path leaves the holder, and Cx is fresh
+    fn prove(params: &MerkleZkParams, state: &Fr, x: &Fr, witness: &NonMembershipProof, transcript: &mut Transcript) -> Option<(G1Projective, MerkleZkProof)> {
+        gen_non_membership_zkp(params, state, x, witness, transcript)
+    }
+
+    fn verify(params: &MerkleZkParams, state: &Fr, proof: &(G1Projective, MerkleZkProof), transcript: &mut Transcript) -> bool {
+        verify_non_membership_zkp(params, state, &proof.0, &proof.1, transcript)
+    }
+}
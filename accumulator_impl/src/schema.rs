@@ -0,0 +1,260 @@
+// Credential schemas: named, typed attributes and their canonical encoding
+// into the Fr messages the issuer signs.
+//
+//   Integer   the value itself (u64)
+//   Date      "YYYY-MM-DD" as a day number, 0001-01-01 = 1 (Rata Die), so
+//             comparisons and range proofs on dates are plain integer ones
+//   String    SHA-256 of the UTF-8 bytes, reduced mod p
+//   Enum      position of the variant in the schema's variant list
+//
+// A credential signs [schema id, attr_1, ..., attr_n, x]: the schema id is
+// a hash of the schema definition, so a signature cannot be reinterpreted
+// under another schema (or another version of the same one). Schemas with
+// validity end in two Integer attributes valid_from, valid_until (unix
+// seconds), see validity.rs.
+
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+use crate::validity::{VALID_FROM, VALID_UNTIL};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AttributeType {
+    Integer,
+    Date,
+    String,
+    Enum { variants: Vec<String> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttributeDef {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: AttributeType,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttributeValue {
+    Integer(u64),
+    Date(String), // YYYY-MM-DD
+    String(String),
+    Enum(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaError {
+    DuplicateAttribute(String),
+    UnknownAttribute(String),
+    MissingAttribute(String),
+    TypeMismatch { attribute: String, expected: AttributeType },
+    InvalidDate { attribute: String, value: String },
+    UnknownVariant { attribute: String, value: String },
+    // messages or signature parameters sized for another schema
+    MessageCountMismatch { expected: usize, got: usize },
+    WrongSchema,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialSchema {
+    pub name: String,
+    pub version: String,
+    pub attributes: Vec<AttributeDef>,
+}
+
+// Day number of a proleptic Gregorian date, 0001-01-01 = 1; None if invalid
+pub fn date_to_day_number(year: i64, month: u32, day: u32) -> Option<u64> {
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = [31, if leap { 29 } else { 28 }, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    if year < 1 || month == 0 || month > 12 || day == 0 || day > days_in_month[month as usize - 1] {
+        return None;
+    }
+    // days from civil (H. Hinnant), shifted from the unix epoch to Rata Die
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some((era * 146_097 + doe - 719_468 + 719_163) as u64)
+}
+
+// Inverse of date_to_day_number, as "YYYY-MM-DD"; None outside years 1..=9999
+pub fn day_number_to_date(n: u64) -> Option<String> {
+    // civil from days (H. Hinnant), from Rata Die back to the unix epoch
+    let z = n as i64 - 719_163 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    if n == 0 || !(1..=9999).contains(&year) {
+        return None;
+    }
+    Some(format!("{:04}-{:02}-{:02}", year, month, day))
+}
+
+pub fn parse_date(value: &str) -> Option<u64> {
+    let parts: Vec<&str> = value.split('-').collect();
+    if parts.len() != 3 || parts[0].len() != 4 || parts[1].len() != 2 || parts[2].len() != 2 {
+        return None;
+    }
+    date_to_day_number(parts[0].parse().ok()?, parts[1].parse().ok()?, parts[2].parse().ok()?)
+}
+
+pub fn encode_string(value: &str) -> Fr {
+    let mut hasher = Sha256::new();
+    hasher.update(b"vac-schema-string");
+    hasher.update(value.as_bytes());
+    Fr::from_le_bytes_mod_order(&hasher.finalize())
+}
+
+impl CredentialSchema {
+    pub fn new(name: &str, version: &str, attributes: Vec<AttributeDef>) -> Result<Self, SchemaError> {
+        for (i, attr) in attributes.iter().enumerate() {
+            if attributes[..i].iter().any(|a| a.name == attr.name) {
+                return Err(SchemaError::DuplicateAttribute(attr.name.clone()));
+            }
+        }
+        Ok(CredentialSchema { name: name.to_string(), version: version.to_string(), attributes })
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let schema: CredentialSchema = serde_json::from_str(json)?;
+        Self::new(&schema.name, &schema.version, schema.attributes).map_err(|e| format!("{:?}", e).into())
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    // SHA-256 of the canonical JSON definition
+    pub fn id(&self) -> String {
+        hex::encode(Sha256::digest(self.to_json().as_bytes()))
+    }
+
+    pub fn id_fr(&self) -> Fr {
+        Fr::from_le_bytes_mod_order(&Sha256::digest(self.to_json().as_bytes()))
+    }
+
+    // Signed messages: schema id, the attributes and the holder secret x
+    pub fn message_count(&self) -> usize {
+        self.attributes.len() + 2
+    }
+
+    // Index of the attribute among the signed messages
+    pub fn message_index(&self, name: &str) -> Option<usize> {
+        self.attributes.iter().position(|a| a.name == name).map(|i| i + 1)
+    }
+
+    // Attribute value from JSON: a number for Integer, a string otherwise
+    pub fn value_from_json(&self, name: &str, value: &serde_json::Value) -> Result<AttributeValue, SchemaError> {
+        let attr = self.attributes.iter().find(|a| a.name == name).ok_or_else(|| SchemaError::UnknownAttribute(name.to_string()))?;
+        let parsed = match (&attr.kind, value) {
+            (AttributeType::Integer, serde_json::Value::Number(n)) => n.as_u64().map(AttributeValue::Integer),
+            (AttributeType::Date, serde_json::Value::String(s)) => Some(AttributeValue::Date(s.clone())),
+            (AttributeType::String, serde_json::Value::String(s)) => Some(AttributeValue::String(s.clone())),
+            (AttributeType::Enum { .. }, serde_json::Value::String(s)) => Some(AttributeValue::Enum(s.clone())),
+            _ => None,
+        };
+        parsed.ok_or_else(|| SchemaError::TypeMismatch { attribute: name.to_string(), expected: attr.kind.clone() })
+    }
+
+    // A JSON object of attribute values, e.g. {"name": "Alice", "age": 21}
+    pub fn values_from_json(&self, json: &str) -> Result<BTreeMap<String, AttributeValue>, Box<dyn std::error::Error>> {
+        let object: BTreeMap<String, serde_json::Value> = serde_json::from_str(json)?;
+        let mut values = BTreeMap::new();
+        for (name, value) in object.iter() {
+            let value = self.value_from_json(name, value).map_err(|e| format!("{:?}", e))?;
+            values.insert(name.clone(), value);
+        }
+        Ok(values)
+    }
+
+    pub fn encode_value(&self, name: &str, value: &AttributeValue) -> Result<Fr, SchemaError> {
+        let attr = self
+            .attributes
+            .iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| SchemaError::UnknownAttribute(name.to_string()))?;
+        let mismatch = || SchemaError::TypeMismatch { attribute: name.to_string(), expected: attr.kind.clone() };
+        match (&attr.kind, value) {
+            (AttributeType::Integer, AttributeValue::Integer(v)) => Ok(Fr::from(*v)),
+            (AttributeType::Date, AttributeValue::Date(v)) => parse_date(v)
+                .map(Fr::from)
+                .ok_or_else(|| SchemaError::InvalidDate { attribute: name.to_string(), value: v.clone() }),
+            (AttributeType::String, AttributeValue::String(v)) => Ok(encode_string(v)),
+            (AttributeType::Enum { variants }, AttributeValue::Enum(v)) => variants
+                .iter()
+                .position(|variant| variant == v)
+                .map(|i| Fr::from(i as u64))
+                .ok_or_else(|| SchemaError::UnknownVariant { attribute: name.to_string(), value: v.clone() }),
+            _ => Err(mismatch()),
+        }
+    }
+
+    // Appends the valid_from/valid_until attributes; the schema id changes
+    pub fn with_validity(self) -> Result<Self, SchemaError> {
+        let mut attributes = self.attributes;
+        for name in [VALID_FROM, VALID_UNTIL] {
+            attributes.push(AttributeDef { name: name.to_string(), kind: AttributeType::Integer });
+        }
+        Self::new(&self.name, &self.version, attributes)
+    }
+
+    // Message indices of valid_from and valid_until, if both are Integer attributes
+    pub fn validity_indices(&self) -> Option<(usize, usize)> {
+        let index = |name: &str| {
+            let i = self.message_index(name)?;
+            (self.attributes[i - 1].kind == AttributeType::Integer).then_some(i)
+        };
+        Some((index(VALID_FROM)?, index(VALID_UNTIL)?))
+    }
+
+    // [schema id, attr_1, ..., attr_n] in schema order; the issuer appends x
+    pub fn encode(&self, values: &BTreeMap<String, AttributeValue>) -> Result<Vec<Fr>, SchemaError> {
+        if let Some(unknown) = values.keys().find(|k| self.message_index(k).is_none()) {
+            return Err(SchemaError::UnknownAttribute(unknown.clone()));
+        }
+        let mut messages = vec![self.id_fr()];
+        for attr in self.attributes.iter() {
+            let value = values.get(&attr.name).ok_or_else(|| SchemaError::MissingAttribute(attr.name.clone()))?;
+            messages.push(self.encode_value(&attr.name, value)?);
+        }
+        Ok(messages)
+    }
+
+    // Signed messages of a credential of this schema (x included)
+    pub fn check_messages(&self, signed: &[Fr]) -> Result<(), SchemaError> {
+        if signed.len() != self.message_count() {
+            return Err(SchemaError::MessageCountMismatch { expected: self.message_count(), got: signed.len() });
+        }
+        if signed[0] != self.id_fr() {
+            return Err(SchemaError::WrongSchema);
+        }
+        Ok(())
+    }
+}
+
+// Schema of the exam credentials used in main.rs
+pub fn exam_candidate_schema() -> CredentialSchema {
+    let attr = |name: &str, kind: AttributeType| AttributeDef { name: name.to_string(), kind };
+    CredentialSchema::new(
+        "exam-candidate",
+        "1",
+        vec![
+            attr("name", AttributeType::String),
+            attr("dob", AttributeType::Date),
+            attr("age", AttributeType::Integer),
+            attr("score", AttributeType::Integer),
+            attr("candidate_id", AttributeType::Integer),
+        ],
+    )
+    .unwrap()
+}
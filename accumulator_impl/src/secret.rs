@@ -0,0 +1,79 @@
+// Wrapper for secret material: x, r, sk, witnesses and ZKP blinding factors.
+//
+//   - wiped when dropped (ZeroizeOnDrop), so nothing has to remember to call
+//     a destructor
+//   - not Clone; copies are made on purpose with clone_secret()
+//   - Debug prints [REDACTED]
+//   - == compares the canonical encodings in constant time
+//
+// expose() hands out a reference for the arithmetic; copying the value out
+// of it (e.g. *secret.expose() for an Fr) leaves an unprotected copy behind.
+
+use ark_serialize::CanonicalSerialize;
+use std::fmt;
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Secret(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn expose_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize + Clone> Secret<T> {
+    pub fn clone_secret(&self) -> Self {
+        Secret(self.0.clone())
+    }
+}
+
+impl<T: Zeroize> Zeroize for Secret<T> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> ZeroizeOnDrop for Secret<T> {}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Secret(value)
+    }
+}
+
+impl<T: Zeroize> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl<T: Zeroize + CanonicalSerialize> PartialEq for Secret<T> {
+    fn eq(&self, other: &Self) -> bool {
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        self.0.serialize_compressed(&mut a).unwrap();
+        other.0.serialize_compressed(&mut b).unwrap();
+        // lengths of canonical encodings are public (fixed per type)
+        let equal = a.len() == b.len() && bool::from(a.ct_eq(&b));
+        a.zeroize();
+        b.zeroize();
+        equal
+    }
+}
+
+impl<T: Zeroize + CanonicalSerialize> Eq for Secret<T> {}
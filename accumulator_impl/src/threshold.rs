@@ -0,0 +1,406 @@
+// t-of-n issuer: the BBS+/accumulator key sk only ever exists as Shamir shares.
+//
+//   key generation   Feldman DKG, every party deals a random degree t-1
+//                    polynomial and the shares are checked against the
+//                    published commitments g2^{a_k}
+//   sk-inversion     shares of 1/(y + sk) for a public y (the BBS+ e, or a
+//                    credential x) with the masking trick of Bar-Ilan and
+//                    Beaver: open u = r(y + sk) for a joint random r, then
+//                    each party holds r_j / u
+//   sk-multiply      alpha (y + sk) is linear in sk, t partials suffice
+//
+// The opening of u multiplies two degree t-1 sharings, so inversions need
+// 2t-1 signers (n >= 2t-1). The opened u_j also carry a joint degree 2t-2
+// sharing of zero: r(X)(y + f(X)) alone factors, and with y public two
+// openings would give away f and sk = f(0). Parties are assumed honest-but-curious; every
+// combined result is checked against the public key before it is used.
+// ThresholdIssuer runs all parties in one process; in a deployment each
+// ThresholdParty lives on its own machine and only the dealings, u_j and
+// partial points cross the network.
+
+use ark_bls12_381::{Bls12_381, Fr, G1Projective, G2Projective};
+use ark_ec::CurveGroup;
+use ark_ff::{Field, One, Zero};
+use ark_std::UniformRand;
+use bbs_plus::prelude::*;
+use rand::{thread_rng, RngCore};
+use std::collections::{BTreeMap, BTreeSet};
+use zeroize::Zeroize;
+
+use crate::acc::ECAccumulator;
+use crate::credential::Credential;
+use crate::helper::verify_witness;
+use crate::keystore::IssuerPublicKeys;
+use crate::revocation::AccRevocation;
+use crate::secret::Secret;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ThresholdError {
+    NotEnoughParties { needed: usize, got: usize },
+    UnknownParty(u64),
+    DuplicateParty(u64),
+    // the share dealt to `receiver` does not match the dealer's commitments
+    BadShare { dealer: u64, receiver: u64 },
+    // y = -sk: 1/(y + sk) does not exist, pick another y
+    NotInvertible,
+    // the combined signature/accumulator value does not verify
+    InvalidResult,
+    AlreadyRevoked,
+}
+
+// One party's contribution to a sharing: Feldman commitments to its
+// polynomial (public) and the share for every party (sent privately)
+#[derive(Clone, Debug)]
+pub struct Dealing {
+    pub dealer: u64,
+    pub commitments: Vec<G2Projective>,
+    pub shares: BTreeMap<u64, Fr>,
+}
+
+fn eval_poly(coeffs: &[Fr], at: u64) -> Fr {
+    let at = Fr::from(at);
+    coeffs.iter().rev().fold(Fr::zero(), |acc, c| acc * at + c)
+}
+
+fn dealing(dealer: u64, mut coeffs: Vec<Fr>, ids: &[u64], g2: &G2Projective) -> Dealing {
+    let commitments = coeffs.iter().map(|a| *g2 * a).collect();
+    let shares = ids.iter().map(|id| (*id, eval_poly(&coeffs, *id))).collect();
+    coeffs.zeroize();
+    Dealing { dealer, commitments, shares }
+}
+
+// Random polynomial of degree t-1, shared among ids
+pub fn deal<R: RngCore>(dealer: u64, t: usize, ids: &[u64], g2: &G2Projective, rng: &mut R) -> Dealing {
+    dealing(dealer, (0..t).map(|_| Fr::rand(rng)).collect(), ids, g2)
+}
+
+// Random polynomial of degree 2t-2 with constant term 0 (commitments[0] is
+// the identity), shared among ids
+pub fn deal_zero<R: RngCore>(dealer: u64, t: usize, ids: &[u64], g2: &G2Projective, rng: &mut R) -> Dealing {
+    let coeffs = (0..2 * t - 1).map(|k| if k == 0 { Fr::zero() } else { Fr::rand(rng) }).collect();
+    dealing(dealer, coeffs, ids, g2)
+}
+
+// g2^share == prod_k C_k^{id^k}
+pub fn verify_share(commitments: &[G2Projective], id: u64, share: &Fr, g2: &G2Projective) -> bool {
+    let id = Fr::from(id);
+    let expected = commitments.iter().rev().fold(G2Projective::zero(), |acc, c| acc * id + c);
+    *g2 * share == expected
+}
+
+// Lagrange coefficients for interpolating at 0 from the points in ids
+pub fn lagrange_coefficients(ids: &[u64]) -> Vec<Fr> {
+    ids.iter()
+        .map(|i| {
+            let (mut num, mut den) = (Fr::one(), Fr::one());
+            for j in ids.iter().filter(|j| *j != i) {
+                num *= Fr::from(*j);
+                den *= Fr::from(*j) - Fr::from(*i);
+            }
+            num * den.inverse().expect("ids are distinct")
+        })
+        .collect()
+}
+
+// Value at 0 of the polynomial through (ids[i], values[i])
+pub fn open(ids: &[u64], values: &[Fr]) -> Fr {
+    lagrange_coefficients(ids).iter().zip(values).map(|(l, v)| *l * v).sum()
+}
+
+// Same in the exponent: combines partials base^{f(id)} into base^{f(0)}
+pub fn combine(ids: &[u64], partials: &[G1Projective]) -> G1Projective {
+    lagrange_coefficients(ids).iter().zip(partials).map(|(l, p)| *p * l).sum()
+}
+
+pub struct ThresholdParty {
+    id: u64,
+    share: Secret<SecretKey<Fr>>, // sk_j = f(j), wiped on drop
+    verification_key: G2Projective, // g2^{sk_j}
+}
+
+impl ThresholdParty {
+    // Checks every dealing against its commitments and sums the shares
+    pub fn from_dealings(id: u64, dealings: &[Dealing], g2: &G2Projective) -> Result<Self, ThresholdError> {
+        let mut share = Fr::zero();
+        for dealing in dealings {
+            let s = dealing.shares.get(&id).ok_or(ThresholdError::UnknownParty(id))?;
+            if !verify_share(&dealing.commitments, id, s, g2) {
+                return Err(ThresholdError::BadShare { dealer: dealing.dealer, receiver: id });
+            }
+            share += s;
+        }
+        let verification_key = *g2 * share;
+        let party = Self { id, share: Secret::new(SecretKey(share)), verification_key };
+        share.zeroize();
+        Ok(party)
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn get_verification_key(&self) -> &G2Projective {
+        &self.verification_key
+    }
+
+    // u_j = r_j (y + sk_j) + o_j, a point on a degree 2t-2 polynomial; o_j is
+    // the party's share of zero
+    pub fn masked(&self, y: &Fr, r_share: &Fr, zero_share: &Fr) -> Fr {
+        *r_share * (*y + self.share.expose().0) + zero_share
+    }
+
+    // base^{r_j / u}: the t partials combine to base^{1/(y + sk)}
+    pub fn partial_inverse(&self, base: &G1Projective, r_share: &Fr, u: &Fr) -> G1Projective {
+        *base * (*r_share * u.inverse().expect("u checked by the caller"))
+    }
+
+    // base^{y + sk_j}: the t partials combine to base^{y + sk}
+    pub fn partial_multiply(&self, base: &G1Projective, y: &Fr) -> G1Projective {
+        *base * (*y + self.share.expose().0)
+    }
+
+    pub fn destructor(&mut self) {
+        self.share.zeroize();
+    }
+}
+
+// Joint public key g2^sk from the DKG commitments
+pub fn joint_public_key(dealings: &[Dealing]) -> G2Projective {
+    dealings.iter().map(|d| d.commitments[0]).sum()
+}
+
+pub struct ThresholdIssuer {
+    key_id: u32,
+    t: usize,
+    parties: BTreeMap<u64, ThresholdParty>,
+    pk: PublicKeyG2<Bls12_381>,
+    sig_param: SignatureParamsG1<Bls12_381>,
+    // Accumulator parameters; g2 is the BBS+ g2, so j = g2^sk is also the pk
+    g1: G1Projective,
+    g2: G2Projective,
+    j: G2Projective,
+    // Parameter for Perdersen Commitment (public for Holder hidding the data)
+    g: G1Projective,
+    h: G1Projective,
+    k: G1Projective,
+    z: G1Projective,
+}
+
+impl ThresholdIssuer {
+    // Parties are numbered 1..=n and run the DKG among themselves
+    pub fn new(t: usize, n: usize) -> Result<Self, ThresholdError> {
+        if t == 0 || n < 2 * t - 1 {
+            return Err(ThresholdError::NotEnoughParties { needed: (2 * t).max(2) - 1, got: n });
+        }
+        let mut rng = thread_rng();
+        // 6 messages: [Name, DOB, Age, Score, Candidate_ID] || x
+        let sig_param = SignatureParamsG1::<Bls12_381>::generate_using_rng(&mut rng, 6);
+        let g2 = sig_param.g2.into();
+
+        let ids: Vec<u64> = (1..=n as u64).collect();
+        let dealings: Vec<Dealing> = ids.iter().map(|id| deal(*id, t, &ids, &g2, &mut rng)).collect();
+        let mut parties = BTreeMap::new();
+        for id in ids.iter() {
+            parties.insert(*id, ThresholdParty::from_dealings(*id, &dealings, &g2)?);
+        }
+        let j = joint_public_key(&dealings);
+
+        Ok(Self {
+            key_id: 1,
+            t,
+            parties,
+            pk: PublicKeyG2(j.into_affine()),
+            sig_param,
+            g1: G1Projective::rand(&mut rng),
+            g2,
+            j,
+            g: G1Projective::rand(&mut rng),
+            h: G1Projective::rand(&mut rng),
+            k: G1Projective::rand(&mut rng),
+            z: G1Projective::rand(&mut rng),
+        })
+    }
+
+    pub fn get_key_id(&self) -> u32 {
+        self.key_id
+    }
+
+    pub fn get_threshold(&self) -> usize {
+        self.t
+    }
+
+    pub fn get_party(&self, id: u64) -> Option<&ThresholdParty> {
+        self.parties.get(&id)
+    }
+
+    // Same as Issuer::public_keys: what verifiers and witness updates need
+    pub fn public_keys(&self) -> IssuerPublicKeys {
+        IssuerPublicKeys {
+            key_id: self.key_id,
+            pk: self.pk.clone(),
+            sig_param: self.sig_param.clone(),
+            g1: self.g1, g2: self.g2, j: self.j,
+            g: self.g, h: self.h, k: self.k, z: self.z,
+        }
+    }
+
+    pub fn get_pk(&self) -> &PublicKeyG2<Bls12_381> {
+        &self.pk
+    }
+
+    pub fn get_sig_param(&self) -> &SignatureParamsG1<Bls12_381> {
+        &self.sig_param
+    }
+
+    pub fn get_g1(&self) -> &G1Projective {
+        &self.g1
+    }
+
+    pub fn get_g2(&self) -> &G2Projective {
+        &self.g2
+    }
+
+    pub fn get_j(&self) -> &G2Projective {
+        &self.j
+    }
+
+    pub fn get_g_h_k_z(&self) -> (&G1Projective, &G1Projective, &G1Projective, &G1Projective) {
+        (&self.g, &self.h, &self.k, &self.z)
+    }
+
+    fn check_signers(&self, signers: &[u64], needed: usize) -> Result<(), ThresholdError> {
+        let mut seen = BTreeSet::new();
+        for id in signers {
+            if !self.parties.contains_key(id) {
+                return Err(ThresholdError::UnknownParty(*id));
+            }
+            if !seen.insert(*id) {
+                return Err(ThresholdError::DuplicateParty(*id));
+            }
+        }
+        if signers.len() < needed {
+            return Err(ThresholdError::NotEnoughParties { needed, got: signers.len() });
+        }
+        Ok(())
+    }
+
+    // Shares r_j of a joint random r and the values u_j the signers open;
+    // they interpolate to u = r (y + sk) at 0 and to nothing useful elsewhere
+    pub fn mask(&self, signers: &[u64], y: &Fr) -> Result<(Vec<Fr>, Vec<Fr>), ThresholdError> {
+        self.check_signers(signers, 2 * self.t - 1)?;
+        let mut rng = thread_rng();
+        let joint = |dealings: Vec<Dealing>| -> Vec<Fr> {
+            signers.iter().map(|id| dealings.iter().map(|d| d.shares[id]).sum()).collect()
+        };
+
+        let r_shares = joint(signers.iter().map(|id| deal(*id, self.t, signers, &self.g2, &mut rng)).collect());
+        let mut zero_shares = joint(signers.iter().map(|id| deal_zero(*id, self.t, signers, &self.g2, &mut rng)).collect());
+        let masked = signers
+            .iter()
+            .zip(r_shares.iter().zip(&zero_shares))
+            .map(|(id, (r, o))| self.parties[id].masked(y, r, o))
+            .collect();
+        zero_shares.zeroize();
+        Ok((r_shares, masked))
+    }
+
+    // base^{1/(y + sk)} computed by the signers, none of them learns sk
+    pub fn invert_on(&self, signers: &[u64], base: &G1Projective, y: &Fr) -> Result<G1Projective, ThresholdError> {
+        let (mut r_shares, masked) = self.mask(signers, y)?;
+        let u = open(signers, &masked);
+        if u.is_zero() {
+            return Err(ThresholdError::NotInvertible);
+        }
+
+        let partials: Vec<G1Projective> = signers
+            .iter()
+            .zip(&r_shares)
+            .map(|(id, r)| self.parties[id].partial_inverse(base, r, &u))
+            .collect();
+        r_shares.zeroize();
+        Ok(combine(signers, &partials))
+    }
+
+    // base^{y + sk}; only t signers are needed
+    pub fn multiply_on(&self, signers: &[u64], base: &G1Projective, y: &Fr) -> Result<G1Projective, ThresholdError> {
+        self.check_signers(signers, self.t)?;
+        let partials: Vec<G1Projective> = signers.iter().map(|id| self.parties[id].partial_multiply(base, y)).collect();
+        Ok(combine(signers, &partials))
+    }
+
+    // BBS+ signature A = B^{1/(e + sk)} with B = g1 h_0^s prod h_i^{m_i}
+    pub fn sign(&self, signers: &[u64], messages: &[Fr]) -> Result<SignatureG1<Bls12_381>, ThresholdError> {
+        let mut rng = thread_rng();
+        let e = Fr::rand(&mut rng);
+        let s = Fr::rand(&mut rng);
+        let b = self.sig_param.b(messages.iter().enumerate(), &s).map_err(|_| ThresholdError::InvalidResult)?;
+        let a = self.invert_on(signers, &b, &e)?;
+
+        let signature = SignatureG1 { A: a.into_affine(), e, s };
+        signature
+            .verify(messages, self.pk.clone(), self.sig_param.clone())
+            .map_err(|_| ThresholdError::InvalidResult)?;
+        Ok(signature)
+    }
+
+    // Threshold version of Issuer::gen_witness_n_cred for the ECAccumulator
+    pub fn gen_witness_n_cred(&self, signers: &[u64], accumulator: &ECAccumulator, messages: Vec<Fr>) -> Result<Credential, ThresholdError> {
+        let mut rng = thread_rng();
+        // x in Z_p* / {-sk}: the inversion itself reports x = -sk
+        let (x, witness) = loop {
+            let candidate = Fr::rand(&mut rng);
+            match self.invert_on(signers, &accumulator.alpha, &candidate) {
+                Ok(witness) => break (candidate, witness),
+                Err(ThresholdError::NotInvertible) => continue,
+                Err(e) => return Err(e),
+            }
+        };
+        if !verify_witness(&accumulator.alpha, &x, &witness, &self.g2, &self.j) {
+            return Err(ThresholdError::InvalidResult);
+        }
+
+        let r = Fr::rand(&mut rng);
+        let mut msg_and_x = Secret::new(messages.clone());
+        msg_and_x.expose_mut().push(x); // X || x
+        let signature = self.sign(signers, msg_and_x.expose())?;
+
+        Ok(Credential::new(signature, messages, x, r, witness, self.key_id))
+    }
+
+    // Threshold version of Issuer::revoke_a_cred: alpha' = alpha^{1/(x + sk)}
+    pub fn revoke_a_cred(&self, signers: &[u64], accumulator: &mut ECAccumulator, cred: &Credential) -> Result<AccRevocation, ThresholdError> {
+        let x = cred.get_x_val();
+        if accumulator.deltas.contains(x) {
+            return Err(ThresholdError::AlreadyRevoked);
+        }
+        let new_alpha = self.invert_on(signers, &accumulator.alpha, x)?;
+        if !verify_witness(&accumulator.alpha, x, &new_alpha, &self.g2, &self.j) {
+            return Err(ThresholdError::InvalidResult);
+        }
+        accumulator.deltas.push(*x);
+        accumulator.alpha = new_alpha;
+        accumulator.epoch += 1;
+        Ok(AccRevocation { delta: *x, new_alpha })
+    }
+
+    // Threshold version of Issuer::reinstate_a_cred: alpha' = alpha^{x + sk}.
+    // Returns the old alpha, which is also the reinstated holder's new witness
+    pub fn reinstate_a_cred(&self, signers: &[u64], accumulator: &mut ECAccumulator, cred: &Credential) -> Result<G1Projective, ThresholdError> {
+        let x = cred.get_x_val();
+        let old_alpha = accumulator.alpha;
+        let new_alpha = self.multiply_on(signers, &old_alpha, x)?;
+        if !verify_witness(&new_alpha, x, &old_alpha, &self.g2, &self.j) {
+            return Err(ThresholdError::InvalidResult);
+        }
+        accumulator.deltas.retain(|delta| delta != x);
+        accumulator.alpha = new_alpha;
+        accumulator.epoch += 1;
+        Ok(old_alpha)
+    }
+
+    pub fn destructor(&mut self) {
+        for party in self.parties.values_mut() {
+            party.destructor();
+        }
+    }
+}
@@ -0,0 +1,93 @@
+// Fiat-Shamir transcripts for every proof in the crate, on Merlin.
+//
+// A transcript absorbs, in this order:
+//
+//   protocol    PROTOCOL and VERSION, fixed for the crate
+//   proof       what the transcript is for (b"presentation", b"expiry", ...)
+//   parameters  the issuer's public keys and generators
+//   epoch       the accumulator epoch, where there is one
+//   session     the verifier challenge (replay.rs)
+//   statement   what is being proven, then the prover's commitments
+//
+// and only then yields challenges. Sub-proofs sharing a transcript (the
+// membership proof, the link and pseudonym proofs, the range proofs and the
+// BBS+ PoK of a presentation) add their own domain separator before their
+// statement, so each one's challenge also covers everything proven before it. Merlin frames every message with
+// its label and length, so no two transcripts absorb the same bytes.
+//
+// Bumping VERSION invalidates all existing proofs; do so whenever what a
+// proof absorbs changes.
+
+use ark_bls12_381::Fr;
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use merlin::Transcript;
+
+use crate::keystore::IssuerPublicKeys;
+use crate::replay::VerifierChallenge;
+
+pub const PROTOCOL: &[u8] = b"vac";
+pub const VERSION: u64 = 2;
+
+// Proof type of a standalone membership proof (Holder::prove_membership)
+pub const MEMBERSHIP_PROOF: &[u8] = b"membership";
+
+pub trait TranscriptProtocol {
+    // Starts a sub-proof
+    fn domain_sep(&mut self, proof: &'static [u8]);
+    fn append_serialized<T: CanonicalSerialize>(&mut self, label: &'static [u8], value: &T);
+    fn append_issuer(&mut self, keys: &IssuerPublicKeys);
+    fn append_session(&mut self, challenge: &VerifierChallenge);
+    // Wide reduction of 64 transcript bytes
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Fr;
+}
+
+impl TranscriptProtocol for Transcript {
+    fn domain_sep(&mut self, proof: &'static [u8]) {
+        self.append_message(b"dom-sep", proof);
+    }
+
+    fn append_serialized<T: CanonicalSerialize>(&mut self, label: &'static [u8], value: &T) {
+        let mut bytes = Vec::new();
+        value.serialize_compressed(&mut bytes).unwrap();
+        self.append_message(label, &bytes);
+    }
+
+    fn append_issuer(&mut self, keys: &IssuerPublicKeys) {
+        self.append_u64(b"key-id", keys.key_id as u64);
+        self.append_message(b"issuer", &keys.to_bytes());
+    }
+
+    fn append_session(&mut self, challenge: &VerifierChallenge) {
+        self.append_message(b"session", &challenge.to_bytes());
+    }
+
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Fr {
+        let mut bytes = [0u8; 64];
+        self.challenge_bytes(label, &mut bytes);
+        Fr::from_le_bytes_mod_order(&bytes)
+    }
+}
+
+// Protocol and version tags, then the proof type
+pub fn new_transcript(proof: &'static [u8]) -> Transcript {
+    let mut transcript = Transcript::new(PROTOCOL);
+    transcript.append_u64(b"version", VERSION);
+    transcript.append_message(b"proof", proof);
+    transcript
+}
+
+// Transcript for proofs about a credential of `keys`, at accumulator `epoch`,
+// for a verifier session
+pub fn session_transcript(
+    proof: &'static [u8],
+    keys: &IssuerPublicKeys,
+    epoch: u64,
+    challenge: &VerifierChallenge,
+) -> Transcript {
+    let mut transcript = new_transcript(proof);
+    transcript.append_issuer(keys);
+    transcript.append_u64(b"epoch", epoch);
+    transcript.append_session(challenge);
+    transcript
+}
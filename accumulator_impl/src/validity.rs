@@ -0,0 +1,381 @@
+// Validity periods and zero-knowledge expiry proofs.
+//
+// A schema with validity (CredentialSchema::with_validity) signs two more
+// Integer attributes, valid_from and valid_until, as unix seconds. The holder
+// shows that a verifier-chosen time `now` lies inside the window without
+// revealing either bound, in a proof bound to the verifier's challenge
+// (replay.rs). Its transcript (transcript.rs) holds the issuer's key, signature
+// parameters and generators, now and the challenge, then:
+//
+//   pok      BBS+ proof of knowledge of the signature, everything hidden but
+//            the schema id
+//   until    range proof that valid_until - now is in [0, 2^64)
+//   from     range proof that now - valid_from is in [0, 2^64)
+//
+// The range proofs commit to the differences with the issuer's Pedersen
+// generators (g, h). Each bound is tied to the signature by a Schnorr proof
+// that uses the same blinding as the BBS+ proof for that message, so both
+// proofs have to give the same response for it. A bound in the wrong order
+// wraps around mod p and no 64-bit decomposition exists.
+//
+// Range proof: C = g*d + h*r is sent as bit commitments C_i = g*b_i + h*r_i
+// with r = sum 2^i r_i, so C = sum 2^i C_i, and each C_i carries a
+// Cramer-Damgard-Schoenmakers OR proof that b_i is 0 or 1. Presentations
+// (presentation.rs) reuse the same range proofs for attribute predicates.
+
+use ark_bls12_381::{Bls12_381, Fr, G1Projective};
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{UniformRand, Zero};
+use bbs_plus::prelude::*;
+use bbs_plus::proof::{PoKOfSignatureG1Proof, PoKOfSignatureG1Protocol};
+use dock_crypto_utils::signature::{MessageOrBlinding, MultiMessageSignatureParams};
+use merlin::Transcript;
+use rand::thread_rng;
+use std::collections::{BTreeMap, BTreeSet};
+use zeroize::Zeroize;
+
+use crate::credential::Credential;
+use crate::replay::VerifierChallenge;
+use crate::schema::CredentialSchema;
+use crate::transcript::{new_transcript, TranscriptProtocol};
+
+pub const VALID_FROM: &str = "valid_from";
+pub const VALID_UNTIL: &str = "valid_until";
+pub const RANGE_BITS: usize = 64;
+
+// Validity window in unix seconds, both ends included
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidityPeriod {
+    pub valid_from: u64,
+    pub valid_until: u64,
+}
+
+impl ValidityPeriod {
+    // None if the window is empty
+    pub fn new(valid_from: u64, valid_until: u64) -> Option<Self> {
+        if valid_from > valid_until {
+            return None;
+        }
+        Some(ValidityPeriod { valid_from, valid_until })
+    }
+
+    pub fn contains(&self, now: u64) -> bool {
+        self.valid_from <= now && now <= self.valid_until
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidityError {
+    NoValidityPeriod, // the schema or the credential has no validity attributes
+    NotYetValid,
+    Expired,
+    ProofFailed,
+}
+
+// Proof of the bit b committed in C_i, one branch real and the other simulated
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+struct BitProof {
+    commitment: G1Projective,
+    a0: G1Projective,
+    a1: G1Projective,
+    c0: Fr, // c1 = c - c0
+    z0: Fr,
+    z1: Fr,
+}
+
+// Proof that the value committed in sum 2^i C_i is in [0, 2^RANGE_BITS), and
+// that value + offset (or offset - value) is the signed message
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct RangeProof {
+    bits: Vec<BitProof>,
+    t: G1Projective, // Schnorr commitment linking the message to C
+    s_r: Fr,
+}
+
+#[derive(Clone, Debug)]
+pub struct ExpiryProof {
+    pok: PoKOfSignatureG1Proof<Bls12_381>,
+    schema_id: Fr,
+    until: RangeProof,
+    from: RangeProof,
+}
+
+fn pow2(i: usize) -> Fr {
+    Fr::from(2u64).pow([i as u64])
+}
+
+fn combined(bits: &[BitProof]) -> G1Projective {
+    bits.iter().enumerate().map(|(i, b)| b.commitment * pow2(i)).sum()
+}
+
+// Bound on a signed message m; the range proof commits to the distance
+// d = m - b (AtLeast) or d = b - m (AtMost)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Bound {
+    AtLeast(u64),
+    AtMost(u64),
+}
+
+// Statements of the linking proof: g*m + h*rho = target
+//   AtLeast(b):  target = C + g*b,  rho = r    (valid_until >= now)
+//   AtMost(b):   target = g*b - C,  rho = -r   (valid_from <= now)
+fn link_target(g: &G1Projective, c: &G1Projective, bound: Bound) -> G1Projective {
+    match bound {
+        Bound::AtLeast(b) => *c + *g * Fr::from(b),
+        Bound::AtMost(b) => *g * Fr::from(b) - *c,
+    }
+}
+
+// Statement and commitments of a range proof
+fn append_range(transcript: &mut Transcript, bound: Bound, bits: &[&BitProof], t: &G1Projective) {
+    transcript.domain_sep(b"range");
+    match bound {
+        Bound::AtLeast(b) => transcript.append_u64(b"at-least", b),
+        Bound::AtMost(b) => transcript.append_u64(b"at-most", b),
+    }
+    let mut bytes = Vec::new();
+    for p in bits {
+        for point in [p.commitment, p.a0, p.a1] {
+            point.serialize_compressed(&mut bytes).unwrap();
+        }
+    }
+    t.serialize_compressed(&mut bytes).unwrap();
+    transcript.append_message(b"bits", &bytes);
+}
+
+// Prover state between the commitments and the challenge
+pub(crate) struct RangeProtocol {
+    bound: Bound,
+    bits: Vec<(bool, Fr, Fr, BitProof)>, // bit, r_i, k_i (nonce of the real branch), partial proof
+    rho: Fr,
+    rho_blinding: Fr,
+    t: G1Projective,
+}
+
+impl RangeProtocol {
+    // value is the distance d; m_blinding the BBS+ blinding of the message
+    pub(crate) fn init(g: &G1Projective, h: &G1Projective, value: u64, bound: Bound, m_blinding: &Fr) -> Self {
+        let mut rng = thread_rng();
+        let mut bits = Vec::with_capacity(RANGE_BITS);
+        let mut r = Fr::from(0u64);
+        for i in 0..RANGE_BITS {
+            let b = (value >> i) & 1 == 1;
+            let r_i = Fr::rand(&mut rng);
+            r += r_i * pow2(i);
+            let commitment = *h * r_i + if b { *g } else { G1Projective::zero() };
+            let y = [commitment, commitment - *g];
+
+            // real branch b, simulated branch 1 - b
+            let k = Fr::rand(&mut rng);
+            let c_sim = Fr::rand(&mut rng);
+            let z_sim = Fr::rand(&mut rng);
+            let a_real = *h * k;
+            let a_sim = *h * z_sim - y[!b as usize] * c_sim;
+            let (a0, a1, z0, z1) = if b { (a_sim, a_real, z_sim, Fr::from(0u64)) } else { (a_real, a_sim, Fr::from(0u64), z_sim) };
+            // c0 holds the simulated challenge until the real one is known
+            bits.push((b, r_i, k, BitProof { commitment, a0, a1, c0: c_sim, z0, z1 }));
+        }
+        let rho = match bound {
+            Bound::AtLeast(_) => r,
+            Bound::AtMost(_) => -r,
+        };
+        let rho_blinding = Fr::rand(&mut rng);
+        let t = *g * m_blinding + *h * rho_blinding;
+        RangeProtocol { bound, bits, rho, rho_blinding, t }
+    }
+
+    pub(crate) fn challenge_contribution(&self, transcript: &mut Transcript) {
+        let bits: Vec<&BitProof> = self.bits.iter().map(|(_, _, _, p)| p).collect();
+        append_range(transcript, self.bound, &bits, &self.t);
+    }
+
+    pub(crate) fn gen_proof(self, c: &Fr) -> RangeProof {
+        let bits = self
+            .bits
+            .into_iter()
+            .map(|(b, r_i, k, mut p)| {
+                let c_sim = p.c0;
+                let c_real = *c - c_sim;
+                if b {
+                    p.z1 = k + c_real * r_i;
+                    // c0 is the simulated branch's challenge already
+                } else {
+                    p.z0 = k + c_real * r_i;
+                    p.c0 = c_real;
+                }
+                p
+            })
+            .collect();
+        RangeProof { bits, t: self.t, s_r: self.rho_blinding + *c * self.rho }
+    }
+}
+
+impl RangeProof {
+    // bound is the verifier's, the one the proof is checked against
+    pub(crate) fn challenge_contribution(&self, transcript: &mut Transcript, bound: Bound) {
+        let bits: Vec<&BitProof> = self.bits.iter().collect();
+        append_range(transcript, bound, &bits, &self.t);
+    }
+
+    // s_m is the BBS+ response for the bounded message
+    pub(crate) fn verify(&self, g: &G1Projective, h: &G1Projective, bound: Bound, c: &Fr, s_m: &Fr) -> bool {
+        if self.bits.len() != RANGE_BITS {
+            return false;
+        }
+        for p in self.bits.iter() {
+            let c1 = *c - p.c0;
+            if *h * p.z0 != p.a0 + p.commitment * p.c0 || *h * p.z1 != p.a1 + (p.commitment - *g) * c1 {
+                return false;
+            }
+        }
+        let target = link_target(g, &combined(&self.bits), bound);
+        *g * s_m + *h * self.s_r == self.t + target * c
+    }
+}
+
+// Public parameters, reference time and session of an expiry proof
+fn transcript(
+    pk: &PublicKeyG2<Bls12_381>,
+    param: &SignatureParamsG1<Bls12_381>,
+    g: &G1Projective,
+    h: &G1Projective,
+    now: u64,
+    session: &VerifierChallenge,
+) -> Transcript {
+    let mut transcript = new_transcript(b"expiry");
+    transcript.append_serialized(b"pk", pk);
+    transcript.append_serialized(b"sig-params", param);
+    transcript.append_serialized(b"g", g);
+    transcript.append_serialized(b"h", h);
+    transcript.append_u64(b"now", now);
+    transcript.append_session(session);
+    transcript
+}
+
+pub(crate) fn to_u64(f: &Fr) -> Option<u64> {
+    let bytes = f.into_bigint().to_bytes_le();
+    if bytes[8..].iter().any(|b| *b != 0) {
+        return None;
+    }
+    Some(u64::from_le_bytes(bytes[..8].try_into().unwrap()))
+}
+
+impl ExpiryProof {
+    // Holder side; g, h are the issuer's Pedersen generators (Issuer::get_g_h_k_z)
+    #[allow(clippy::too_many_arguments)]
+    pub fn new<W: Zeroize>(
+        cred: &Credential<W>,
+        schema: &CredentialSchema,
+        pk: &PublicKeyG2<Bls12_381>,
+        param: &SignatureParamsG1<Bls12_381>,
+        g: &G1Projective,
+        h: &G1Projective,
+        now: u64,
+        session: &VerifierChallenge,
+    ) -> Result<Self, ValidityError> {
+        let (from_idx, until_idx) = schema.validity_indices().ok_or(ValidityError::NoValidityPeriod)?;
+        let period = cred.get_validity().ok_or(ValidityError::NoValidityPeriod)?;
+        if now < period.valid_from {
+            return Err(ValidityError::NotYetValid);
+        }
+        if now > period.valid_until {
+            return Err(ValidityError::Expired);
+        }
+
+        let signed = cred.signed_messages();
+        let signed = signed.expose();
+        if schema.check_messages(signed).is_err() {
+            return Err(ValidityError::NoValidityPeriod);
+        }
+
+        let mut rng = thread_rng();
+        let from_blinding = Fr::rand(&mut rng);
+        let until_blinding = Fr::rand(&mut rng);
+        let messages_and_blindings = signed.iter().enumerate().map(|(i, m)| {
+            if i == 0 {
+                MessageOrBlinding::RevealMessage(m)
+            } else if i == from_idx {
+                MessageOrBlinding::BlindMessageWithConcreteBlinding { message: m, blinding: from_blinding }
+            } else if i == until_idx {
+                MessageOrBlinding::BlindMessageWithConcreteBlinding { message: m, blinding: until_blinding }
+            } else {
+                MessageOrBlinding::BlindMessageRandomly(m)
+            }
+        });
+        let pok = PoKOfSignatureG1Protocol::init(&mut rng, cred.get_signature(), param, messages_and_blindings)
+            .map_err(|_| ValidityError::ProofFailed)?;
+        let until = RangeProtocol::init(g, h, period.valid_until - now, Bound::AtLeast(now), &until_blinding);
+        let from = RangeProtocol::init(g, h, now - period.valid_from, Bound::AtMost(now), &from_blinding);
+
+        let revealed = BTreeMap::from([(0, signed[0])]);
+        let mut pok_bytes = Vec::new();
+        pok.challenge_contribution(&revealed, param, &mut pok_bytes).map_err(|_| ValidityError::ProofFailed)?;
+        let mut transcript = transcript(pk, param, g, h, now, session);
+        transcript.domain_sep(b"bbs-pok");
+        transcript.append_message(b"pok", &pok_bytes);
+        until.challenge_contribution(&mut transcript);
+        from.challenge_contribution(&mut transcript);
+        let c = transcript.challenge_scalar(b"c");
+
+        Ok(ExpiryProof {
+            pok: pok.gen_proof(&c).map_err(|_| ValidityError::ProofFailed)?,
+            schema_id: signed[0],
+            until: until.gen_proof(&c),
+            from: from.gen_proof(&c),
+        })
+    }
+
+    // Verifier side, at the verifier's own reference time and for the
+    // challenge it issued
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify(
+        &self,
+        schema: &CredentialSchema,
+        pk: &PublicKeyG2<Bls12_381>,
+        param: &SignatureParamsG1<Bls12_381>,
+        g: &G1Projective,
+        h: &G1Projective,
+        now: u64,
+        session: &VerifierChallenge,
+    ) -> bool {
+        let Some((from_idx, until_idx)) = schema.validity_indices() else {
+            return false;
+        };
+        if self.schema_id != schema.id_fr() || param.supported_message_count() != schema.message_count() {
+            return false;
+        }
+
+        let revealed = BTreeMap::from([(0, self.schema_id)]);
+        let mut pok_bytes = Vec::new();
+        if self.pok.challenge_contribution(&revealed, param, &mut pok_bytes).is_err() {
+            return false;
+        }
+        let mut transcript = transcript(pk, param, g, h, now, session);
+        transcript.domain_sep(b"bbs-pok");
+        transcript.append_message(b"pok", &pok_bytes);
+        self.until.challenge_contribution(&mut transcript, Bound::AtLeast(now));
+        self.from.challenge_contribution(&mut transcript, Bound::AtMost(now));
+        let c = transcript.challenge_scalar(b"c");
+
+        if self.pok.verify(&revealed, &c, pk.clone(), param.clone()).is_err() {
+            return false;
+        }
+        let revealed_ids = BTreeSet::from([0]);
+        let (Ok(s_from), Ok(s_until)) = (
+            self.pok.get_resp_for_message(from_idx, &revealed_ids),
+            self.pok.get_resp_for_message(until_idx, &revealed_ids),
+        ) else {
+            return false;
+        };
+        self.until.verify(g, h, Bound::AtLeast(now), &c, s_until) && self.from.verify(g, h, Bound::AtMost(now), &c, s_from)
+    }
+}
+
+// Reads the validity period back from signed messages of the schema
+pub fn validity_from_messages(schema: &CredentialSchema, messages: &[Fr]) -> Option<ValidityPeriod> {
+    let (from_idx, until_idx) = schema.validity_indices()?;
+    let valid_from = to_u64(messages.get(from_idx)?)?;
+    let valid_until = to_u64(messages.get(until_idx)?)?;
+    ValidityPeriod::new(valid_from, valid_until)
+}
@@ -0,0 +1,503 @@
+// W3C Verifiable Credentials Data Model 2.0 encoding.
+//
+// A Credential exports as a VC whose proof is a DataIntegrityProof with the
+// BBS+ cryptosuite below:
+//
+//   issuer             urn:vac:issuer:<IssuerPublicKeys::fingerprint>
+//   credentialSchema   urn:vac:schema:<CredentialSchema::id>
+//   credentialSubject  the attributes (Integer as numbers, Date, String and
+//                      Enum as strings) and holderCommitment, hex of h_L*x:
+//                      the term the signed holder secret x adds to the BBS+
+//                      message commitment, h_L its generator
+//   validFrom/Until    the signed valid_from/valid_until, for schemas with
+//                      validity (not repeated in credentialSubject)
+//   credentialStatus   revocation entry with the accumulator witness
+//   proof              proofValue is the BBS+ signature (multibase hex, "f"
+//                      prefix); messageIndices maps every signed attribute to
+//                      its BBS+ message index
+//
+// x, which opens holderCommitment, and r are the holder's secret and never
+// part of the document; importing a VC back into a Credential takes them
+// separately. The signature is checked with h_L*x in place of the message.
+// String attributes are signed as hashes, so exporting needs their values;
+// the other kinds are decoded from the signed messages.
+//
+// A Presentation exports as a Verifiable Presentation holding one derived
+// credential: the disclosed attributes, the accumulator epoch in its status
+// entry and the presentation proof, with the request nonce as challenge and
+// the verifier as domain. The pseudonym, if any, is the presentation's holder.
+//
+// Terms outside the VC 2.0 context fall under its issuer-dependent @vocab.
+
+use ark_bls12_381::{Bls12_381, Fr, G1Projective};
+use ark_ec::pairing::Pairing;
+use ark_ec::CurveGroup;
+use ark_ff::Zero;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use bbs_plus::signature::SignatureG1;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::error::Error;
+use zeroize::Zeroize;
+
+use crate::credential::Credential;
+use crate::keystore::IssuerPublicKeys;
+use crate::presentation::{Predicate, Presentation};
+use crate::schema::{day_number_to_date, parse_date, AttributeType, AttributeValue, CredentialSchema, SchemaError};
+use crate::validity::{to_u64, validity_from_messages, VALID_FROM, VALID_UNTIL};
+
+pub const VC_CONTEXT_V2: &str = "https://www.w3.org/ns/credentials/v2";
+pub const CRYPTOSUITE: &str = "vac-bbs-plus-2025";
+pub const STATUS_TYPE: &str = "VacAccumulatorStatusEntry";
+pub const HOLDER_COMMITMENT: &str = "holderCommitment";
+const SCHEMA_INDEX: &str = "credentialSchema";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaReference {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+// Revocation status: the witness in an exported credential, the epoch the
+// non-revocation proof was made at in a presentation
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusEntry {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub status_purpose: String,
+    pub accumulator: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub witness: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataIntegrityProof {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub cryptosuite: String,
+    pub verification_method: String,
+    pub proof_purpose: String,
+    pub proof_value: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub message_indices: BTreeMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub challenge: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub predicates: Vec<Predicate>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiableCredential {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    pub issuer: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<String>,
+    pub credential_schema: SchemaReference,
+    pub credential_subject: Map<String, Value>,
+    pub credential_status: StatusEntry,
+    pub proof: DataIntegrityProof,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiablePresentation {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub holder: Option<String>, // urn:vac:pseudonym:<hex>
+    pub verifiable_credential: Vec<VerifiableCredential>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum VcError {
+    NotAVerifiableCredential, // missing the VC 2.0 context, type or cryptosuite
+    WrongIssuer,
+    WrongSchema,
+    IndexMismatch, // messageIndices differ from the schema's
+    MissingValue(String), // String attribute exported without its value
+    SubjectMismatch(String), // value does not encode to the signed message
+    Schema(SchemaError),
+    Malformed(String), // field that does not decode
+    InvalidSignature,
+    CommitmentMismatch, // x does not open holderCommitment
+    DateOutOfRange(u64), // seconds past 9999-12-31T23:59:59Z, no dateTime form
+}
+
+impl From<SchemaError> for VcError {
+    fn from(e: SchemaError) -> Self {
+        VcError::Schema(e)
+    }
+}
+
+pub fn issuer_urn(issuer_id: &str) -> String {
+    format!("urn:vac:issuer:{}", issuer_id)
+}
+
+pub fn schema_urn(schema_id: &str) -> String {
+    format!("urn:vac:schema:{}", schema_id)
+}
+
+fn accumulator_urn(issuer_id: &str) -> String {
+    format!("urn:vac:accumulator:{}", issuer_id)
+}
+
+fn to_hex<T: CanonicalSerialize>(value: &T) -> String {
+    let mut bytes = Vec::new();
+    value.serialize_compressed(&mut bytes).unwrap();
+    hex::encode(bytes)
+}
+
+fn from_hex<T: CanonicalDeserialize>(s: &str, field: &str) -> Result<T, VcError> {
+    let malformed = || VcError::Malformed(field.to_string());
+    let bytes = hex::decode(s).map_err(|_| malformed())?;
+    T::deserialize_compressed(&bytes[..]).map_err(|_| malformed())
+}
+
+// Unix seconds as an XML Schema dateTime in UTC
+pub fn to_datetime(secs: u64) -> Result<String, VcError> {
+    let date = day_number_to_date(secs / 86_400 + 719_163).ok_or(VcError::DateOutOfRange(secs))?;
+    let t = secs % 86_400;
+    Ok(format!("{}T{:02}:{:02}:{:02}Z", date, t / 3600, t / 60 % 60, t % 60))
+}
+
+// Inverse of to_datetime; only the UTC "Z" form is accepted
+pub fn parse_datetime(value: &str) -> Option<u64> {
+    let (date, time) = value.strip_suffix('Z')?.split_once('T')?;
+    let days = parse_date(date)?.checked_sub(719_163)?;
+    let parts: Vec<u64> = time.split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    match parts[..] {
+        [h, m, s] if h < 24 && m < 60 && s < 60 => Some(days * 86_400 + h * 3600 + m * 60 + s),
+        _ => None,
+    }
+}
+
+// Attribute -> message index, schema id and holderCommitment (x) included
+fn message_indices(schema: &CredentialSchema) -> BTreeMap<String, usize> {
+    let mut indices: BTreeMap<String, usize> =
+        schema.attributes.iter().map(|a| (a.name.clone(), schema.message_index(&a.name).unwrap())).collect();
+    indices.insert(SCHEMA_INDEX.to_string(), 0);
+    indices.insert(HOLDER_COMMITMENT.to_string(), schema.message_count() - 1);
+    indices
+}
+
+fn is_validity(schema: &CredentialSchema, name: &str) -> bool {
+    schema.validity_indices().is_some() && (name == VALID_FROM || name == VALID_UNTIL)
+}
+
+// Value of a signed message: `given` if it encodes to it, otherwise decoded
+// (impossible for String attributes)
+fn decode_value(schema: &CredentialSchema, name: &str, m: &Fr, given: Option<&AttributeValue>) -> Result<AttributeValue, VcError> {
+    if let Some(value) = given {
+        return match schema.encode_value(name, value)? == *m {
+            true => Ok(value.clone()),
+            false => Err(VcError::SubjectMismatch(name.to_string())),
+        };
+    }
+    let attr = schema.attributes.iter().find(|a| a.name == name).ok_or_else(|| SchemaError::UnknownAttribute(name.to_string()))?;
+    let value = match &attr.kind {
+        AttributeType::Integer => to_u64(m).map(AttributeValue::Integer),
+        AttributeType::Date => to_u64(m).and_then(day_number_to_date).map(AttributeValue::Date),
+        AttributeType::Enum { variants } => to_u64(m).and_then(|i| variants.get(i as usize)).cloned().map(AttributeValue::Enum),
+        AttributeType::String => None,
+    };
+    value.ok_or_else(|| VcError::MissingValue(name.to_string()))
+}
+
+fn value_to_json(value: &AttributeValue) -> Value {
+    match value {
+        AttributeValue::Integer(v) => json!(v),
+        AttributeValue::Date(v) | AttributeValue::String(v) | AttributeValue::Enum(v) => json!(v),
+    }
+}
+
+fn value_from_json(schema: &CredentialSchema, name: &str, value: &Value) -> Result<AttributeValue, VcError> {
+    schema.value_from_json(name, value).map_err(|e| match e {
+        SchemaError::TypeMismatch { .. } => VcError::Malformed(name.to_string()),
+        e => VcError::Schema(e),
+    })
+}
+
+// h_L*x, with h_L the BBS+ generator of the last message
+fn holder_term(issuer: &IssuerPublicKeys, x: &Fr) -> G1Projective {
+    *issuer.sig_param.h.last().unwrap() * x
+}
+
+// BBS+ verification, e(A, pk)*e(A*e - b, g2) == 1, with the term of the
+// last message given instead of the message
+fn verify_with_holder_term(signature: &SignatureG1<Bls12_381>, messages: &[Fr], term: &G1Projective, issuer: &IssuerPublicKeys) -> bool {
+    let params = &issuer.sig_param;
+    if messages.len() + 1 != params.h.len() || !signature.is_non_zero() {
+        return false;
+    }
+    let Ok(b) = params.b(messages.iter().enumerate(), &signature.s) else {
+        return false;
+    };
+    let aeb = signature.A * signature.e - (b + term);
+    Bls12_381::multi_pairing([signature.A, aeb.into_affine()], [issuer.pk.0, params.g2]).is_zero()
+}
+
+impl VerifiableCredential {
+    // Holder or issuer side. `values` needs the String attributes; any value
+    // given is checked against the signed message
+    pub fn from_credential<W: Zeroize + CanonicalSerialize>(
+        cred: &Credential<W>,
+        schema: &CredentialSchema,
+        issuer: &IssuerPublicKeys,
+        values: &BTreeMap<String, AttributeValue>,
+    ) -> Result<Self, VcError> {
+        if cred.get_key_id() != issuer.key_id {
+            return Err(VcError::WrongIssuer);
+        }
+        let signed = cred.signed_messages();
+        let signed = signed.expose();
+        schema.check_messages(signed)?;
+
+        let mut subject = Map::new();
+        for attr in schema.attributes.iter().filter(|a| !is_validity(schema, &a.name)) {
+            let idx = schema.message_index(&attr.name).unwrap();
+            let value = decode_value(schema, &attr.name, &signed[idx], values.get(&attr.name))?;
+            subject.insert(attr.name.clone(), value_to_json(&value));
+        }
+        subject.insert(HOLDER_COMMITMENT.to_string(), json!(to_hex(&holder_term(issuer, cred.get_x_val()))));
+
+        let issuer_id = issuer.fingerprint();
+        let validity = cred.get_validity().filter(|_| schema.validity_indices().is_some());
+        Ok(VerifiableCredential {
+            context: vec![VC_CONTEXT_V2.to_string()],
+            types: vec!["VerifiableCredential".to_string(), schema.name.clone()],
+            issuer: issuer_urn(&issuer_id),
+            valid_from: validity.map(|v| to_datetime(v.valid_from)).transpose()?,
+            valid_until: validity.map(|v| to_datetime(v.valid_until)).transpose()?,
+            credential_schema: SchemaReference { id: schema_urn(&schema.id()), kind: "VacCredentialSchema".to_string() },
+            credential_subject: subject,
+            credential_status: StatusEntry {
+                kind: STATUS_TYPE.to_string(),
+                status_purpose: "revocation".to_string(),
+                accumulator: accumulator_urn(&issuer_id),
+                witness: Some(to_hex(cred.get_witness())),
+                epoch: None,
+            },
+            proof: DataIntegrityProof {
+                kind: "DataIntegrityProof".to_string(),
+                cryptosuite: CRYPTOSUITE.to_string(),
+                verification_method: format!("{}#key-{}", issuer_urn(&issuer_id), issuer.key_id),
+                proof_purpose: "assertionMethod".to_string(),
+                proof_value: format!("f{}", to_hex(cred.get_signature())),
+                message_indices: message_indices(schema),
+                challenge: None,
+                domain: None,
+                predicates: Vec::new(),
+            },
+        })
+    }
+
+    // Checks the document against the schema and issuer; returns the signed
+    // messages but x, holderCommitment and the signature
+    fn signed_messages(
+        &self,
+        schema: &CredentialSchema,
+        issuer: &IssuerPublicKeys,
+    ) -> Result<(Vec<Fr>, G1Projective, SignatureG1<Bls12_381>), VcError> {
+        if self.context.first().map(String::as_str) != Some(VC_CONTEXT_V2)
+            || !self.types.iter().any(|t| t == "VerifiableCredential")
+            || self.proof.cryptosuite != CRYPTOSUITE
+        {
+            return Err(VcError::NotAVerifiableCredential);
+        }
+        let issuer_id = issuer.fingerprint();
+        if self.issuer != issuer_urn(&issuer_id)
+            || self.proof.verification_method != format!("{}#key-{}", issuer_urn(&issuer_id), issuer.key_id)
+        {
+            return Err(VcError::WrongIssuer);
+        }
+        if self.credential_schema.id != schema_urn(&schema.id()) {
+            return Err(VcError::WrongSchema);
+        }
+        if self.proof.message_indices != message_indices(schema) {
+            return Err(VcError::IndexMismatch);
+        }
+
+        let mut values = BTreeMap::new();
+        for (name, value) in self.credential_subject.iter().filter(|(name, _)| *name != HOLDER_COMMITMENT) {
+            values.insert(name.clone(), value_from_json(schema, name, value)?);
+        }
+        if schema.validity_indices().is_some() {
+            for (name, datetime) in [(VALID_FROM, &self.valid_from), (VALID_UNTIL, &self.valid_until)] {
+                let secs = datetime.as_deref().and_then(parse_datetime).ok_or_else(|| VcError::Malformed(name.to_string()))?;
+                values.insert(name.to_string(), AttributeValue::Integer(secs));
+            }
+        }
+        let messages = schema.encode(&values)?;
+
+        let term_hex = self.credential_subject.get(HOLDER_COMMITMENT).and_then(Value::as_str);
+        let term: G1Projective = from_hex(term_hex.unwrap_or_default(), HOLDER_COMMITMENT)?;
+        let sig_hex = self.proof.proof_value.strip_prefix('f').ok_or_else(|| VcError::Malformed("proofValue".to_string()))?;
+        let signature: SignatureG1<Bls12_381> = from_hex(sig_hex, "proofValue")?;
+        Ok((messages, term, signature))
+    }
+
+    // Verifier side: the issuer's signature over the disclosed attributes
+    pub fn verify(&self, schema: &CredentialSchema, issuer: &IssuerPublicKeys) -> Result<(), VcError> {
+        let (messages, term, signature) = self.signed_messages(schema, issuer)?;
+        match verify_with_holder_term(&signature, &messages, &term, issuer) {
+            true => Ok(()),
+            false => Err(VcError::InvalidSignature),
+        }
+    }
+
+    // Holder side: back to a Credential, with the holder secret x that opens
+    // holderCommitment and the r kept with it
+    pub fn to_credential<W: Zeroize + CanonicalDeserialize>(
+        &self,
+        schema: &CredentialSchema,
+        issuer: &IssuerPublicKeys,
+        x_val: Fr,
+        r: Fr,
+    ) -> Result<Credential<W>, VcError> {
+        let (messages, term, signature) = self.signed_messages(schema, issuer)?;
+        if holder_term(issuer, &x_val) != term {
+            return Err(VcError::CommitmentMismatch);
+        }
+        let mut signed = messages.clone();
+        signed.push(x_val);
+        signature.verify(&signed, issuer.pk.clone(), issuer.sig_param.clone()).map_err(|_| VcError::InvalidSignature)?;
+        signed.zeroize();
+        if self.credential_status.kind != STATUS_TYPE {
+            return Err(VcError::Malformed("credentialStatus".to_string()));
+        }
+        let witness: W = from_hex(self.credential_status.witness.as_deref().unwrap_or_default(), "witness")?;
+
+        let validity = validity_from_messages(schema, &messages);
+        let mut cred = Credential::new(signature, messages, x_val, r, witness, issuer.key_id);
+        cred.set_validity(validity);
+        Ok(cred)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+impl VerifiablePresentation {
+    // Holder side. `values` needs the revealed String attributes, as for
+    // VerifiableCredential::from_credential
+    pub fn from_presentation(
+        presentation: &Presentation,
+        schema: &CredentialSchema,
+        values: &BTreeMap<String, AttributeValue>,
+    ) -> Result<Self, VcError> {
+        if presentation.schema_id != schema.id() {
+            return Err(VcError::WrongSchema);
+        }
+        let mut subject = Map::new();
+        for name in presentation.revealed.keys() {
+            let m = presentation.revealed_value(name).ok_or_else(|| VcError::Malformed(name.clone()))?;
+            subject.insert(name.clone(), value_to_json(&decode_value(schema, name, &m, values.get(name))?));
+        }
+        let credential = VerifiableCredential {
+            context: vec![VC_CONTEXT_V2.to_string()],
+            types: vec!["VerifiableCredential".to_string(), schema.name.clone()],
+            issuer: issuer_urn(&presentation.issuer),
+            valid_from: None,
+            valid_until: None,
+            credential_schema: SchemaReference { id: schema_urn(&schema.id()), kind: "VacCredentialSchema".to_string() },
+            credential_subject: subject,
+            credential_status: StatusEntry {
+                kind: STATUS_TYPE.to_string(),
+                status_purpose: "revocation".to_string(),
+                accumulator: accumulator_urn(&presentation.issuer),
+                witness: None,
+                epoch: Some(presentation.epoch),
+            },
+            proof: DataIntegrityProof {
+                kind: "DataIntegrityProof".to_string(),
+                cryptosuite: CRYPTOSUITE.to_string(),
+                verification_method: issuer_urn(&presentation.issuer),
+                proof_purpose: "assertionMethod".to_string(),
+                proof_value: format!("f{}", presentation.proof),
+                message_indices: BTreeMap::new(),
+                challenge: Some(presentation.nonce.clone()),
+                domain: Some(presentation.verifier.clone()),
+                predicates: presentation.predicates.clone(),
+            },
+        };
+        Ok(VerifiablePresentation {
+            context: vec![VC_CONTEXT_V2.to_string()],
+            types: vec!["VerifiablePresentation".to_string()],
+            holder: presentation.pseudonym.as_ref().map(|nym| format!("urn:vac:pseudonym:{}", nym)),
+            verifiable_credential: vec![credential],
+        })
+    }
+
+    // Verifier side: the Presentation to validate against the request, and
+    // the disclosed values, each checked against its signed encoding
+    pub fn to_presentation(&self, schema: &CredentialSchema) -> Result<(Presentation, BTreeMap<String, AttributeValue>), VcError> {
+        let [credential] = &self.verifiable_credential[..] else {
+            return Err(VcError::Malformed("verifiableCredential".to_string()));
+        };
+        if self.context.first().map(String::as_str) != Some(VC_CONTEXT_V2)
+            || !self.types.iter().any(|t| t == "VerifiablePresentation")
+            || credential.proof.cryptosuite != CRYPTOSUITE
+        {
+            return Err(VcError::NotAVerifiableCredential);
+        }
+        if credential.credential_schema.id != schema_urn(&schema.id()) {
+            return Err(VcError::WrongSchema);
+        }
+        let malformed = |field: &str| VcError::Malformed(field.to_string());
+        let issuer = credential.issuer.strip_prefix("urn:vac:issuer:").ok_or_else(|| malformed("issuer"))?;
+        let pseudonym = match &self.holder {
+            Some(holder) => Some(holder.strip_prefix("urn:vac:pseudonym:").ok_or_else(|| malformed("holder"))?.to_string()),
+            None => None,
+        };
+
+        let mut values = BTreeMap::new();
+        let mut revealed = BTreeMap::new();
+        for (name, value) in credential.credential_subject.iter() {
+            let value = value_from_json(schema, name, value)?;
+            revealed.insert(name.clone(), to_hex(&schema.encode_value(name, &value)?));
+            values.insert(name.clone(), value);
+        }
+        let presentation = Presentation {
+            verifier: credential.proof.domain.clone().ok_or_else(|| malformed("domain"))?,
+            nonce: credential.proof.challenge.clone().ok_or_else(|| malformed("challenge"))?,
+            issuer: issuer.to_string(),
+            schema_id: schema.id(),
+            epoch: credential.credential_status.epoch.ok_or_else(|| malformed("epoch"))?,
+            revealed,
+            predicates: credential.proof.predicates.clone(),
+            pseudonym,
+            proof: credential.proof.proof_value.strip_prefix('f').ok_or_else(|| malformed("proofValue"))?.to_string(),
+        };
+        Ok((presentation, values))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
@@ -0,0 +1,379 @@
+// Holder wallet: many credentials, from any number of issuers, kept in one
+// encrypted file. The whole wallet is sealed as a single blob:
+//
+//   kdf, salt    Argon2id parameters, as in the issuer keystore
+//   nonce        fresh XChaCha20-Poly1305 nonce for every save
+//   sealed       JSON list of entries; each entry's credential (signature,
+//                x, r, witness, ...) is Credential::to_bytes in hex
+//
+// Nothing but the KDF parameters is readable without the passphrase, not
+// even which issuers the holder has credentials from. In memory, entries are
+// indexed by issuer (IssuerPublicKeys::fingerprint) and schema id; the
+// derived key and every credential wipe themselves when the wallet is dropped.
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::{thread_rng, RngCore};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroize;
+
+use crate::acc::ECAccumulator;
+use crate::credential::{Credential, UpdateLog};
+use crate::keystore::{derive_key, IssuerPublicKeys, KdfParams};
+use crate::revocation::{AccRevocation, RevocationScheme};
+use crate::schema::CredentialSchema;
+use crate::secret::Secret;
+
+const WALLET_AAD: &[u8] = b"vac-holder-wallet";
+
+pub struct WalletEntry<W: Zeroize> {
+    pub id: String,
+    pub issuer: String, // IssuerPublicKeys::fingerprint of the signing key
+    pub schema_id: Option<String>, // None for credentials issued without a schema
+    pub revoked: bool, // set when a witness refresh reports the credential revoked
+    pub epoch: Option<u64>, // accumulator epoch the witness is current at, if known (sync_witnesses)
+    cred: Credential<W>,
+}
+
+impl<W: Zeroize> WalletEntry<W> {
+    pub fn get_cred(&self) -> &Credential<W> {
+        &self.cred
+    }
+}
+
+// What a verifier accepts; empty lists accept anything
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CredentialQuery {
+    pub issuers: Vec<String>,
+    pub schemas: Vec<String>,
+    pub valid_at: Option<u64>, // only credentials whose validity period contains it
+    pub include_revoked: bool,
+}
+
+impl CredentialQuery {
+    pub fn matches<W: Zeroize>(&self, entry: &WalletEntry<W>) -> bool {
+        (self.issuers.is_empty() || self.issuers.contains(&entry.issuer))
+            && (self.schemas.is_empty() || entry.schema_id.as_ref().is_some_and(|s| self.schemas.contains(s)))
+            && self.valid_at.is_none_or(|now| entry.cred.get_validity().is_some_and(|v| v.contains(now)))
+            && (self.include_revoked || !entry.revoked)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct WalletFile {
+    kdf: KdfParams,
+    salt: String,
+    nonce: String,
+    sealed: String,
+}
+
+#[derive(Serialize, Deserialize, Zeroize)]
+struct StoredEntry {
+    id: String,
+    issuer: String,
+    schema_id: Option<String>,
+    revoked: bool,
+    #[serde(default)]
+    epoch: Option<u64>,
+    credential: String, // hex of Credential::to_bytes
+}
+
+pub struct Wallet<S: RevocationScheme = ECAccumulator> {
+    path: PathBuf,
+    kdf: KdfParams,
+    salt: [u8; 16],
+    key: Secret<[u8; 32]>,
+    entries: BTreeMap<String, WalletEntry<S::Witness>>,
+    by_issuer: BTreeMap<String, BTreeSet<String>>,
+    by_schema: BTreeMap<String, BTreeSet<String>>,
+    next_id: u64,
+}
+
+impl<S: RevocationScheme> Wallet<S>
+where
+    S::Witness: CanonicalSerialize + CanonicalDeserialize,
+{
+    pub fn create(path: impl AsRef<Path>, passphrase: &str) -> Result<Self, Box<dyn Error>> {
+        Self::create_with_kdf(path, passphrase, KdfParams::default())
+    }
+
+    // Refuses to overwrite an existing wallet
+    pub fn create_with_kdf(path: impl AsRef<Path>, passphrase: &str, kdf: KdfParams) -> Result<Self, Box<dyn Error>> {
+        if path.as_ref().exists() {
+            return Err(format!("wallet {} already exists", path.as_ref().display()).into());
+        }
+        let mut salt = [0u8; 16];
+        thread_rng().fill_bytes(&mut salt);
+        let wallet = Wallet {
+            path: path.as_ref().to_path_buf(),
+            kdf,
+            salt,
+            key: Secret::new(derive_key(passphrase, &salt, &kdf)?),
+            entries: BTreeMap::new(),
+            by_issuer: BTreeMap::new(),
+            by_schema: BTreeMap::new(),
+            next_id: 1,
+        };
+        wallet.save()?;
+        Ok(wallet)
+    }
+
+    // A wrong passphrase or a tampered file both fail the AEAD check
+    pub fn open(path: impl AsRef<Path>, passphrase: &str) -> Result<Self, Box<dyn Error>> {
+        let file: WalletFile = serde_json::from_str(&std::fs::read_to_string(path.as_ref())?)?;
+        let salt: [u8; 16] = hex::decode(&file.salt)?.try_into().map_err(|_| "bad salt length in wallet")?;
+        let nonce = hex::decode(&file.nonce)?;
+        if nonce.len() != 24 {
+            return Err("bad nonce length in wallet".into());
+        }
+
+        let mut wallet = Wallet {
+            path: path.as_ref().to_path_buf(),
+            kdf: file.kdf,
+            salt,
+            key: Secret::new(derive_key(passphrase, &salt, &file.kdf)?),
+            entries: BTreeMap::new(),
+            by_issuer: BTreeMap::new(),
+            by_schema: BTreeMap::new(),
+            next_id: 1,
+        };
+        let mut plain = XChaCha20Poly1305::new(Key::from_slice(wallet.key.expose()))
+            .decrypt(XNonce::from_slice(&nonce), Payload { msg: &hex::decode(&file.sealed)?, aad: WALLET_AAD })
+            .map_err(|_| "cannot decrypt wallet: wrong passphrase or corrupted file")?;
+        let stored: Result<Vec<StoredEntry>, _> = serde_json::from_slice(&plain);
+        plain.zeroize();
+
+        for mut entry in stored? {
+            let mut bytes = hex::decode(&entry.credential)?;
+            let cred = Credential::from_bytes(&bytes);
+            bytes.zeroize();
+            let id = std::mem::take(&mut entry.id);
+            let number = id.trim_start_matches("wc-").parse::<u64>().unwrap_or(0);
+            wallet.next_id = wallet.next_id.max(number + 1);
+            wallet.insert(WalletEntry {
+                id,
+                issuer: std::mem::take(&mut entry.issuer),
+                schema_id: entry.schema_id.take(),
+                revoked: entry.revoked,
+                epoch: entry.epoch,
+                cred: cred?,
+            });
+            entry.zeroize();
+        }
+        Ok(wallet)
+    }
+
+    // Re-encrypts the whole wallet under a fresh nonce, replacing the file atomically
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let stored: Vec<StoredEntry> = self
+            .entries
+            .values()
+            .map(|e| StoredEntry {
+                id: e.id.clone(),
+                issuer: e.issuer.clone(),
+                schema_id: e.schema_id.clone(),
+                revoked: e.revoked,
+                epoch: e.epoch,
+                credential: hex::encode(e.cred.to_bytes()),
+            })
+            .collect();
+        let mut plain = serde_json::to_vec(&stored)?;
+        for mut entry in stored {
+            entry.zeroize();
+        }
+
+        let mut nonce = [0u8; 24];
+        thread_rng().fill_bytes(&mut nonce);
+        let sealed = XChaCha20Poly1305::new(Key::from_slice(self.key.expose()))
+            .encrypt(XNonce::from_slice(&nonce), Payload { msg: &plain, aad: WALLET_AAD })
+            .map_err(|_| "failed to encrypt the wallet");
+        plain.zeroize();
+
+        let file = WalletFile { kdf: self.kdf, salt: hex::encode(self.salt), nonce: hex::encode(nonce), sealed: hex::encode(sealed?) };
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&file)?)?;
+        std::fs::rename(tmp, &self.path)?;
+        Ok(())
+    }
+
+    fn insert(&mut self, entry: WalletEntry<S::Witness>) {
+        self.by_issuer.entry(entry.issuer.clone()).or_default().insert(entry.id.clone());
+        if let Some(schema_id) = &entry.schema_id {
+            self.by_schema.entry(schema_id.clone()).or_default().insert(entry.id.clone());
+        }
+        self.entries.insert(entry.id.clone(), entry);
+    }
+
+    // Adds a credential signed by `issuer` (optionally under `schema`) and
+    // returns its wallet id; call save() to persist
+    pub fn add(
+        &mut self,
+        cred: Credential<S::Witness>,
+        issuer: &IssuerPublicKeys,
+        schema: Option<&CredentialSchema>,
+    ) -> Result<String, Box<dyn Error>> {
+        self.add_entry(cred, issuer, schema, None)
+    }
+
+    // Same, for a credential whose witness is current at `epoch` (the
+    // accumulator epoch it was issued at), so sync_witnesses knows which
+    // updates it still needs
+    pub fn add_at_epoch(
+        &mut self,
+        cred: Credential<S::Witness>,
+        issuer: &IssuerPublicKeys,
+        schema: Option<&CredentialSchema>,
+        epoch: u64,
+    ) -> Result<String, Box<dyn Error>> {
+        self.add_entry(cred, issuer, schema, Some(epoch))
+    }
+
+    fn add_entry(
+        &mut self,
+        cred: Credential<S::Witness>,
+        issuer: &IssuerPublicKeys,
+        schema: Option<&CredentialSchema>,
+        epoch: Option<u64>,
+    ) -> Result<String, Box<dyn Error>> {
+        if cred.get_key_id() != issuer.key_id {
+            return Err(format!("credential was signed with key {}, not {}", cred.get_key_id(), issuer.key_id).into());
+        }
+        if let Some(schema) = schema {
+            if cred.get_message().first() != Some(&schema.id_fr()) || cred.get_message().len() + 1 != schema.message_count() {
+                return Err(format!("credential was not issued under schema {}", schema.name).into());
+            }
+        }
+        let id = format!("wc-{:04}", self.next_id);
+        self.next_id += 1;
+        self.insert(WalletEntry {
+            id: id.clone(),
+            issuer: issuer.fingerprint(),
+            schema_id: schema.map(|s| s.id()),
+            revoked: false,
+            epoch,
+            cred,
+        });
+        Ok(id)
+    }
+
+    // Removes the credential, which is wiped as it is dropped
+    pub fn remove(&mut self, id: &str) -> bool {
+        match self.entries.remove(id) {
+            Some(entry) => {
+                if let Some(ids) = self.by_issuer.get_mut(&entry.issuer) {
+                    ids.remove(id);
+                }
+                if let Some(ids) = entry.schema_id.as_ref().and_then(|s| self.by_schema.get_mut(s)) {
+                    ids.remove(id);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&WalletEntry<S::Witness>> {
+        self.entries.get(id)
+    }
+
+    pub fn issuers(&self) -> Vec<&String> {
+        self.by_issuer.iter().filter(|(_, ids)| !ids.is_empty()).map(|(issuer, _)| issuer).collect()
+    }
+
+    pub fn by_issuer(&self, issuer: &str) -> Vec<&WalletEntry<S::Witness>> {
+        self.by_issuer.get(issuer).map_or(Vec::new(), |ids| ids.iter().map(|id| &self.entries[id]).collect())
+    }
+
+    pub fn by_schema(&self, schema_id: &str) -> Vec<&WalletEntry<S::Witness>> {
+        self.by_schema.get(schema_id).map_or(Vec::new(), |ids| ids.iter().map(|id| &self.entries[id]).collect())
+    }
+
+    // Credentials that can answer a presentation request, in id order
+    pub fn query(&self, query: &CredentialQuery) -> Vec<&WalletEntry<S::Witness>> {
+        self.entries.values().filter(|e| query.matches(e)).collect()
+    }
+
+    // Applies an issuer's update feed, in publication order, to every live
+    // credential of that issuer in one pass. Returns the ids found revoked
+    pub fn refresh_witnesses(&mut self, issuer: &str, params: &S::VerifyParams, updates: &[S::Update]) -> Vec<String> {
+        let mut revoked = Vec::new();
+        let ids = self.by_issuer.get(issuer).cloned().unwrap_or_default();
+        for id in ids {
+            let entry = self.entries.get_mut(&id).expect("indexed credential");
+            if entry.revoked {
+                continue;
+            }
+            if !updates.iter().all(|update| entry.cred.update_revocation_witness::<S>(params, update)) {
+                entry.revoked = true;
+                revoked.push(id);
+            }
+        }
+        revoked
+    }
+
+    // refresh_witnesses from an issuer's full update log, as (epoch after the
+    // update, update) in publication order: each credential only takes the
+    // updates past its own epoch, then moves to the last one. Returns the ids
+    // found revoked. Refused, with nothing changed, if a live credential of
+    // the issuer has no known epoch: which updates it still needs cannot be
+    // told from the log, give them to refresh_witnesses instead
+    pub fn sync_witnesses(&mut self, issuer: &str, params: &S::VerifyParams, log: &[(u64, S::Update)]) -> Result<Vec<String>, Box<dyn Error>> {
+        let ids = self.by_issuer.get(issuer).cloned().unwrap_or_default();
+        let live: Vec<&String> = ids.iter().filter(|id| !self.entries[*id].revoked).collect();
+        if let Some(id) = live.iter().find(|id| self.entries[**id].epoch.is_none()) {
+            return Err(format!("credential {} has no known epoch, refresh it with the updates it is missing", id).into());
+        }
+
+        let mut revoked = Vec::new();
+        let latest = match log.last() {
+            Some((epoch, _)) => *epoch,
+            None => return Ok(revoked),
+        };
+        for id in live {
+            let entry = self.entries.get_mut(id).expect("indexed credential");
+            let since = entry.epoch.expect("checked above");
+            let mut pending = log.iter().filter(|(epoch, _)| *epoch > since);
+            if !pending.all(|(_, update)| entry.cred.update_revocation_witness::<S>(params, update)) {
+                entry.revoked = true;
+                revoked.push(id.clone());
+                continue;
+            }
+            entry.epoch = Some(since.max(latest));
+        }
+        Ok(revoked)
+    }
+}
+
+impl Wallet<ECAccumulator> {
+    // refresh_witnesses for accumulator credentials: the feed is folded into
+    // each witness at once (Credential::batch_update_witness), credentials in
+    // parallel. Returns the ids found revoked
+    pub fn par_refresh_witnesses(&mut self, issuer: &str, updates: &[AccRevocation]) -> Vec<String> {
+        let log = UpdateLog::new(&updates.iter().map(|u| (u.new_alpha, u.delta)).collect::<Vec<_>>());
+        let ids = self.by_issuer.get(issuer).cloned().unwrap_or_default();
+        self.entries
+            .par_iter_mut()
+            .filter(|(id, entry)| ids.contains(*id) && !entry.revoked)
+            .filter_map(|(id, entry)| {
+                if entry.cred.apply_update_log(&log) {
+                    return None;
+                }
+                entry.revoked = true;
+                Some(id.clone())
+            })
+            .collect()
+    }
+}
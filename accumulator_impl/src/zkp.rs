@@ -1,159 +1,242 @@
-// use bls12_381::{G1Projective, G2Projective, Scalar, pairing, Gt};
-// use group::{Curve, GroupEncoding};
-// use ff::Field;
-// use sha2::{Digest, Sha256};
-// use rand::thread_rng;
-// use std::ops::Neg;
-
-// pub struct ZKProof {
-//     pub c: Scalar,
-//     pub s_x: Scalar,
-//     pub s_l: Scalar,
-//     pub s_sigma: Scalar,
-//     pub s_rho: Scalar,
-//     pub s_xsigma: Scalar,
-//     pub s_xrho: Scalar,
-//     pub c_w: G1Projective,
-//     pub c_sigma: G1Projective,
-//     pub c_rho: G1Projective,
-// }
-
-// pub fn commit(g: &G1Projective, h: &G1Projective, x: Scalar, l: Scalar) -> G1Projective {
-//     g * x + h * l
-// }
-
-// pub fn hash_gt(gt: &Gt) -> Vec<u8> {
-//     let mut hasher = Sha256::new();
-//     hasher.update(format!("{:?}", gt).as_bytes());
-//     hasher.finalize().to_vec()
-// }
-
-// pub fn gen_zkp(
-//     g: &G1Projective,
-//     h: &G1Projective,
-//     k: &G1Projective,
-//     z: &G1Projective,
-//     g2: &G2Projective,
-//     j: &G2Projective,
-//     alpha: &G1Projective,
-//     e_z_g2: &Gt,
-//     e_z_j: &Gt,
-//     x: Scalar,
-//     l: Scalar,
-//     witness: &G1Projective,
-// ) -> (G1Projective, ZKProof) {
-//     let mut rng = thread_rng();
-//     let commitment = commit(g, h, x, l);
-//     let sigma = Scalar::random(&mut rng);
-//     let rho = Scalar::random(&mut rng);
-//     let v_x = Scalar::random(&mut rng);
-//     let v_l = Scalar::random(&mut rng);
-//     let v_sigma = Scalar::random(&mut rng);
-//     let v_rho = Scalar::random(&mut rng);
-//     let v_xsigma = Scalar::random(&mut rng);
-//     let v_xrho = Scalar::random(&mut rng);
-
-//     let c_w = witness + z * (sigma + rho);
-//     let c_sigma = h * sigma;
-//     let c_rho = k * rho;
-//     let t_sigma = h * v_sigma;
-//     let t_rho = k * v_rho;
-//     let t_sigma_prime = c_sigma * v_x + h * v_xsigma.neg();
-//     let t_rho_prime = c_rho * v_x + k * v_xrho.neg();
-//     let e_cw_g2 = pairing(&c_w.to_affine(), &g2.to_affine());
-//     let e_cw_j = pairing(&c_w.to_affine(), &j.to_affine());
-//     let y = pairing(&alpha.to_affine(), &g2.to_affine()) - e_cw_j;
-//     let t_o = y * v_l + e_cw_g2 * v_x + e_z_g2 * (v_xsigma + v_xrho).neg() + e_z_j * (v_sigma + v_rho).neg();
-
-//     let mut hasher = Sha256::new();
-//     hasher.update(g.to_affine().to_bytes());
-//     hasher.update(h.to_affine().to_bytes());
-//     hasher.update(k.to_affine().to_bytes());
-//     hasher.update(z.to_affine().to_bytes());
-//     hasher.update(g2.to_affine().to_bytes());
-//     hasher.update(j.to_affine().to_bytes());
-//     hasher.update(alpha.to_affine().to_bytes());
-//     hasher.update(commitment.to_affine().to_bytes());
-//     hasher.update(c_w.to_affine().to_bytes());
-//     hasher.update(c_sigma.to_affine().to_bytes());
-//     hasher.update(c_rho.to_affine().to_bytes());
-//     hasher.update(t_sigma.to_affine().to_bytes());
-//     hasher.update(t_rho.to_affine().to_bytes());
-//     hasher.update(t_sigma_prime.to_affine().to_bytes());
-//     hasher.update(t_rho_prime.to_affine().to_bytes());
-//     hasher.update(hash_gt(&t_o));
-//     let hash = hasher.finalize();
-//     let mut wide = [0u8; 64];
-//     wide[..32].copy_from_slice(&hash);
-//     let c = Scalar::from_bytes_wide(&wide);
-
-//     let s_x = v_x - c * x;
-//     let s_l = v_l - c * l;
-//     let s_sigma = v_sigma - c * sigma;
-//     let s_rho = v_rho - c * rho;
-//     let s_xsigma = v_xsigma - c * (x * sigma);
-//     let s_xrho = v_xrho - c * (x * rho);
-
-//     (
-//         commitment,
-//         ZKProof {
-//             c,
-//             s_x,
-//             s_l,
-//             s_sigma,
-//             s_rho,
-//             s_xsigma,
-//             s_xrho,
-//             c_w,
-//             c_sigma,
-//             c_rho,
-//         },
-//     )
-// }
-
-// pub fn verify_zkp(
-//     g: &G1Projective,
-//     h: &G1Projective,
-//     k: &G1Projective,
-//     z: &G1Projective,
-//     g2: &G2Projective,
-//     j: &G2Projective,
-//     alpha: &G1Projective,
-//     e_z_g2: &Gt,
-//     e_z_j: &Gt,
-//     commitment: &G1Projective,
-//     proof: &ZKProof,
-// ) -> bool {
-//     let t_sigma = proof.c_sigma * proof.c + h * proof.s_sigma;
-//     let t_rho = proof.c_rho * proof.c + k * proof.s_rho;
-//     let t_sigma_prime = proof.c_sigma * proof.s_x + h * proof.s_xsigma.neg();
-//     let t_rho_prime = proof.c_rho * proof.s_x + k * proof.s_xrho.neg();
-//     let e_cw_g2 = pairing(&proof.c_w.to_affine(), &g2.to_affine());
-//     let e_cw_j = pairing(&proof.c_w.to_affine(), &j.to_affine());
-//     let y = pairing(&alpha.to_affine(), &g2.to_affine()) - e_cw_j;
-//     let t_o = y * proof.c + e_cw_g2 * proof.s_x + e_z_g2 * (proof.s_xsigma + proof.s_xrho).neg() + e_z_j * (proof.s_sigma + proof.s_rho).neg();
-
-//     let mut hasher = Sha256::new();
-//     hasher.update(g.to_affine().to_bytes());
-//     hasher.update(h.to_affine().to_bytes());
-//     hasher.update(k.to_affine().to_bytes());
-//     hasher.update(z.to_affine().to_bytes());
-//     hasher.update(g2.to_affine().to_bytes());
-//     hasher.update(j.to_affine().to_bytes());
-//     hasher.update(alpha.to_affine().to_bytes());
-//     hasher.update(commitment.to_affine().to_bytes());
-//     hasher.update(proof.c_w.to_affine().to_bytes());
-//     hasher.update(proof.c_sigma.to_affine().to_bytes());
-//     hasher.update(proof.c_rho.to_affine().to_bytes());
-//     hasher.update(t_sigma.to_affine().to_bytes());
-//     hasher.update(t_rho.to_affine().to_bytes());
-//     hasher.update(t_sigma_prime.to_affine().to_bytes());
-//     hasher.update(t_rho_prime.to_affine().to_bytes());
-//     hasher.update(hash_gt(&t_o));
-//     let hash = hasher.finalize();
-//     let mut wide = [0u8; 64];
-//     wide[..32].copy_from_slice(&hash);
-//     let expected_c = Scalar::from_bytes_wide(&wide);
-
-//     proof.c == expected_c
-// }
\ No newline at end of file
+// Zero-knowledge proof of accumulator membership: the holder shows that the
+// x committed in Cx = g*x + h*l has a witness w with e(w, g2*x + j) = e(alpha, g2),
+// without revealing x, l or w. The witness is blinded as
+//
+//   C_w = w + z*(sigma + rho)    C_sigma = h*sigma    C_rho = k*rho
+//
+// and the proof is of knowledge of (x, l, sigma, rho, x*sigma, x*rho) with
+//
+//   Cx      = g*x + h*l
+//   C_sigma = h*sigma            0 = C_sigma*x - h*(x*sigma)
+//   C_rho   = k*rho              0 = C_rho*x - k*(x*rho)
+//   e(alpha, g2) - e(C_w, j) = e(C_w, g2)*x - e(z, g2)*(x*sigma + x*rho) - e(z, j)*(sigma + rho)
+//
+// Responses are s = v - c*secret. With y = e(alpha, g2) - e(C_w, j), the
+// pairing statement's commitment t_o checks out iff
+//
+//   t_o = e(alpha*c + C_w*s_x - z*(s_xsigma + s_xrho), g2) + e(-C_w*c - z*(s_sigma + s_rho), j)
+//
+// so the proof carries t_o and verifying it takes one two-pairing product.
+// check_zkp does everything else and returns that equation as a PairingCheck;
+// batch_holds checks a random linear combination of many of them with two
+// pairings in total, and find_invalid bisects a failing batch.
+//
+// The challenge comes from the caller's
+// transcript (transcript::session_transcript), which already holds the issuer
+// keys, the epoch and the verifier session; the proof adds alpha, Cx, the
+// blinded witness and its commitments. A proof is not replayable elsewhere.
+//
+// An earlier bls12_381 draft of this file had no statement for Cx at all, so x
+// was not bound to the commitment, and put y*v_l into the pairing commitment,
+// which the verifier's y*c never matches.
+
+use ark_bls12_381::{Bls12_381, Fr, G1Projective};
+use ark_ec::pairing::{Pairing, PairingOutput};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{UniformRand, Zero};
+use merlin::Transcript;
+use rand::{thread_rng, Rng};
+
+use crate::keystore::IssuerPublicKeys;
+use crate::secret::Secret;
+use crate::transcript::TranscriptProtocol;
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ZKProof {
+    pub c: Fr,
+    pub s_x: Fr,
+    pub s_l: Fr,
+    pub s_sigma: Fr,
+    pub s_rho: Fr,
+    pub s_xsigma: Fr,
+    pub s_xrho: Fr,
+    pub c_w: G1Projective,
+    pub c_sigma: G1Projective,
+    pub c_rho: G1Projective,
+    pub t_o: PairingOutput<Bls12_381>,
+}
+
+// The pairing equation of a verified membership proof, e(a, g2) + e(b, j) = t_o
+#[derive(Clone, Debug, PartialEq)]
+pub struct PairingCheck {
+    a: G1Projective,
+    b: G1Projective,
+    t_o: PairingOutput<Bls12_381>,
+}
+
+pub fn commit(g: &G1Projective, h: &G1Projective, x: &Fr, l: &Fr) -> G1Projective {
+    *g * x + *h * l
+}
+
+// Schnorr commitments, in the order they are absorbed
+struct Commitments {
+    t_x: G1Projective,
+    t_sigma: G1Projective,
+    t_rho: G1Projective,
+    t_sigma_prime: G1Projective,
+    t_rho_prime: G1Projective,
+    t_o: PairingOutput<Bls12_381>,
+}
+
+fn challenge(
+    transcript: &mut Transcript,
+    alpha: &G1Projective,
+    commitment: &G1Projective,
+    proof: (&G1Projective, &G1Projective, &G1Projective),
+    t: &Commitments,
+) -> Fr {
+    transcript.domain_sep(b"membership");
+    transcript.append_serialized(b"alpha", alpha);
+    transcript.append_serialized(b"cx", commitment);
+    transcript.append_serialized(b"c_w", proof.0);
+    transcript.append_serialized(b"c_sigma", proof.1);
+    transcript.append_serialized(b"c_rho", proof.2);
+    transcript.append_serialized(b"t_x", &t.t_x);
+    transcript.append_serialized(b"t_sigma", &t.t_sigma);
+    transcript.append_serialized(b"t_rho", &t.t_rho);
+    transcript.append_serialized(b"t_sigma_prime", &t.t_sigma_prime);
+    transcript.append_serialized(b"t_rho_prime", &t.t_rho_prime);
+    transcript.append_serialized(b"t_o", &t.t_o);
+    transcript.challenge_scalar(b"c")
+}
+
+// e(a, g2) + e(b, j)
+fn pairing_product(keys: &IssuerPublicKeys, a: G1Projective, b: G1Projective) -> PairingOutput<Bls12_381> {
+    Bls12_381::multi_pairing([a, b], [keys.g2, keys.j])
+}
+
+// Holder side: x, l open Cx and witness is x's membership witness for alpha.
+// Returns Cx with the proof
+pub fn gen_zkp(
+    keys: &IssuerPublicKeys,
+    alpha: &G1Projective,
+    x: &Fr,
+    l: &Fr,
+    witness: &G1Projective,
+    transcript: &mut Transcript,
+) -> (G1Projective, ZKProof) {
+    let (g, h, k, z) = (keys.g, keys.h, keys.k, keys.z);
+    let mut rng = thread_rng();
+    let commitment = commit(&g, &h, x, l);
+    let sigma = Secret::new(Fr::rand(&mut rng));
+    let rho = Secret::new(Fr::rand(&mut rng));
+    let xsigma = Secret::new(*x * sigma.expose());
+    let xrho = Secret::new(*x * rho.expose());
+    let v: Secret<[Fr; 6]> = Secret::new([(); 6].map(|_| Fr::rand(&mut rng)));
+    let [v_x, v_l, v_sigma, v_rho, v_xsigma, v_xrho] = v.expose();
+
+    let c_w = *witness + z * (*sigma.expose() + rho.expose());
+    let c_sigma = h * sigma.expose();
+    let c_rho = k * rho.expose();
+    let t = Commitments {
+        t_x: g * v_x + h * v_l,
+        t_sigma: h * v_sigma,
+        t_rho: k * v_rho,
+        t_sigma_prime: c_sigma * v_x - h * v_xsigma,
+        t_rho_prime: c_rho * v_x - k * v_xrho,
+        t_o: pairing_product(keys, c_w * v_x - z * (*v_xsigma + v_xrho), -z * (*v_sigma + v_rho)),
+    };
+    let c = challenge(transcript, alpha, &commitment, (&c_w, &c_sigma, &c_rho), &t);
+
+    (
+        commitment,
+        ZKProof {
+            c,
+            s_x: *v_x - c * x,
+            s_l: *v_l - c * l,
+            s_sigma: *v_sigma - c * sigma.expose(),
+            s_rho: *v_rho - c * rho.expose(),
+            s_xsigma: *v_xsigma - c * xsigma.expose(),
+            s_xrho: *v_xrho - c * xrho.expose(),
+            c_w,
+            c_sigma,
+            c_rho,
+            t_o: t.t_o,
+        },
+    )
+}
+
+// Verifier side, against the accumulator value alpha it trusts
+pub fn verify_zkp(
+    keys: &IssuerPublicKeys,
+    alpha: &G1Projective,
+    commitment: &G1Projective,
+    proof: &ZKProof,
+    transcript: &mut Transcript,
+) -> bool {
+    check_zkp(keys, alpha, commitment, proof, transcript).is_some_and(|check| check.holds(keys))
+}
+
+// verify_zkp without the pairings: None if the proof is already invalid,
+// else the pairing equation still to check, alone or in a batch
+pub fn check_zkp(
+    keys: &IssuerPublicKeys,
+    alpha: &G1Projective,
+    commitment: &G1Projective,
+    proof: &ZKProof,
+    transcript: &mut Transcript,
+) -> Option<PairingCheck> {
+    let (g, h, k, z) = (keys.g, keys.h, keys.k, keys.z);
+    let c = proof.c;
+    let t = Commitments {
+        t_x: *commitment * c + g * proof.s_x + h * proof.s_l,
+        t_sigma: proof.c_sigma * c + h * proof.s_sigma,
+        t_rho: proof.c_rho * c + k * proof.s_rho,
+        t_sigma_prime: proof.c_sigma * proof.s_x - h * proof.s_xsigma,
+        t_rho_prime: proof.c_rho * proof.s_x - k * proof.s_xrho,
+        t_o: proof.t_o,
+    };
+    if c != challenge(transcript, alpha, commitment, (&proof.c_w, &proof.c_sigma, &proof.c_rho), &t) {
+        return None;
+    }
+    Some(PairingCheck {
+        a: *alpha * c + proof.c_w * proof.s_x - z * (proof.s_xsigma + proof.s_xrho),
+        b: -proof.c_w * c - z * (proof.s_sigma + proof.s_rho),
+        t_o: proof.t_o,
+    })
+}
+
+impl PairingCheck {
+    pub fn holds(&self, keys: &IssuerPublicKeys) -> bool {
+        pairing_product(keys, self.a, self.b) == self.t_o
+    }
+}
+
+// Whether all checks hold, with two pairings whatever their number: checks
+// sum(r_i * a_i), sum(r_i * b_i) against sum(r_i * t_o_i) for random 128-bit
+// r_i, so a batch with a failing check passes with probability 2^-128
+pub fn batch_holds(keys: &IssuerPublicKeys, checks: &[PairingCheck]) -> bool {
+    let mut rng = thread_rng();
+    let (mut a, mut b, mut t_o) = (G1Projective::zero(), G1Projective::zero(), PairingOutput::<Bls12_381>::zero());
+    for check in checks {
+        let r = Fr::from(rng.gen::<u128>());
+        a += check.a * r;
+        b += check.b * r;
+        t_o += check.t_o * r;
+    }
+    pairing_product(keys, a, b) == t_o
+}
+
+// Indices of the failing checks, by bisecting the batch; costs two pairings
+// when all hold, and O(f log n) batches for f failures among n
+pub fn find_invalid(keys: &IssuerPublicKeys, checks: &[PairingCheck]) -> Vec<usize> {
+    let mut invalid = Vec::new();
+    bisect(keys, checks, 0, false, &mut invalid);
+    invalid
+}
+
+// known_invalid: the caller already knows the range holds a failing check
+fn bisect(keys: &IssuerPublicKeys, checks: &[PairingCheck], offset: usize, known_invalid: bool, invalid: &mut Vec<usize>) {
+    if checks.is_empty() || (!known_invalid && batch_holds(keys, checks)) {
+        return;
+    }
+    if checks.len() == 1 {
+        invalid.push(offset);
+        return;
+    }
+    let mid = checks.len() / 2;
+    let before = invalid.len();
+    bisect(keys, &checks[..mid], offset, false, invalid);
+    // if the first half holds, the failure is in the second
+    bisect(keys, &checks[mid..], offset + mid, invalid.len() == before, invalid);
+}
@@ -0,0 +1,60 @@
+use ark_bls12_381::Fr;
+use ark_std::UniformRand;
+use rand::thread_rng;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::credential::Credential;
+use accumulator_impl::helper::verify_witness;
+use accumulator_impl::issuer::Issuer;
+
+fn messages() -> Vec<Fr> {
+    let mut rng = thread_rng();
+    (0..5).map(|_| Fr::rand(&mut rng)).collect()
+}
+
+fn is_member(acc: &ECAccumulator, issuer: &Issuer, cred: &Credential) -> bool {
+    verify_witness(acc.get_alpha(), cred.get_x_val(), cred.get_witness(), issuer.get_g2(), issuer.get_j())
+}
+
+#[test]
+fn witnesses_stay_valid_when_elements_are_added() {
+    let mut rng = thread_rng();
+    let issuer = Issuer::new();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let mut cred = issuer.gen_witness_n_cred(&acc, messages());
+    assert!(is_member(&acc, &issuer, &cred));
+
+    let y = Fr::rand(&mut rng);
+    let (old_alpha, _) = issuer.add_to_acc(&mut acc, &[y])[0];
+    assert!(!is_member(&acc, &issuer, &cred));
+    cred.update_witness_on_add(y, old_alpha);
+    assert!(is_member(&acc, &issuer, &cred));
+
+    let ys: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+    let updates = issuer.add_to_acc(&mut acc, &ys);
+    cred.batch_update_witness_on_add(&updates);
+    assert!(is_member(&acc, &issuer, &cred));
+}
+
+#[test]
+fn suspend_and_reinstate() {
+    let issuer = Issuer::new();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let mut alice = issuer.gen_witness_n_cred(&acc, messages());
+    let mut bob = issuer.gen_witness_n_cred(&acc, messages());
+
+    // suspend alice
+    let update = issuer.revoke_a_cred(&mut acc, &alice).unwrap();
+    assert_eq!(acc.get_deltas(), &vec![update.delta]);
+    bob.update_witness(update.delta, update.new_alpha);
+    assert!(is_member(&acc, &issuer, &bob));
+    assert!(!is_member(&acc, &issuer, &alice));
+
+    // reinstate her: her new witness is the alpha before the addition
+    let old_alpha = issuer.reinstate_a_cred(&mut acc, &alice);
+    assert!(acc.get_deltas().is_empty());
+    alice.update_witness_on_add(*alice.get_x_val(), old_alpha);
+    bob.update_witness_on_add(*alice.get_x_val(), old_alpha);
+    assert!(is_member(&acc, &issuer, &alice));
+    assert!(is_member(&acc, &issuer, &bob));
+}
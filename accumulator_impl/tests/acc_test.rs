@@ -1,140 +1,98 @@
 #[cfg(test)]
 mod tests {
-    use bls12_381::{G1Projective, Scalar};
-    use ff::Field;
-    use group::Group;
+    use ark_bls12_381::{Fr, G1Projective};
+    use ark_std::UniformRand;
+    use merlin::Transcript;
     use rand::thread_rng;
 
     use accumulator_impl::acc::ECAccumulator;
+    use accumulator_impl::helper::verify_witness;
+    use accumulator_impl::issuer::Issuer;
+    use accumulator_impl::keystore::IssuerPublicKeys;
+    use accumulator_impl::replay::VerifierChallenge;
+    use accumulator_impl::transcript::{session_transcript, MEMBERSHIP_PROOF};
     use accumulator_impl::zkp::{commit, gen_zkp, verify_zkp, ZKProof};
 
+    fn messages() -> Vec<Fr> {
+        let mut rng = thread_rng();
+        (0..5).map(|_| Fr::rand(&mut rng)).collect()
+    }
+
+    fn transcript(keys: &IssuerPublicKeys, acc: &ECAccumulator, challenge: &VerifierChallenge) -> Transcript {
+        session_transcript(MEMBERSHIP_PROOF, keys, acc.get_epoch(), challenge)
+    }
+
     #[test]
     fn test_accumulator_operations() {
         let mut rng = thread_rng();
-        let acc = ECAccumulator::new();
+        let issuer = Issuer::new();
+        let mut acc = ECAccumulator::new(issuer.get_g1());
 
         // Generate a witness for an element x
-        let x = Scalar::random(&mut rng);
-        let witness = acc.gen_witness(x);
+        let x = issuer.gen_witness_n_cred(&acc, messages());
 
         // Verify the witness
-        assert!(acc.verify_witness(x, witness), "Witness verification failed");
+        assert!(
+            verify_witness(acc.get_alpha(), x.get_x_val(), x.get_witness(), issuer.get_g2(), issuer.get_j()),
+            "Witness verification failed"
+        );
 
         // Verify invalid witness fails
-        let invalid_witness = G1Projective::random(&mut rng);
+        let invalid_witness = G1Projective::rand(&mut rng);
         assert!(
-            !acc.verify_witness(x, invalid_witness),
+            !verify_witness(acc.get_alpha(), x.get_x_val(), &invalid_witness, issuer.get_g2(), issuer.get_j()),
             "Invalid witness should not verify"
         );
 
-        // Perform deletion
-        let (new_alpha, delta) = acc.del(x);
-
-        // Update the witness for another element y
-        let y = Scalar::random(&mut rng);
-        let old_witness_y = acc.gen_witness(y);
-        let mut updated_acc = acc;
-        updated_acc.alpha = new_alpha;
-        let new_witness_y = updated_acc.update_witness(old_witness_y, y, delta, new_alpha);
+        // Perform deletion, then update the witness for another element y
+        let mut y = issuer.gen_witness_n_cred(&acc, messages());
+        let update = issuer.revoke_a_cred(&mut acc, &x).unwrap();
+        y.update_witness(update.delta, update.new_alpha);
 
         // Verify the updated witness
         assert!(
-            updated_acc.verify_witness(y, new_witness_y),
+            verify_witness(acc.get_alpha(), y.get_x_val(), y.get_witness(), issuer.get_g2(), issuer.get_j()),
             "Updated witness verification failed"
         );
+        assert!(
+            !verify_witness(acc.get_alpha(), x.get_x_val(), x.get_witness(), issuer.get_g2(), issuer.get_j()),
+            "Deleted element should not verify"
+        );
     }
 
     #[test]
     fn test_zkp_generation_and_verification() {
         let mut rng = thread_rng();
-        let acc = ECAccumulator::new();
+        let issuer = Issuer::new();
+        let keys = issuer.public_keys();
+        let challenge = VerifierChallenge::new("https://verifier.example");
+        let acc = ECAccumulator::new(issuer.get_g1());
 
         // Generate a witness for an element x
-        let x = Scalar::random(&mut rng);
-        let l = Scalar::random(&mut rng); // Random blinding factor for Pedersen commitment
-        let witness = acc.gen_witness(x);
+        let cred = issuer.gen_witness_n_cred(&acc, messages());
+        let l = Fr::rand(&mut rng); // Random blinding factor for Pedersen commitment
 
         // Generate ZKP
-        let (commitment, proof) = gen_zkp(
-            &acc.g,
-            &acc.h,
-            &acc.k,
-            &acc.z,
-            &acc.g2,
-            &acc.j,
-            &acc.alpha,
-            &acc.e_z_g2,
-            &acc.e_z_j,
-            x,
-            l,
-            &witness,
-        );
+        let (commitment, proof) =
+            gen_zkp(&keys, acc.get_alpha(), cred.get_x_val(), &l, cred.get_witness(), &mut transcript(&keys, &acc, &challenge));
 
         // Verify ZKP
         assert!(
-            verify_zkp(
-                &acc.g,
-                &acc.h,
-                &acc.k,
-                &acc.z,
-                &acc.g2,
-                &acc.j,
-                &acc.alpha,
-                &acc.e_z_g2,
-                &acc.e_z_j,
-                &commitment,
-                &proof
-            ),
+            verify_zkp(&keys, acc.get_alpha(), &commitment, &proof, &mut transcript(&keys, &acc, &challenge)),
             "ZKP verification failed"
         );
 
         // Test invalid proof (tampered s_x)
-        let tampered_proof = ZKProof {
-            c: proof.c,
-            s_x: Scalar::random(&mut rng), // Tamper s_x
-            s_l: proof.s_l,
-            s_sigma: proof.s_sigma,
-            s_rho: proof.s_rho,
-            s_xsigma: proof.s_xsigma,
-            s_xrho: proof.s_xrho,
-            c_w: proof.c_w,
-            c_sigma: proof.c_sigma,
-            c_rho: proof.c_rho,
-        };
-
+        let tampered_proof = ZKProof { s_x: Fr::rand(&mut rng), ..proof.clone() };
         assert!(
-            !verify_zkp(
-                &acc.g,
-                &acc.h,
-                &acc.k,
-                &acc.z,
-                &acc.g2,
-                &acc.j,
-                &acc.alpha,
-                &acc.e_z_g2,
-                &acc.e_z_j,
-                &commitment,
-                &tampered_proof
-            ),
+            !verify_zkp(&keys, acc.get_alpha(), &commitment, &tampered_proof, &mut transcript(&keys, &acc, &challenge)),
             "Tampered proof should not verify"
         );
 
         // Test invalid commitment
-        let invalid_commitment = commit(&acc.g, &acc.h, Scalar::random(&mut rng), l);
+        let invalid_commitment = commit(&keys.g, &keys.h, &Fr::rand(&mut rng), &l);
         assert!(
-            !verify_zkp(
-                &acc.g,
-                &acc.h,
-                &acc.k,
-                &acc.z,
-                &acc.g2,
-                &acc.j,
-                &acc.alpha,
-                &acc.e_z_g2,
-                &acc.e_z_j,
-                &invalid_commitment,
-                &proof
-            ),
+            !verify_zkp(&keys, acc.get_alpha(), &invalid_commitment, &proof, &mut transcript(&keys, &acc, &challenge)),
             "Invalid commitment should not verify"
         );
     }
@@ -142,104 +100,47 @@ mod tests {
     #[test]
     fn test_zkp_with_deleted_element() {
         let mut rng = thread_rng();
-        let acc = ECAccumulator::new();
+        let issuer = Issuer::new();
+        let keys = issuer.public_keys();
+        let challenge = VerifierChallenge::new("https://verifier.example");
+        let mut acc = ECAccumulator::new(issuer.get_g1());
 
         // Generate a witness for an element x
-        let x = Scalar::random(&mut rng);
-        let l = Scalar::random(&mut rng);
-        let witness = acc.gen_witness(x);
+        let x = issuer.gen_witness_n_cred(&acc, messages());
+        let mut y = issuer.gen_witness_n_cred(&acc, messages());
+        let l = Fr::rand(&mut rng);
 
         // Generate ZKP before deletion
-        let (commitment, proof) = gen_zkp(
-            &acc.g,
-            &acc.h,
-            &acc.k,
-            &acc.z,
-            &acc.g2,
-            &acc.j,
-            &acc.alpha,
-            &acc.e_z_g2,
-            &acc.e_z_j,
-            x,
-            l,
-            &witness,
-        );
+        let (commitment, proof) =
+            gen_zkp(&keys, acc.get_alpha(), x.get_x_val(), &l, x.get_witness(), &mut transcript(&keys, &acc, &challenge));
         assert!(
-            verify_zkp(
-                &acc.g,
-                &acc.h,
-                &acc.k,
-                &acc.z,
-                &acc.g2,
-                &acc.j,
-                &acc.alpha,
-                &acc.e_z_g2,
-                &acc.e_z_j,
-                &commitment,
-                &proof
-            ),
+            verify_zkp(&keys, acc.get_alpha(), &commitment, &proof, &mut transcript(&keys, &acc, &challenge)),
             "ZKP verification before deletion failed"
         );
 
         // Delete x and update accumulator
-        let (new_alpha, delta) = acc.del(x);
-        let mut updated_acc = acc;
-        updated_acc.alpha = new_alpha;
-
-        // Generate a new witness for another element y
-        let y = Scalar::random(&mut rng);
-        let old_witness_y = updated_acc.gen_witness(y);
-        let new_witness_y = updated_acc.update_witness(old_witness_y, y, delta, new_alpha);
-        let new_l = Scalar::random(&mut rng);
-        let (new_commitment, new_proof) = gen_zkp(
-            &updated_acc.g,
-            &updated_acc.h,
-            &updated_acc.k,
-            &updated_acc.z,
-            &updated_acc.g2,
-            &updated_acc.j,
-            &updated_acc.alpha,
-            &updated_acc.e_z_g2,
-            &updated_acc.e_z_j,
-            y,
-            new_l,
-            &new_witness_y,
-        );
+        let update = issuer.revoke_a_cred(&mut acc, &x).unwrap();
 
-        // Verify ZKP for y with updated accumulator
+        // The old proof and a fresh proof for x no longer verify
+        assert!(
+            !verify_zkp(&keys, acc.get_alpha(), &commitment, &proof, &mut transcript(&keys, &acc, &challenge)),
+            "ZKP for deleted element should not verify"
+        );
+        let (stale_commitment, stale_proof) =
+            gen_zkp(&keys, acc.get_alpha(), x.get_x_val(), &l, x.get_witness(), &mut transcript(&keys, &acc, &challenge));
         assert!(
-            verify_zkp(
-                &updated_acc.g,
-                &updated_acc.h,
-                &updated_acc.k,
-                &updated_acc.z,
-                &updated_acc.g2,
-                &updated_acc.j,
-                &updated_acc.alpha,
-                &updated_acc.e_z_g2,
-                &updated_acc.e_z_j,
-                &new_commitment,
-                &new_proof
-            ),
-            "ZKP verification after deletion failed"
+            !verify_zkp(&keys, acc.get_alpha(), &stale_commitment, &stale_proof, &mut transcript(&keys, &acc, &challenge)),
+            "ZKP with a stale witness should not verify"
         );
 
-        // Attempt to verify old proof with updated accumulator (should fail)
+        // y updates its witness and still proves membership
+        y.update_witness(update.delta, update.new_alpha);
+        let new_l = Fr::rand(&mut rng);
+        let (new_commitment, new_proof) =
+            gen_zkp(&keys, acc.get_alpha(), y.get_x_val(), &new_l, y.get_witness(), &mut transcript(&keys, &acc, &challenge));
         assert!(
-            !verify_zkp(
-                &updated_acc.g,
-                &updated_acc.h,
-                &updated_acc.k,
-                &updated_acc.z,
-                &updated_acc.g2,
-                &updated_acc.j,
-                &updated_acc.alpha,
-                &updated_acc.e_z_g2,
-                &updated_acc.e_z_j,
-                &commitment,
-                &proof
-            ),
-            "Old proof should not verify after deletion"
+            verify_zkp(&keys, acc.get_alpha(), &new_commitment, &new_proof, &mut transcript(&keys, &acc, &challenge)),
+            "ZKP verification after update failed"
         );
     }
-}
\ No newline at end of file
+}
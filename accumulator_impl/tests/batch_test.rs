@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::holder::Holder;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::presentation::*;
+use accumulator_impl::replay::VerifierChallenge;
+use accumulator_impl::schema::*;
+use accumulator_impl::transcript::*;
+use accumulator_impl::zkp::*;
+
+fn values(score: u64) -> BTreeMap<String, AttributeValue> {
+    BTreeMap::from([
+        ("name".to_string(), AttributeValue::String("Alice".to_string())),
+        ("dob".to_string(), AttributeValue::Date("2004-11-15".to_string())),
+        ("age".to_string(), AttributeValue::Integer(21)),
+        ("score".to_string(), AttributeValue::Integer(score)),
+        ("candidate_id".to_string(), AttributeValue::Integer(245)),
+    ])
+}
+
+#[test]
+fn batch_finds_the_stale_witnesses() {
+    let schema = exam_candidate_schema();
+    let issuer = Issuer::for_schema(&schema);
+    let keys = issuer.public_keys();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let mut holders: Vec<Holder> =
+        (0..8).map(|_| Holder::new(issuer.issue_with_schema(&acc, &schema, &values(75)).unwrap())).collect();
+
+    // a revocation the holders at 2 and 5 do not catch up with: their proofs
+    // pass the Fiat-Shamir check, only the pairing equation fails
+    let revoked = issuer.issue_with_schema(&acc, &schema, &values(75)).unwrap();
+    let update = issuer.revoke_a_cred(&mut acc, &revoked).unwrap();
+    for (i, holder) in holders.iter_mut().enumerate() {
+        if i != 2 && i != 5 {
+            holder.update_witness(&keys, &update);
+        }
+    }
+
+    let challenge = VerifierChallenge::new("https://verifier.example");
+    let transcript = || session_transcript(MEMBERSHIP_PROOF, &keys, acc.get_epoch(), &challenge);
+    let checks: Vec<PairingCheck> = holders
+        .iter()
+        .map(|holder| {
+            let (cx, proof) = holder.prove_membership(&keys, &acc, &challenge);
+            let check = check_zkp(&keys, acc.get_alpha(), &cx, &proof, &mut transcript()).unwrap();
+            assert_eq!(check.holds(&keys), verify_zkp(&keys, acc.get_alpha(), &cx, &proof, &mut transcript()));
+            check
+        })
+        .collect();
+
+    assert!(!batch_holds(&keys, &checks));
+    assert_eq!(find_invalid(&keys, &checks), vec![2, 5]);
+    let valid: Vec<PairingCheck> =
+        checks.iter().enumerate().filter(|(i, _)| *i != 2 && *i != 5).map(|(_, c)| c.clone()).collect();
+    assert!(batch_holds(&keys, &valid));
+    assert!(find_invalid(&keys, &valid).is_empty());
+    assert!(batch_holds(&keys, &[]));
+    assert_eq!(find_invalid(&keys, &checks[2..3]), vec![0]);
+}
+
+#[test]
+fn validate_a_queue_of_presentations() {
+    let schema = exam_candidate_schema();
+    let issuer = Issuer::for_schema(&schema);
+    let keys = issuer.public_keys();
+    let acc = ECAccumulator::new(issuer.get_g1());
+    let state = AccumulatorState::from(&acc);
+
+    let queue: Vec<(PresentationRequest, Presentation)> = [75, 80, 40, 90]
+        .into_iter()
+        .map(|score| {
+            let cred = issuer.issue_with_schema(&acc, &schema, &values(score)).unwrap();
+            let request = PresentationRequest {
+                reveal: vec!["name".to_string()],
+                ..PresentationRequest::new(&VerifierChallenge::new("https://verifier.example"))
+            };
+            let presentation = Presentation::create(&request, &cred, &keys, &schema, &state).unwrap();
+            (request, presentation)
+        })
+        .collect();
+    let batch: Vec<(&PresentationRequest, &Presentation)> = queue.iter().map(|(r, p)| (r, p)).collect();
+    let results = PresentationRequest::validate_batch(&batch, &keys, &schema, &state);
+    assert_eq!(results, vec![Ok(()); 4]);
+    for ((request, presentation), result) in batch.iter().zip(results) {
+        assert_eq!(request.validate(presentation, &keys, &schema, &state), result);
+    }
+
+    // answers to the wrong request are caught before the batch, the others
+    // still pass
+    let mut mixed = batch.clone();
+    mixed[1] = (batch[2].0, batch[1].1);
+    assert_eq!(
+        PresentationRequest::validate_batch(&mixed, &keys, &schema, &state),
+        vec![Ok(()), Err(PresentationError::NonceMismatch), Ok(()), Ok(())]
+    );
+
+    // a state whose alpha differs at the same epoch fails them all
+    let forged = AccumulatorState { epoch: state.epoch, alpha: ECAccumulator::new(issuer.get_g1()).alpha };
+    assert!(PresentationRequest::validate_batch(&batch, &keys, &schema, &forged)
+        .iter()
+        .all(|r| *r == Err(PresentationError::InvalidProof)));
+}
@@ -0,0 +1,91 @@
+use ark_bls12_381::Fr;
+use ark_std::UniformRand;
+use rand::thread_rng;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::holder::Holder;
+use accumulator_impl::keystore::*;
+
+const PASSPHRASE: &str = "correct horse battery staple";
+
+fn messages() -> Vec<Fr> {
+    let mut rng = thread_rng();
+    (0..5).map(|_| Fr::rand(&mut rng)).collect()
+}
+
+// cheap Argon2id settings, the default cost is for production
+fn keystore(name: &str) -> IssuerKeystore {
+    let dir = std::env::temp_dir().join(format!("issuer_keys_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    IssuerKeystore::with_kdf(dir, KdfParams { m_cost: 64, t_cost: 1, p_cost: 1 }).unwrap()
+}
+
+fn is_valid(keys: &IssuerPublicKeys, acc: &ECAccumulator, holder: &Holder) -> bool {
+    holder.verify_mem(&keys.pk, &keys.sig_param, acc.get_alpha(), &keys.g2, &keys.j)
+}
+
+#[test]
+fn issuer_survives_a_restart() {
+    let store = keystore("restart");
+    let issuer = store.load_or_create(PASSPHRASE).unwrap();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let alice = Holder::new(issuer.gen_witness_n_cred(&acc, messages()));
+    let mut bob = Holder::new(issuer.gen_witness_n_cred(&acc, messages()));
+    drop(issuer);
+
+    // the reloaded issuer can still revoke what the first one issued
+    let issuer = store.load_or_create(PASSPHRASE).unwrap();
+    assert_eq!(store.key_ids().unwrap(), vec![1]);
+    let update = issuer.revoke_a_cred(&mut acc, alice.get_cred()).unwrap();
+    assert!(bob.update_witness(&issuer.public_keys(), &update));
+
+    let keys = store.public_keys(bob.get_cred().get_key_id()).unwrap();
+    assert_eq!(keys, issuer.public_keys());
+    assert!(is_valid(&keys, &acc, &bob));
+    assert!(!is_valid(&keys, &acc, &alice));
+}
+
+#[test]
+fn wrong_passphrase_or_tampering_is_rejected() {
+    let store = keystore("tamper");
+    store.load_or_create(PASSPHRASE).unwrap();
+    assert!(store.load("Tr0ub4dor&3").is_err());
+    assert!(store.load(PASSPHRASE).is_ok());
+
+    // swapping in another version's public keys breaks the AEAD binding
+    let path = store.dir().join(KEYSTORE_FILE);
+    let other = keystore("tamper_other");
+    other.load_or_create(PASSPHRASE).unwrap();
+    let mut manifest: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let foreign: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(other.dir().join(KEYSTORE_FILE)).unwrap()).unwrap();
+    manifest["keys"][0]["public"] = foreign["keys"][0]["public"].clone();
+    std::fs::write(&path, manifest.to_string()).unwrap();
+    assert!(store.load(PASSPHRASE).is_err());
+}
+
+#[test]
+fn rotated_keys_keep_old_credentials_verifiable() {
+    let store = keystore("rotate");
+    let v1 = store.load_or_create(PASSPHRASE).unwrap();
+    let acc_v1 = ECAccumulator::new(v1.get_g1());
+    let old = Holder::new(v1.gen_witness_n_cred(&acc_v1, messages()));
+
+    let v2 = store.rotate(PASSPHRASE).unwrap();
+    assert_eq!(v2.get_key_id(), 2);
+    assert_eq!(store.current_key_id().unwrap(), Some(2));
+    assert_eq!(store.load(PASSPHRASE).unwrap().get_key_id(), 2);
+    let acc_v2 = ECAccumulator::new(v2.get_g1());
+    let new = Holder::new(v2.gen_witness_n_cred(&acc_v2, messages()));
+
+    // verifiers pick the keys by the credential's key id
+    let keys_old = store.public_keys(old.get_cred().get_key_id()).unwrap();
+    let keys_new = store.public_keys(new.get_cred().get_key_id()).unwrap();
+    assert!(is_valid(&keys_old, &acc_v1, &old));
+    assert!(is_valid(&keys_new, &acc_v2, &new));
+    assert!(!is_valid(&keys_new, &acc_v1, &old));
+
+    // the old version is still there to revoke with
+    let v1 = store.load_version(1, PASSPHRASE).unwrap();
+    assert_eq!(v1.public_keys(), keys_old);
+    assert!(store.save(&v1, PASSPHRASE).is_err());
+}
@@ -0,0 +1,215 @@
+use ark_bls12_381::{Fr, G1Projective, G2Projective};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_std::UniformRand;
+use rand::thread_rng;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::helper::generate_bbs_param_keypair;
+use accumulator_impl::keystore::IssuerPublicKeys;
+use accumulator_impl::revocation::merkle::*;
+use accumulator_impl::revocation::{RevocationError, RevocationScheme};
+use accumulator_impl::transcript::{new_transcript, MEMBERSHIP_PROOF};
+use accumulator_impl::zkp::commit;
+
+#[test]
+fn membership_and_non_membership_proofs() {
+    let mut rng = thread_rng();
+    let mut tree = SparseMerkleTree::new(DEFAULT_DEPTH);
+    let params = tree.params();
+    let empty_root = tree.root();
+
+    let keys: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+    for key in keys.iter() {
+        let update = tree.insert(*key).unwrap();
+        assert_eq!(update.new_root, tree.root());
+    }
+    assert_eq!(tree.insert(keys[0]), Err(MerkleError::AlreadyPresent));
+    assert_eq!(tree.len(), 5);
+
+    let root = tree.root();
+    let mem = tree.prove_membership(&keys[2]).unwrap();
+    assert!(verify_membership(&root, &keys[2], &mem, &params));
+    assert!(!verify_membership(&root, &keys[3], &mem, &params));
+    assert!(tree.prove_non_membership(&keys[2]).is_none());
+
+    let outsider = Fr::rand(&mut rng);
+    assert!(tree.prove_membership(&outsider).is_none());
+    let non_mem = tree.prove_non_membership(&outsider).unwrap();
+    assert!(verify_non_membership(&root, &outsider, &non_mem, &params));
+    assert!(!verify_non_membership(&root, &keys[1], &non_mem, &params));
+
+    for key in keys.iter() {
+        tree.remove(key).unwrap();
+    }
+    assert_eq!(tree.remove(&keys[0]), Err(MerkleError::NotPresent));
+    assert_eq!(tree.root(), empty_root);
+}
+
+#[test]
+fn occupied_slot_proves_non_membership() {
+    let mut rng = thread_rng();
+    // two levels: four slots, collisions are easy to find
+    let mut tree = SparseMerkleTree::new(2);
+    let params = tree.params();
+    let config = params.config.clone();
+
+    let x_a = Fr::rand(&mut rng);
+    let a = key_from_x(&x_a, &config);
+    tree.insert(a).unwrap();
+    let (x, b) = loop {
+        let x = Fr::rand(&mut rng);
+        let candidate = key_from_x(&x, &config);
+        if candidate != a && tree.prove_non_membership(&candidate).unwrap().occupant == Some(a) {
+            break (x, candidate);
+        }
+    };
+    assert_eq!(tree.insert(b), Err(MerkleError::SlotTaken));
+
+    let proof = tree.prove_non_membership(&b).unwrap();
+    assert!(verify_non_membership(&tree.root(), &b, &proof, &params));
+
+    // claiming the slot is empty must fail
+    let lie = NonMembershipProof { occupant: None, ..proof.clone() };
+    assert!(!verify_non_membership(&tree.root(), &b, &lie, &params));
+
+    let satisfied = |x: Fr, proof: NonMembershipProof| {
+        let challenge = Fr::rand(&mut thread_rng());
+        let circuit = MerkleNonMembershipCircuit { root: tree.root(), challenge, x, proof, config: config.clone() };
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        // a witness can be bad enough to fail synthesis already
+        circuit.generate_constraints(cs.clone()).is_ok() && cs.is_satisfied().unwrap()
+    };
+    assert!(satisfied(x, proof.clone()));
+    assert!(!satisfied(x, lie));
+
+    // the key is derived from x inside the circuit: the revoked x_a cannot
+    // borrow the path that shows b absent
+    assert!(!satisfied(x_a, proof));
+}
+
+#[test]
+fn verifier_pins_the_depth() {
+    let mut rng = thread_rng();
+    let params = MerkleParams::new(DEFAULT_DEPTH);
+
+    // a path from a shallower tree checks out against that tree's root, but
+    // the verifier expects DEFAULT_DEPTH levels
+    let shallow = SparseMerkleTree::new(4);
+    let key = Fr::rand(&mut rng);
+    let proof = shallow.prove_non_membership(&key).unwrap();
+    assert!(verify_non_membership(&shallow.root(), &key, &proof, &shallow.params()));
+    assert!(!verify_non_membership(&shallow.root(), &key, &proof, &params));
+
+    let mut tree = SparseMerkleTree::new(DEFAULT_DEPTH);
+    tree.insert(key).unwrap();
+    let mut mem = tree.prove_membership(&key).unwrap();
+    assert!(verify_membership(&tree.root(), &key, &mem, &params));
+    mem.path.siblings.pop();
+    assert!(!verify_membership(&tree.root(), &key, &mem, &params));
+}
+
+#[test]
+fn paths_follow_published_updates() {
+    let mut rng = thread_rng();
+    let mut tree = SparseMerkleTree::new(DEFAULT_DEPTH);
+    let params = tree.params();
+    let config = params.config.clone();
+
+    let mine = Fr::rand(&mut rng);
+    let mut proof = tree.prove_non_membership(&mine).unwrap();
+    for _ in 0..6 {
+        let update = tree.insert(Fr::rand(&mut rng)).unwrap();
+        assert!(update_path(&mut proof.path, &update, &config));
+        assert!(verify_non_membership(&tree.root(), &mine, &proof, &params));
+    }
+    assert_eq!(proof, tree.prove_non_membership(&mine).unwrap());
+
+    // our own revocation cannot be applied
+    let update = tree.insert(mine).unwrap();
+    assert!(!update_path(&mut proof.path, &update, &config));
+}
+
+#[test]
+fn non_membership_legogroth16() {
+    let mut rng = thread_rng();
+    let mut tree = SparseMerkleTree::new(DEFAULT_DEPTH);
+    let params = MerkleZkParams::setup(tree.params(), &mut rng);
+    for _ in 0..3 {
+        tree.insert(Fr::rand(&mut rng)).unwrap();
+    }
+    let (x, root) = (Fr::rand(&mut rng), tree.root());
+    let witness = tree.prove_non_membership(&key_from_x(&x, tree.config())).unwrap();
+
+    let transcript = new_transcript(MEMBERSHIP_PROOF);
+    let (cx, proof) = gen_non_membership_zkp(&params, &root, &x, &witness, &mut transcript.clone()).unwrap();
+    assert!(verify_non_membership_zkp(&params, &root, &cx, &proof, &mut transcript.clone()));
+
+    // bound to the root, the transcript and the x in Cx
+    assert!(!verify_non_membership_zkp(&params, &(root + Fr::from(1u64)), &cx, &proof, &mut transcript.clone()));
+    assert!(!verify_non_membership_zkp(&params, &root, &cx, &proof, &mut new_transcript(b"other")));
+    let other = commit(&params.g, &params.h, &Fr::rand(&mut rng), &Fr::rand(&mut rng));
+    assert!(!verify_non_membership_zkp(&params, &root, &other, &proof, &mut transcript.clone()));
+
+    // nothing repeats between two proofs for the same x
+    let (cx2, proof2) = gen_non_membership_zkp(&params, &root, &x, &witness, &mut transcript.clone()).unwrap();
+    assert!(verify_non_membership_zkp(&params, &root, &cx2, &proof2, &mut transcript.clone()));
+    assert_ne!(cx, cx2);
+    assert_ne!(proof.snark.d, proof2.snark.d);
+
+    // once x is revoked its old path no longer leads to the root
+    tree.insert(key_from_x(&x, tree.config())).unwrap();
+    assert!(gen_non_membership_zkp(&params, &tree.root(), &x, &witness, &mut transcript.clone()).is_none());
+}
+
+// Same flow through the trait for both backends
+fn revocation_flow<S: RevocationScheme>(scheme: &mut S, sk: &S::SecretKey, params: &S::VerifyParams) {
+    let mut rng = thread_rng();
+    let alice = Fr::rand(&mut rng);
+    let bob = Fr::rand(&mut rng);
+    let check = |scheme: &S, x: &Fr, wit: &S::Witness| {
+        let transcript = new_transcript(MEMBERSHIP_PROOF);
+        let state = scheme.public_state();
+        S::prove(params, &state, x, wit, &mut transcript.clone())
+            .is_some_and(|proof| S::verify(params, &state, &proof, &mut transcript.clone()))
+    };
+    let mut alice_wit = scheme.gen_witness(&alice, sk).unwrap();
+    let mut bob_wit = scheme.gen_witness(&bob, sk).unwrap();
+    assert!(check(scheme, &alice, &alice_wit));
+
+    let update = scheme.revoke(&bob, sk).unwrap();
+    assert!(matches!(scheme.revoke(&bob, sk), Err(RevocationError::AlreadyRevoked)));
+    assert!(!check(scheme, &alice, &alice_wit));
+
+    assert!(S::update_witness(params, &alice, &mut alice_wit, &update));
+    assert!(check(scheme, &alice, &alice_wit));
+
+    assert!(!S::update_witness(params, &bob, &mut bob_wit, &update));
+    assert!(!check(scheme, &bob, &bob_wit));
+    assert!(scheme.gen_witness(&bob, sk).is_none());
+}
+
+#[test]
+fn both_backends_behind_the_trait() {
+    let mut rng = thread_rng();
+    let (sig_param, keypair) = generate_bbs_param_keypair();
+    let g2 = G2Projective::rand(&mut rng);
+    let keys = IssuerPublicKeys {
+        key_id: 1,
+        pk: keypair.public_key.clone(),
+        sig_param,
+        g1: G1Projective::rand(&mut rng),
+        g2,
+        j: g2 * keypair.secret_key.0,
+        g: G1Projective::rand(&mut rng),
+        h: G1Projective::rand(&mut rng),
+        k: G1Projective::rand(&mut rng),
+        z: G1Projective::rand(&mut rng),
+    };
+    let mut acc = ECAccumulator::new(&keys.g1);
+    revocation_flow(&mut acc, &keypair.secret_key, &keys);
+
+    // every check is a LegoGroth16 proof; non_membership_legogroth16 covers DEFAULT_DEPTH
+    let mut tree = SparseMerkleTree::new(16);
+    let params = MerkleZkParams::setup(tree.params(), &mut rng);
+    revocation_flow(&mut tree, &(), &params);
+}
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::keystore::KdfParams;
+use accumulator_impl::presentation::*;
+use accumulator_impl::replay::VerifierChallenge;
+use accumulator_impl::schema::*;
+use accumulator_impl::wallet::Wallet;
+
+const KDF: KdfParams = KdfParams { m_cost: 64, t_cost: 1, p_cost: 1 };
+
+fn values(name: &str, score: u64) -> BTreeMap<String, AttributeValue> {
+    BTreeMap::from([
+        ("name".to_string(), AttributeValue::String(name.to_string())),
+        ("dob".to_string(), AttributeValue::Date("2004-11-15".to_string())),
+        ("age".to_string(), AttributeValue::Integer(21)),
+        ("score".to_string(), AttributeValue::Integer(score)),
+        ("candidate_id".to_string(), AttributeValue::Integer(245)),
+    ])
+}
+
+fn wallet_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("presentation_{}_{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn request(schema: &CredentialSchema, issuer: &Issuer) -> PresentationRequest {
+    PresentationRequest {
+        issuers: vec![issuer.public_keys().fingerprint()],
+        schemas: vec![schema.id()],
+        reveal: vec!["name".to_string()],
+        predicates: vec![
+            Predicate { attribute: "score".to_string(), min: Some(60), max: None },
+            Predicate { attribute: "dob".to_string(), min: None, max: parse_date("2008-01-01") },
+        ],
+        ..PresentationRequest::new(&VerifierChallenge::new("https://verifier.example"))
+    }
+}
+
+#[test]
+fn present_from_wallet_and_validate() {
+    let schema = exam_candidate_schema();
+    let issuer = Issuer::for_schema(&schema);
+    let keys = issuer.public_keys();
+    let acc = ECAccumulator::new(issuer.get_g1());
+
+    let mut wallet = Wallet::<ECAccumulator>::create_with_kdf(wallet_path("wallet"), "pass", KDF).unwrap();
+    // the first credential fails the score predicate, the second answers
+    wallet.add(issuer.issue_with_schema(&acc, &schema, &values("Bob", 40)).unwrap(), &keys, Some(&schema)).unwrap();
+    wallet.add(issuer.issue_with_schema(&acc, &schema, &values("Alice", 75)).unwrap(), &keys, Some(&schema)).unwrap();
+
+    let mut request = request(&schema, &issuer);
+    request.epoch = Some(acc.get_epoch());
+    let state = AccumulatorState::from(&acc);
+    let presentation = Presentation::from_wallet(&wallet, &request, &keys, &schema, &state).unwrap();
+    assert!(presentation.reveals(&schema, "name", &AttributeValue::String("Alice".to_string())));
+    assert_eq!(presentation.revealed.len(), 1);
+    assert_eq!(request.validate(&presentation, &keys, &schema, &state), Ok(()));
+
+    // encodings round-trip, both for the request and the answer
+    let request = PresentationRequest::from_json(&request.to_json()).unwrap();
+    assert_eq!(PresentationRequest::from_cbor(&request.to_cbor()).unwrap(), request);
+    let decoded = Presentation::from_cbor(&presentation.to_cbor()).unwrap();
+    assert_eq!(Presentation::from_json(&decoded.to_json()).unwrap(), presentation);
+    assert_eq!(request.validate(&decoded, &keys, &schema, &state), Ok(()));
+
+    // nobody qualifies under a stricter predicate
+    let mut strict = request.clone();
+    strict.predicates[0].min = Some(90);
+    assert_eq!(
+        Presentation::from_wallet(&wallet, &strict, &keys, &schema, &state).err(),
+        Some(PresentationError::PredicateNotSatisfied("score".to_string()))
+    );
+}
+
+#[test]
+fn presentation_must_meet_its_request() {
+    let schema = exam_candidate_schema();
+    let issuer = Issuer::for_schema(&schema);
+    let keys = issuer.public_keys();
+    let acc = ECAccumulator::new(issuer.get_g1());
+    let cred = issuer.issue_with_schema(&acc, &schema, &values("Alice", 75)).unwrap();
+    let state = AccumulatorState::from(&acc);
+
+    let request = request(&schema, &issuer);
+    let presentation = Presentation::create(&request, &cred, &keys, &schema, &state).unwrap();
+    assert_eq!(request.validate(&presentation, &keys, &schema, &state), Ok(()));
+
+    // a replay to another session or another verifier
+    let other = PresentationRequest { nonce: VerifierChallenge::new("").nonce, ..request.clone() };
+    assert_eq!(other.validate(&presentation, &keys, &schema, &state), Err(PresentationError::NonceMismatch));
+    let mut forged = presentation.clone();
+    forged.nonce = other.nonce.clone();
+    assert_eq!(other.validate(&forged, &keys, &schema, &state), Err(PresentationError::InvalidProof));
+    let elsewhere = PresentationRequest { verifier: "https://elsewhere.example".to_string(), ..request.clone() };
+    assert_eq!(elsewhere.validate(&presentation, &keys, &schema, &state), Err(PresentationError::WrongVerifier));
+    let mut forged = presentation.clone();
+    forged.verifier = elsewhere.verifier.clone();
+    assert_eq!(elsewhere.validate(&forged, &keys, &schema, &state), Err(PresentationError::InvalidProof));
+
+    // changed disclosures and predicates are caught
+    let mut more = request.clone();
+    more.reveal.push("age".to_string());
+    assert_eq!(more.validate(&presentation, &keys, &schema, &state), Err(PresentationError::RevealMismatch));
+    let mut forged = presentation.clone();
+    forged.revealed.insert("name".to_string(), presentation.revealed["name"].replace('0', "1"));
+    assert!(request.validate(&forged, &keys, &schema, &state).is_err());
+    let mut looser = request.clone();
+    looser.predicates[0].min = Some(50);
+    assert_eq!(looser.validate(&presentation, &keys, &schema, &state), Err(PresentationError::PredicateMismatch));
+    let mut forged = presentation.clone();
+    forged.predicates = looser.predicates.clone();
+    assert_eq!(looser.validate(&forged, &keys, &schema, &state), Err(PresentationError::InvalidProof));
+
+    // another issuer or schema
+    let stranger = Issuer::for_schema(&schema);
+    assert_eq!(
+        request.validate(&presentation, &stranger.public_keys(), &schema, &state),
+        Err(PresentationError::WrongIssuer)
+    );
+    let mut any_issuer = request.clone();
+    any_issuer.issuers.clear();
+    any_issuer.schemas = vec![schema.clone().with_validity().unwrap().id()];
+    assert_eq!(any_issuer.validate(&presentation, &keys, &schema, &state), Err(PresentationError::SchemaNotAccepted));
+    let mut strings_only = request.clone();
+    strings_only.predicates = vec![Predicate { attribute: "name".to_string(), min: Some(1), max: None }];
+    assert_eq!(
+        Presentation::create(&strings_only, &cred, &keys, &schema, &state).err(),
+        Some(PresentationError::NotComparable("name".to_string()))
+    );
+}
+
+#[test]
+fn epochs_revocation_and_pseudonyms() {
+    let schema = exam_candidate_schema();
+    let issuer = Issuer::for_schema(&schema);
+    let keys = issuer.public_keys();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let alice = issuer.issue_with_schema(&acc, &schema, &values("Alice", 75)).unwrap();
+    let mut bob = issuer.issue_with_schema(&acc, &schema, &values("Bob", 80)).unwrap();
+    let old = AccumulatorState::from(&acc);
+
+    let update = issuer.revoke_a_cred(&mut acc, &alice).unwrap();
+    let new = AccumulatorState::from(&acc);
+    assert_eq!(new.epoch, old.epoch + 1);
+    assert_eq!(
+        Presentation::create(&request(&schema, &issuer), &alice, &keys, &schema, &new).err(),
+        Some(PresentationError::Revoked)
+    );
+
+    // the verifier wants the latest epoch; an answer at the old one is refused
+    let mut request = request(&schema, &issuer);
+    request.epoch = Some(new.epoch);
+    assert_eq!(
+        Presentation::create(&request, &bob, &keys, &schema, &old).err(),
+        Some(PresentationError::EpochMismatch { required: new.epoch, got: old.epoch })
+    );
+    request.epoch = None;
+    let stale = Presentation::create(&request, &bob, &keys, &schema, &old).unwrap();
+    request.epoch = Some(new.epoch);
+    assert_eq!(
+        request.validate(&stale, &keys, &schema, &new),
+        Err(PresentationError::EpochMismatch { required: new.epoch, got: old.epoch })
+    );
+    bob.update_witness(update.delta, update.new_alpha);
+    let fresh = Presentation::create(&request, &bob, &keys, &schema, &new).unwrap();
+    assert_eq!(request.validate(&fresh, &keys, &schema, &new), Ok(()));
+
+    // same nym within a scope, different across scopes
+    request.scope = Some("exam-board-2026".to_string());
+    let first = Presentation::create(&request, &bob, &keys, &schema, &new).unwrap();
+    let second = Presentation::create(&request, &bob, &keys, &schema, &new).unwrap();
+    assert_eq!(request.validate(&first, &keys, &schema, &new), Ok(()));
+    assert_eq!(first.pseudonym, second.pseudonym);
+    assert!(first.pseudonym.is_some());
+    let mut other_scope = request.clone();
+    other_scope.scope = Some("library".to_string());
+    let third = Presentation::create(&other_scope, &bob, &keys, &schema, &new).unwrap();
+    assert_ne!(first.pseudonym, third.pseudonym);
+    assert_eq!(other_scope.validate(&first, &keys, &schema, &new), Err(PresentationError::PseudonymMismatch));
+    assert_eq!(request.validate(&fresh, &keys, &schema, &new), Err(PresentationError::PseudonymMismatch));
+
+    // the nym is proven on the signed x: another credential's nym is refused
+    let carol = issuer.issue_with_schema(&acc, &schema, &values("Carol", 90)).unwrap();
+    let theirs = Presentation::create(&request, &carol, &keys, &schema, &new).unwrap();
+    assert_ne!(theirs.pseudonym, first.pseudonym);
+    let mut forged = first.clone();
+    forged.pseudonym = theirs.pseudonym.clone();
+    assert_eq!(request.validate(&forged, &keys, &schema, &new), Err(PresentationError::PseudonymMismatch));
+}
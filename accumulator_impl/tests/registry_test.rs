@@ -0,0 +1,133 @@
+use ark_bls12_381::Fr;
+use ark_std::UniformRand;
+use rand::thread_rng;
+use std::collections::BTreeMap;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::helper::verify_witness;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::registry::*;
+use accumulator_impl::revocation::merkle::{SparseMerkleTree, DEFAULT_DEPTH};
+use accumulator_impl::revocation::RevocationScheme;
+
+fn messages() -> Vec<Fr> {
+    let mut rng = thread_rng();
+    (0..5).map(|_| Fr::rand(&mut rng)).collect()
+}
+
+fn metadata(candidate_id: &str, exam: &str) -> BTreeMap<String, String> {
+    BTreeMap::from([
+        ("candidate_id".to_string(), candidate_id.to_string()),
+        ("exam".to_string(), exam.to_string()),
+    ])
+}
+
+fn registry_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("cred_registry_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn revoke_by_id_and_by_attribute() {
+    let issuer = Issuer::new();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let registry = CredentialRegistry::open(registry_dir("revoke")).unwrap();
+
+    let (alice_id, alice) = registry.issue(&issuer, &acc, messages(), metadata("C-001", "math")).unwrap();
+    let (_, mut bob) = registry.issue(&issuer, &acc, messages(), metadata("C-002", "math")).unwrap();
+    let (_, carol) = registry.issue(&issuer, &acc, messages(), metadata("C-002", "physics")).unwrap();
+    assert_eq!(registry.len(), 3);
+    assert_eq!(registry.get(&alice_id).unwrap().unwrap().get_handle().unwrap(), *alice.get_x_val());
+
+    // revoking needs the record only, not alice's credential
+    let update = registry.revoke(&issuer, &mut acc, &alice_id).unwrap();
+    assert_eq!(update.delta, *alice.get_x_val());
+    assert!(registry.revoke(&issuer, &mut acc, &alice_id).is_err());
+    assert!(registry.revoke(&issuer, &mut acc, "cred-99999999").is_err());
+    assert_eq!(registry.get(&alice_id).unwrap().unwrap().status, CredentialStatus::Revoked);
+    bob.update_witness(update.delta, update.new_alpha);
+
+    // both of candidate C-002's credentials at once
+    assert_eq!(registry.find_by_attribute("candidate_id", "C-002").unwrap().len(), 2);
+    let updates = registry.revoke_by_attribute(&issuer, &mut acc, "candidate_id", "C-002").unwrap();
+    assert_eq!(updates.len(), 2);
+    assert_eq!(acc.get_deltas(), &vec![*alice.get_x_val(), *bob.get_x_val(), *carol.get_x_val()]);
+    assert!(registry.revoke_by_attribute(&issuer, &mut acc, "candidate_id", "C-002").unwrap().is_empty());
+
+    // reinstate alice; her witness is the alpha before re-admission
+    let old_alpha = registry.reinstate(&issuer, &mut acc, &alice_id).unwrap();
+    assert!(verify_witness(acc.get_alpha(), alice.get_x_val(), &old_alpha, issuer.get_g2(), issuer.get_j()));
+    assert_eq!(registry.get(&alice_id).unwrap().unwrap().status, CredentialStatus::Active);
+}
+
+#[test]
+fn records_and_audit_trail_persist() {
+    let dir = registry_dir("persist");
+    let issuer = Issuer::new();
+    let mut tree = SparseMerkleTree::new(DEFAULT_DEPTH);
+    let id = {
+        let registry = CredentialRegistry::open(&dir).unwrap();
+        let (id, _) = registry.issue(&issuer, &tree, messages(), metadata("C-007", "math")).unwrap();
+        registry.revoke(&issuer, &mut tree, &id).unwrap();
+        id
+    };
+
+    let registry = CredentialRegistry::open(&dir).unwrap();
+    let record = registry.get(&id).unwrap().unwrap();
+    assert_eq!(record.status, CredentialStatus::Revoked);
+    assert_eq!(record.key_id, issuer.get_key_id());
+    assert!(tree.gen_witness(&record.get_handle().unwrap(), &()).is_none());
+
+    let log = registry.audit_log().unwrap();
+    let actions: Vec<AuditAction> = log.iter().map(|e| e.action).collect();
+    assert_eq!(actions, vec![AuditAction::Issued, AuditAction::Revoked]);
+    assert!(log.iter().all(|e| e.credential_id == id));
+    assert_eq!(log[1].prev, log[0].hash());
+    assert!(registry.verify_audit_log().unwrap());
+
+    // ids keep counting after a reopen
+    let (next, _) = registry.issue(&issuer, &tree, messages(), BTreeMap::new()).unwrap();
+    assert_ne!(next, id);
+}
+
+#[test]
+fn credentials_of_another_key_version_are_refused() {
+    let old_issuer = Issuer::with_key_id(1);
+    let new_issuer = Issuer::with_key_id(2);
+    let mut acc = ECAccumulator::new(old_issuer.get_g1());
+    let registry = CredentialRegistry::open(registry_dir("key_version")).unwrap();
+
+    let (id, _) = registry.issue(&old_issuer, &acc, messages(), BTreeMap::new()).unwrap();
+    assert!(registry.revoke(&new_issuer, &mut acc, &id).is_err());
+    assert!(acc.get_deltas().is_empty());
+    assert!(registry.revoke(&old_issuer, &mut acc, &id).is_ok());
+}
+
+#[test]
+fn a_failed_batch_revokes_nothing() {
+    let old_issuer = Issuer::with_key_id(1);
+    let new_issuer = Issuer::with_key_id(2);
+    let mut acc = ECAccumulator::new(new_issuer.get_g1());
+    let registry = CredentialRegistry::open(registry_dir("batch")).unwrap();
+
+    // the second match was issued under the old key and stops the batch
+    let (first, _) = registry.issue(&new_issuer, &acc, messages(), metadata("C-003", "math")).unwrap();
+    registry.issue(&old_issuer, &acc, messages(), metadata("C-003", "physics")).unwrap();
+    let (alpha, epoch, entries) = (*acc.get_alpha(), acc.get_epoch(), registry.audit_log().unwrap().len());
+
+    assert!(registry.revoke_by_attribute(&new_issuer, &mut acc, "candidate_id", "C-003").is_err());
+    assert_eq!((*acc.get_alpha(), acc.get_epoch()), (alpha, epoch));
+    assert_eq!(registry.get(&first).unwrap().unwrap().status, CredentialStatus::Active);
+    assert_eq!(registry.audit_log().unwrap().len(), entries);
+
+    // a prepared batch changes nothing until it is committed
+    let pending = registry.prepare_revoke(&new_issuer, &acc, &first).unwrap();
+    assert_eq!(acc.get_epoch(), epoch);
+    assert_eq!(registry.get(&first).unwrap().unwrap().status, CredentialStatus::Active);
+    let revoked = registry.commit(pending, &mut acc).unwrap();
+    assert_eq!(revoked[0].0, first);
+    assert_eq!(acc.get_epoch(), epoch + 1);
+    assert_eq!(registry.get(&first).unwrap().unwrap().status, CredentialStatus::Revoked);
+    assert!(registry.verify_audit_log().unwrap());
+}
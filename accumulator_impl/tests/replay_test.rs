@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::holder::Holder;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::presentation::*;
+use accumulator_impl::replay::*;
+use accumulator_impl::schema::*;
+use accumulator_impl::transcript::*;
+use accumulator_impl::zkp::verify_zkp;
+
+const VERIFIER: &str = "https://verifier.example";
+const NOW: u64 = 1_767_225_600;
+
+fn values() -> BTreeMap<String, AttributeValue> {
+    BTreeMap::from([
+        ("name".to_string(), AttributeValue::String("Alice".to_string())),
+        ("dob".to_string(), AttributeValue::Date("2004-11-15".to_string())),
+        ("age".to_string(), AttributeValue::Integer(21)),
+        ("score".to_string(), AttributeValue::Integer(75)),
+        ("candidate_id".to_string(), AttributeValue::Integer(245)),
+    ])
+}
+
+#[test]
+fn cache_accepts_each_challenge_once() {
+    let mut cache = ReplayCache::new(VERIFIER, 300);
+    let first = cache.issue(NOW);
+    let second = cache.issue(NOW);
+    assert_eq!(first.verifier, VERIFIER);
+    assert_ne!(first.nonce, second.nonce);
+    assert_ne!(first.to_bytes(), second.to_bytes());
+    assert_eq!(cache.pending(), 2);
+
+    assert_eq!(cache.redeem(&first, NOW + 10), Ok(()));
+    assert_eq!(cache.redeem(&first, NOW + 20), Err(ReplayError::Replayed));
+    assert_eq!(cache.pending(), 1);
+
+    // only challenges issued here, for this verifier, within their lifetime
+    assert_eq!(cache.redeem(&VerifierChallenge::new(VERIFIER), NOW), Err(ReplayError::UnknownNonce));
+    let foreign = VerifierChallenge { verifier: "https://elsewhere.example".to_string(), ..second.clone() };
+    assert_eq!(cache.redeem(&foreign, NOW), Err(ReplayError::WrongVerifier));
+    assert_eq!(cache.redeem(&second, NOW + 301), Err(ReplayError::Expired));
+    assert_eq!(cache.redeem(&second, NOW + 302), Err(ReplayError::UnknownNonce));
+
+    // redeemed nonces are forgotten once expired, and stay rejected
+    cache.prune(NOW + 301);
+    assert_eq!(cache.pending(), 0);
+    assert_eq!(cache.redeem(&first, NOW + 301), Err(ReplayError::UnknownNonce));
+}
+
+#[test]
+fn presentations_are_not_replayable() {
+    let schema = exam_candidate_schema();
+    let issuer = Issuer::for_schema(&schema);
+    let keys = issuer.public_keys();
+    let acc = ECAccumulator::new(issuer.get_g1());
+    let cred = issuer.issue_with_schema(&acc, &schema, &values()).unwrap();
+    let state = AccumulatorState::from(&acc);
+
+    let mut cache = ReplayCache::new(VERIFIER, 300);
+    let request = PresentationRequest {
+        reveal: vec!["name".to_string()],
+        predicates: vec![Predicate { attribute: "score".to_string(), min: Some(60), max: None }],
+        ..PresentationRequest::new(&cache.issue(NOW))
+    };
+    let presentation = Presentation::create(&request, &cred, &keys, &schema, &state).unwrap();
+    assert_eq!(presentation.challenge(), request.challenge());
+    assert_eq!(request.validate_once(&presentation, &keys, &schema, &state, &mut cache, NOW + 1), Ok(()));
+    assert_eq!(
+        request.validate_once(&presentation, &keys, &schema, &state, &mut cache, NOW + 2),
+        Err(PresentationError::Replay(ReplayError::Replayed))
+    );
+
+    // an invalid answer does not use up the challenge
+    let request = PresentationRequest { reveal: vec!["name".to_string()], ..PresentationRequest::new(&cache.issue(NOW)) };
+    let presentation = Presentation::create(&request, &cred, &keys, &schema, &state).unwrap();
+    let mut forged = presentation.clone();
+    forged.revealed.insert("name".to_string(), presentation.revealed["name"].replace('0', "1"));
+    assert!(request.validate_once(&forged, &keys, &schema, &state, &mut cache, NOW + 1).is_err());
+    assert_eq!(cache.pending(), 1);
+    assert_eq!(
+        request.validate_once(&presentation, &keys, &schema, &state, &mut cache, NOW + 301),
+        Err(PresentationError::Replay(ReplayError::Expired))
+    );
+}
+
+#[test]
+fn membership_proof_is_bound_to_the_challenge() {
+    let schema = exam_candidate_schema();
+    let issuer = Issuer::for_schema(&schema);
+    let keys = issuer.public_keys();
+    let acc = ECAccumulator::new(issuer.get_g1());
+    let holder = Holder::new(issuer.issue_with_schema(&acc, &schema, &values()).unwrap());
+
+    let challenge = VerifierChallenge::new(VERIFIER);
+    let (cx, proof) = holder.prove_membership(&keys, &acc, &challenge);
+    let verify = |challenge: &VerifierChallenge| {
+        let mut transcript = session_transcript(MEMBERSHIP_PROOF, &keys, acc.get_epoch(), challenge);
+        verify_zkp(&keys, acc.get_alpha(), &cx, &proof, &mut transcript)
+    };
+    assert!(verify(&challenge));
+    assert!(!verify(&VerifierChallenge::new(VERIFIER)));
+    assert!(!verify(&VerifierChallenge { verifier: "https://elsewhere.example".to_string(), ..challenge }));
+}
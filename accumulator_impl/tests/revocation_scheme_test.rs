@@ -0,0 +1,85 @@
+use ark_bls12_381::Fr;
+use ark_std::UniformRand;
+use rand::thread_rng;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::holder::Holder;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::revocation::merkle::{MerkleZkParams, SparseMerkleTree};
+use accumulator_impl::revocation::{RevocationError, RevocationScheme};
+use accumulator_impl::transcript::{new_transcript, MEMBERSHIP_PROOF};
+
+fn messages() -> Vec<Fr> {
+    let mut rng = thread_rng();
+    (0..5).map(|_| Fr::rand(&mut rng)).collect()
+}
+
+// Issue three credentials, revoke one, the others follow the published update
+fn issue_and_revoke<S: RevocationScheme>(issuer: &Issuer, scheme: &mut S, params: &S::VerifyParams) {
+    let (pk, sig_param) = (issuer.get_pk(), issuer.get_sig_param());
+
+    let alice = Holder::<S>::from_cred(issuer.gen_witness_n_cred(scheme, messages()));
+    let mut bob = Holder::<S>::from_cred(issuer.gen_witness_n_cred(scheme, messages()));
+    let mut carol = Holder::<S>::from_cred(issuer.gen_witness_n_cred(scheme, messages()));
+    assert!(alice.verify_cred(pk, sig_param, &scheme.public_state(), params));
+
+    let update = issuer.revoke_a_cred(scheme, alice.get_cred()).unwrap();
+    assert!(matches!(issuer.revoke_a_cred(scheme, alice.get_cred()), Err(RevocationError::AlreadyRevoked)));
+    let state = scheme.public_state();
+    assert!(!alice.verify_cred(pk, sig_param, &state, params));
+    assert!(!bob.verify_cred(pk, sig_param, &state, params));
+
+    assert!(bob.update_witness(params, &update));
+    assert!(carol.update_witness(params, &update));
+    assert!(bob.verify_cred(pk, sig_param, &state, params));
+    assert!(carol.verify_cred(pk, sig_param, &state, params));
+}
+
+#[test]
+fn issuer_and_holder_with_accumulator() {
+    let issuer = Issuer::new();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    issue_and_revoke(&issuer, &mut acc, &issuer.public_keys());
+}
+
+#[test]
+fn issuer_and_holder_with_merkle_tree() {
+    let issuer = Issuer::new();
+    // shallow: every check is a LegoGroth16 proof
+    let mut tree = SparseMerkleTree::new(16);
+    let params = MerkleZkParams::setup(tree.params(), &mut thread_rng());
+    issue_and_revoke(&issuer, &mut tree, &params);
+}
+
+#[test]
+fn default_holder_is_the_accumulator_one() {
+    let issuer = Issuer::new();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let alice = Holder::new(issuer.gen_witness_n_cred(&acc, messages()));
+    let mut bob = Holder::new(issuer.gen_witness_n_cred(&acc, messages()));
+
+    let update = issuer.revoke_a_cred(&mut acc, alice.get_cred()).unwrap();
+    assert_eq!(update.delta, *alice.get_cred().get_x_val());
+    assert!(bob.update_witness(&issuer.public_keys(), &update));
+    assert!(bob.verify_mem(issuer.get_pk(), issuer.get_sig_param(), acc.get_alpha(), issuer.get_g2(), issuer.get_j()));
+}
+
+#[test]
+fn accumulator_proofs_hide_the_witness() {
+    let issuer = Issuer::new();
+    let keys = issuer.public_keys();
+    let acc = ECAccumulator::new(issuer.get_g1());
+    let cred = issuer.gen_witness_n_cred(&acc, messages());
+    let (x, witness) = (cred.get_x_val(), cred.get_witness());
+
+    let transcript = new_transcript(MEMBERSHIP_PROOF);
+    let first = ECAccumulator::prove(&keys, acc.get_alpha(), x, witness, &mut transcript.clone()).unwrap();
+    let second = ECAccumulator::prove(&keys, acc.get_alpha(), x, witness, &mut transcript.clone()).unwrap();
+    assert!(ECAccumulator::verify(&keys, acc.get_alpha(), &first, &mut transcript.clone()));
+    assert!(ECAccumulator::verify(&keys, acc.get_alpha(), &second, &mut transcript.clone()));
+
+    // neither the witness nor a common commitment shows up in the proofs
+    assert_ne!(first.1.c_w, *witness);
+    assert_ne!(first.0, second.0);
+    assert_ne!(first.1.c_w, second.1.c_w);
+}
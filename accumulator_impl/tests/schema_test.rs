@@ -0,0 +1,117 @@
+use ark_bls12_381::Fr;
+use std::collections::BTreeMap;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::schema::*;
+
+fn values(name: &str, dob: &str) -> BTreeMap<String, AttributeValue> {
+    BTreeMap::from([
+        ("name".to_string(), AttributeValue::String(name.to_string())),
+        ("dob".to_string(), AttributeValue::Date(dob.to_string())),
+        ("age".to_string(), AttributeValue::Integer(21)),
+        ("score".to_string(), AttributeValue::Integer(75)),
+        ("candidate_id".to_string(), AttributeValue::Integer(245)),
+    ])
+}
+
+#[test]
+fn day_numbers() {
+    assert_eq!(date_to_day_number(1, 1, 1), Some(1));
+    assert_eq!(parse_date("1970-01-01"), Some(719_163));
+    assert_eq!(parse_date("2004-11-15"), Some(731_900));
+    assert_eq!(parse_date("2000-03-01").unwrap() - parse_date("2000-02-28").unwrap(), 2);
+    assert_eq!(parse_date("1900-03-01").unwrap() - parse_date("1900-02-28").unwrap(), 1);
+    for bad in ["2001-02-29", "2004-13-01", "2004-00-10", "15112004", "2004-1-15"] {
+        assert_eq!(parse_date(bad), None, "{}", bad);
+    }
+}
+
+#[test]
+fn encoding_follows_the_schema() {
+    let schema = exam_candidate_schema();
+    assert_eq!(schema.message_count(), 7);
+    assert_eq!(schema.message_index("dob"), Some(2));
+
+    let messages = schema.encode(&values("Alice", "2004-11-15")).unwrap();
+    assert_eq!(messages[0], schema.id_fr());
+    assert_eq!(messages[1], encode_string("Alice"));
+    assert_eq!(messages[2], Fr::from(731_900u64));
+    assert_eq!(messages[5], Fr::from(245u64));
+
+    let mut missing = values("Alice", "2004-11-15");
+    missing.remove("age");
+    assert_eq!(schema.encode(&missing), Err(SchemaError::MissingAttribute("age".to_string())));
+    let mut unknown = values("Alice", "2004-11-15");
+    unknown.insert("height".to_string(), AttributeValue::Integer(180));
+    assert_eq!(schema.encode(&unknown), Err(SchemaError::UnknownAttribute("height".to_string())));
+    assert!(matches!(schema.encode(&values("Alice", "2004-02-30")), Err(SchemaError::InvalidDate { .. })));
+    assert!(matches!(
+        schema.encode_value("age", &AttributeValue::String("21".to_string())),
+        Err(SchemaError::TypeMismatch { .. })
+    ));
+
+    let attr = |name: &str, kind| AttributeDef { name: name.to_string(), kind };
+    let grades = vec!["fail".to_string(), "pass".to_string(), "merit".to_string()];
+    let schema = CredentialSchema::new("grade", "1", vec![attr("grade", AttributeType::Enum { variants: grades })]).unwrap();
+    assert_eq!(schema.encode_value("grade", &AttributeValue::Enum("merit".to_string())), Ok(Fr::from(2u64)));
+    assert!(schema.encode_value("grade", &AttributeValue::Enum("distinction".to_string())).is_err());
+    assert_eq!(
+        CredentialSchema::new("dup", "1", vec![attr("a", AttributeType::Integer), attr("a", AttributeType::Date)]),
+        Err(SchemaError::DuplicateAttribute("a".to_string()))
+    );
+}
+
+#[test]
+fn schema_json_and_id() {
+    let schema = exam_candidate_schema();
+    let parsed = CredentialSchema::from_json(&schema.to_json()).unwrap();
+    assert_eq!(parsed, schema);
+    assert_eq!(parsed.id(), schema.id());
+
+    let json = r#"{"name":"exam-candidate","version":"2","attributes":[{"name":"name","type":"string"}]}"#;
+    let other = CredentialSchema::from_json(json).unwrap();
+    assert_eq!(other.attributes[0].kind, AttributeType::String);
+    assert_ne!(other.id(), schema.id());
+}
+
+#[test]
+fn values_from_json() {
+    let schema = exam_candidate_schema();
+    let json = r#"{"name": "Alice", "dob": "2004-11-15", "age": 21, "score": 75, "candidate_id": 245}"#;
+    assert_eq!(schema.values_from_json(json).unwrap(), values("Alice", "2004-11-15"));
+
+    assert_eq!(
+        schema.value_from_json("age", &serde_json::json!("21")),
+        Err(SchemaError::TypeMismatch { attribute: "age".to_string(), expected: AttributeType::Integer })
+    );
+    assert_eq!(schema.value_from_json("height", &serde_json::json!(180)), Err(SchemaError::UnknownAttribute("height".to_string())));
+    assert!(schema.values_from_json(r#"{"name": 7}"#).is_err());
+    assert!(schema.values_from_json("[]").is_err());
+}
+
+#[test]
+fn schema_id_is_signed() {
+    let schema = exam_candidate_schema();
+    let issuer = Issuer::for_schema(&schema);
+    let acc = ECAccumulator::new(issuer.get_g1());
+    let cred = issuer.issue_with_schema(&acc, &schema, &values("Alice", "2004-11-15")).unwrap();
+
+    let mut signed = cred.signed_messages().expose().clone();
+    assert_eq!(schema.check_messages(&signed), Ok(()));
+    assert!(cred.get_signature().verify(&signed, issuer.get_pk().clone(), issuer.get_sig_param().clone()).is_ok());
+
+    // the same attributes under another schema id do not verify
+    let mut v2 = schema.clone();
+    v2.version = "2".to_string();
+    signed[0] = v2.id_fr();
+    assert_eq!(v2.check_messages(&signed), Ok(()));
+    assert!(cred.get_signature().verify(&signed, issuer.get_pk().clone(), issuer.get_sig_param().clone()).is_err());
+
+    // keys sized for the legacy 6-message layout are refused
+    let legacy = Issuer::new();
+    assert_eq!(
+        legacy.issue_with_schema(&acc, &schema, &values("Alice", "2004-11-15")).err(),
+        Some(SchemaError::MessageCountMismatch { expected: 7, got: 6 })
+    );
+}
@@ -0,0 +1,75 @@
+use ark_bls12_381::Fr;
+use ark_ff::Zero;
+use ark_std::UniformRand;
+use rand::thread_rng;
+use std::cell::Cell;
+use std::rc::Rc;
+use zeroize::Zeroize;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::secret::Secret;
+
+fn messages() -> Vec<Fr> {
+    let mut rng = thread_rng();
+    (0..5).map(|_| Fr::rand(&mut rng)).collect()
+}
+
+// records that it was wiped
+struct Tracked(Rc<Cell<bool>>);
+
+impl Zeroize for Tracked {
+    fn zeroize(&mut self) {
+        self.0.set(true);
+    }
+}
+
+#[test]
+fn secrets_are_wiped_on_drop() {
+    let wiped = Rc::new(Cell::new(false));
+    drop(Secret::new(Tracked(wiped.clone())));
+    assert!(wiped.get());
+
+    // drop runs the same wipe, which clears the bytes in place
+    let mut bytes = Secret::new([7u8; 32]);
+    bytes.zeroize();
+    assert!(bytes.expose().iter().all(|b| *b == 0));
+}
+
+#[test]
+fn redacted_debug_and_constant_time_eq() {
+    let x = Fr::from(424_242u64);
+    let secret = Secret::new(x);
+    assert_eq!(format!("{:?}", secret), "Secret([REDACTED])");
+    assert!(!format!("{:?}", secret).contains("424242"));
+
+    assert_eq!(secret, Secret::new(x));
+    assert_ne!(secret, Secret::new(x + Fr::from(1u64)));
+
+    // clones are explicit and independent
+    let mut original = Secret::new(x);
+    let copy = original.clone_secret();
+    original.zeroize();
+    assert!(original.expose().is_zero());
+    assert_eq!(*copy.expose(), x);
+}
+
+#[test]
+fn credentials_hold_their_secrets_wrapped() {
+    let issuer = Issuer::new();
+    let acc = ECAccumulator::new(issuer.get_g1());
+    let mut cred = issuer.gen_witness_n_cred(&acc, messages());
+    let x = *cred.get_x_val();
+
+    let debug = format!("{:?}", cred);
+    assert!(debug.contains("[REDACTED]"));
+    assert!(!debug.contains(&format!("{}", x)));
+
+    let copy = cred.clone_secret();
+    cred.destructor();
+    assert!(cred.get_x_val().is_zero());
+    assert!(cred.get_r().is_zero());
+    assert!(cred.get_witness().is_zero());
+    assert!(cred.get_message().iter().all(|m| m.is_zero()));
+    assert_eq!(*copy.get_x_val(), x);
+}
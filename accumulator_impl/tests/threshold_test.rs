@@ -0,0 +1,117 @@
+use ark_bls12_381::{Fr, G2Projective};
+use ark_ff::Field;
+use ark_std::UniformRand;
+use rand::thread_rng;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::holder::Holder;
+use accumulator_impl::threshold::*;
+
+fn messages() -> Vec<Fr> {
+    let mut rng = thread_rng();
+    (0..5).map(|_| Fr::rand(&mut rng)).collect()
+}
+
+fn is_valid(issuer: &ThresholdIssuer, acc: &ECAccumulator, holder: &Holder) -> bool {
+    holder.verify_mem(issuer.get_pk(), issuer.get_sig_param(), acc.get_alpha(), issuer.get_g2(), issuer.get_j())
+}
+
+#[test]
+fn dkg_shares_reconstruct_the_public_key() {
+    let mut rng = thread_rng();
+    let g2 = G2Projective::rand(&mut rng);
+    let ids = [1u64, 2, 3, 4, 5];
+    let dealings: Vec<Dealing> = ids.iter().map(|id| deal(*id, 3, &ids, &g2, &mut rng)).collect();
+    let pk = joint_public_key(&dealings);
+
+    // any 3 verification keys interpolate to the joint key
+    let parties: Vec<ThresholdParty> = ids.iter().map(|id| ThresholdParty::from_dealings(*id, &dealings, &g2).unwrap()).collect();
+    for subset in [[1u64, 2, 3], [2, 4, 5], [1, 3, 5]] {
+        let lambdas = lagrange_coefficients(&subset);
+        let combined: G2Projective = subset.iter().zip(lambdas).map(|(id, l)| *parties[*id as usize - 1].get_verification_key() * l).sum();
+        assert_eq!(combined, pk);
+    }
+
+    // a dealer sending a share off its polynomial is caught by the receiver
+    let mut bad = dealings.clone();
+    *bad[1].shares.get_mut(&4).unwrap() += Fr::from(1u64);
+    assert_eq!(
+        ThresholdParty::from_dealings(4, &bad, &g2).err(),
+        Some(ThresholdError::BadShare { dealer: 2, receiver: 4 })
+    );
+    assert!(ThresholdParty::from_dealings(3, &bad, &g2).is_ok());
+}
+
+#[test]
+fn threshold_issuing_and_revocation() {
+    // 2-of-4: inversions take 3 signers, any 3 will do
+    let issuer = ThresholdIssuer::new(2, 4).unwrap();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+
+    let alice = Holder::new(issuer.gen_witness_n_cred(&[1, 2, 3], &acc, messages()).unwrap());
+    let mut bob = Holder::new(issuer.gen_witness_n_cred(&[2, 3, 4], &acc, messages()).unwrap());
+    assert!(is_valid(&issuer, &acc, &alice));
+    assert!(is_valid(&issuer, &acc, &bob));
+
+    let update = issuer.revoke_a_cred(&[1, 3, 4], &mut acc, alice.get_cred()).unwrap();
+    assert_eq!(issuer.revoke_a_cred(&[1, 2, 4], &mut acc, alice.get_cred()).err(), Some(ThresholdError::AlreadyRevoked));
+    assert!(!is_valid(&issuer, &acc, &alice));
+    assert!(bob.update_witness(&issuer.public_keys(), &update));
+    assert!(is_valid(&issuer, &acc, &bob));
+
+    // reinstating is linear in sk, two signers are enough
+    let old_alpha = issuer.reinstate_a_cred(&[2, 4], &mut acc, alice.get_cred()).unwrap();
+    bob.update_witness_on_add(*alice.get_cred().get_x_val(), old_alpha);
+    assert!(is_valid(&issuer, &acc, &bob));
+    assert!(acc.get_deltas().is_empty());
+}
+
+#[test]
+fn too_few_or_unknown_signers_are_refused() {
+    assert_eq!(ThresholdIssuer::new(3, 4).err(), Some(ThresholdError::NotEnoughParties { needed: 5, got: 4 }));
+
+    let issuer = ThresholdIssuer::new(2, 3).unwrap();
+    let acc = ECAccumulator::new(issuer.get_g1());
+    assert_eq!(
+        issuer.gen_witness_n_cred(&[1, 2], &acc, messages()).err(),
+        Some(ThresholdError::NotEnoughParties { needed: 3, got: 2 })
+    );
+    assert_eq!(issuer.gen_witness_n_cred(&[1, 2, 7], &acc, messages()).err(), Some(ThresholdError::UnknownParty(7)));
+    assert_eq!(issuer.gen_witness_n_cred(&[1, 2, 2], &acc, messages()).err(), Some(ThresholdError::DuplicateParty(2)));
+
+    let signature = issuer.sign(&[3, 1, 2], &[Fr::from(7u64); 6]).unwrap();
+    assert!(signature.verify(&[Fr::from(7u64); 6], issuer.get_pk().clone(), issuer.get_sig_param().clone()).is_ok());
+}
+
+// Roots of the quadratic through (1, u[0]), (2, u[1]), (3, u[2])
+fn quadratic_roots(u: &[Fr]) -> Vec<Fr> {
+    let two = Fr::from(2u64);
+    let c2 = (u[0] - two * u[1] + u[2]) / two;
+    let c1 = u[1] - u[0] - Fr::from(3u64) * c2;
+    let c0 = u[0] - c1 - c2;
+    match (c1 * c1 - Fr::from(4u64) * c0 * c2).sqrt() {
+        Some(d) => vec![(-c1 + d) / (two * c2), (-c1 - d) / (two * c2)],
+        None => vec![],
+    }
+}
+
+#[test]
+fn opened_masks_do_not_factor() {
+    // with 2-of-3 the opened u_j pin a quadratic; were it r(X)(y + f(X)), one
+    // of its roots would satisfy f(X) = -y and two openings would give f
+    let issuer = ThresholdIssuer::new(2, 3).unwrap();
+    let signers = [1u64, 2, 3];
+    let mut rng = thread_rng();
+    let (y1, y2) = (Fr::rand(&mut rng), Fr::rand(&mut rng));
+    let (_, u1) = issuer.mask(&signers, &y1).unwrap();
+    let (_, u2) = issuer.mask(&signers, &y2).unwrap();
+
+    for x1 in quadratic_roots(&u1) {
+        for x2 in quadratic_roots(&u2) {
+            // f(X) = a + bX through (x1, -y1) and (x2, -y2)
+            let b = (y1 - y2) / (x2 - x1);
+            let a = -y1 - b * x1;
+            assert_ne!(*issuer.get_g2() * a, *issuer.get_j());
+        }
+    }
+}
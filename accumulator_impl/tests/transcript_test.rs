@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::holder::Holder;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::replay::VerifierChallenge;
+use accumulator_impl::schema::*;
+use accumulator_impl::transcript::*;
+use accumulator_impl::zkp::verify_zkp;
+
+fn values() -> BTreeMap<String, AttributeValue> {
+    BTreeMap::from([
+        ("name".to_string(), AttributeValue::String("Alice".to_string())),
+        ("dob".to_string(), AttributeValue::Date("2004-11-15".to_string())),
+        ("age".to_string(), AttributeValue::Integer(21)),
+        ("score".to_string(), AttributeValue::Integer(75)),
+        ("candidate_id".to_string(), AttributeValue::Integer(245)),
+    ])
+}
+
+#[test]
+fn transcripts_are_domain_separated() {
+    let schema = exam_candidate_schema();
+    let keys = Issuer::for_schema(&schema).public_keys();
+    let other = Issuer::for_schema(&schema).public_keys();
+    let challenge = VerifierChallenge::new("https://verifier.example");
+
+    let c = |mut transcript: merlin::Transcript| transcript.challenge_scalar(b"c");
+    let base = c(session_transcript(MEMBERSHIP_PROOF, &keys, 1, &challenge));
+    assert_eq!(base, c(session_transcript(MEMBERSHIP_PROOF, &keys, 1, &challenge)));
+    assert_ne!(base, c(session_transcript(b"presentation", &keys, 1, &challenge)));
+    assert_ne!(base, c(session_transcript(MEMBERSHIP_PROOF, &keys, 2, &challenge)));
+    assert_ne!(base, c(session_transcript(MEMBERSHIP_PROOF, &other, 1, &challenge)));
+    assert_ne!(base, c(session_transcript(MEMBERSHIP_PROOF, &keys, 1, &VerifierChallenge::new("https://verifier.example"))));
+
+    // framing: moving bytes between messages changes the challenge
+    let mut split = new_transcript(b"test");
+    split.append_message(b"m", b"ab");
+    split.append_message(b"m", b"c");
+    let mut joined = new_transcript(b"test");
+    joined.append_message(b"m", b"a");
+    joined.append_message(b"m", b"bc");
+    assert_ne!(c(split), c(joined));
+}
+
+#[test]
+fn membership_proof_is_bound_to_its_context() {
+    let schema = exam_candidate_schema();
+    let issuer = Issuer::for_schema(&schema);
+    let keys = issuer.public_keys();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let holder = Holder::new(issuer.issue_with_schema(&acc, &schema, &values()).unwrap());
+    let challenge = VerifierChallenge::new("https://verifier.example");
+
+    let (cx, proof) = holder.prove_membership(&keys, &acc, &challenge);
+    let epoch = acc.get_epoch();
+    let verify = |proof_type: &'static [u8], epoch: u64| {
+        let mut transcript = session_transcript(proof_type, &keys, epoch, &challenge);
+        verify_zkp(&keys, acc.get_alpha(), &cx, &proof, &mut transcript)
+    };
+    assert!(verify(MEMBERSHIP_PROOF, epoch));
+    assert!(!verify(MEMBERSHIP_PROOF, epoch + 1));
+    assert!(!verify(b"presentation", epoch));
+
+    // a later accumulator state with the same holder still accumulated
+    let bystander = issuer.issue_with_schema(&acc, &schema, &values()).unwrap();
+    issuer.revoke_a_cred(&mut acc, &bystander).unwrap();
+    let mut transcript = session_transcript(MEMBERSHIP_PROOF, &keys, acc.get_epoch(), &challenge);
+    assert!(!verify_zkp(&keys, acc.get_alpha(), &cx, &proof, &mut transcript));
+}
@@ -0,0 +1,97 @@
+use ark_bls12_381::{Fr, G1Projective, G2Projective};
+use ark_std::UniformRand;
+use bbs_plus::prelude::SecretKey;
+use rand::thread_rng;
+
+use accumulator_impl::acc::UniversalAccumulator;
+use accumulator_impl::helper::{verify_non_mem_witness, verify_witness};
+
+struct Keys {
+    sk: SecretKey<Fr>,
+    g1: G1Projective,
+    g2: G2Projective,
+    j: G2Projective,
+}
+
+fn keys() -> Keys {
+    let mut rng = thread_rng();
+    let sk = SecretKey(Fr::rand(&mut rng));
+    let g2 = G2Projective::rand(&mut rng);
+    Keys { j: g2 * sk.0, sk, g1: G1Projective::rand(&mut rng), g2 }
+}
+
+fn check(acc: &UniversalAccumulator, keys: &Keys, x: &Fr, w: &G1Projective, d: &Fr) -> bool {
+    verify_non_mem_witness(acc.get_alpha(), acc.get_base(), x, w, d, &keys.g2, &keys.j)
+}
+
+#[test]
+fn non_membership_witness_verifies() {
+    let mut rng = thread_rng();
+    let keys = keys();
+    let mut acc = UniversalAccumulator::new(&keys.g1);
+
+    let revoked: Vec<Fr> = (0..3).map(|_| Fr::rand(&mut rng)).collect();
+    for y in revoked.iter() {
+        assert!(acc.add(y, &keys.sk).is_some());
+    }
+    // adding twice is refused
+    assert!(acc.add(&revoked[0], &keys.sk).is_none());
+
+    let x = Fr::rand(&mut rng);
+    let wit = acc.gen_non_mem_witness(&x, &keys.sk).unwrap();
+    assert!(check(&acc, &keys, &x, &wit.w, &wit.d));
+
+    // wrong element or zero d must fail
+    assert!(!check(&acc, &keys, &Fr::rand(&mut rng), &wit.w, &wit.d));
+    assert!(!check(&acc, &keys, &x, &wit.w, &Fr::from(0u64)));
+
+    // accumulated elements have no non-membership witness, only a membership one
+    assert!(acc.gen_non_mem_witness(&revoked[1], &keys.sk).is_none());
+    let mem = acc.gen_witness(&revoked[1], &keys.sk).unwrap();
+    assert!(verify_witness(acc.get_alpha(), &revoked[1], &mem, &keys.g2, &keys.j));
+    assert!(acc.gen_witness(&x, &keys.sk).is_none());
+}
+
+#[test]
+fn non_membership_witness_follows_updates() {
+    let mut rng = thread_rng();
+    let keys = keys();
+    let mut acc = UniversalAccumulator::new(&keys.g1);
+
+    // empty set: d = 1
+    let x = Fr::rand(&mut rng);
+    let mut wit = acc.gen_non_mem_witness(&x, &keys.sk).unwrap();
+    assert_eq!(wit.d, Fr::from(1u64));
+    assert!(check(&acc, &keys, &x, &wit.w, &wit.d));
+
+    let ys: Vec<Fr> = (0..4).map(|_| Fr::rand(&mut rng)).collect();
+    for y in ys.iter() {
+        let old_alpha = acc.add(y, &keys.sk).unwrap();
+        wit.update_on_add(&x, y, &old_alpha);
+        assert!(check(&acc, &keys, &x, &wit.w, &wit.d));
+    }
+
+    for y in ys.iter().take(2) {
+        let new_alpha = acc.delete(y, &keys.sk).unwrap();
+        wit.update_on_delete(&x, y, &new_alpha);
+        assert!(check(&acc, &keys, &x, &wit.w, &wit.d));
+    }
+    assert!(acc.delete(&ys[0], &keys.sk).is_none());
+
+    // the publicly updated witness equals a freshly issued one
+    assert_eq!(wit, acc.gen_non_mem_witness(&x, &keys.sk).unwrap());
+}
+
+#[test]
+fn revoking_x_invalidates_its_non_membership_witness() {
+    let mut rng = thread_rng();
+    let keys = keys();
+    let mut acc = UniversalAccumulator::new(&keys.g1);
+
+    let x = Fr::rand(&mut rng);
+    let mut wit = acc.gen_non_mem_witness(&x, &keys.sk).unwrap();
+
+    let old_alpha = acc.add(&x, &keys.sk).unwrap();
+    wit.update_on_add(&x, &x, &old_alpha);
+    assert!(!check(&acc, &keys, &x, &wit.w, &wit.d));
+}
@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::holder::Holder;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::replay::VerifierChallenge;
+use accumulator_impl::schema::*;
+use accumulator_impl::validity::*;
+
+const FROM: u64 = 1_767_225_600; // 2026-01-01
+const UNTIL: u64 = 1_798_761_599; // 2026-12-31 23:59:59
+
+fn values() -> BTreeMap<String, AttributeValue> {
+    BTreeMap::from([
+        ("name".to_string(), AttributeValue::String("Alice".to_string())),
+        ("dob".to_string(), AttributeValue::Date("2004-11-15".to_string())),
+        ("age".to_string(), AttributeValue::Integer(21)),
+        ("score".to_string(), AttributeValue::Integer(75)),
+        ("candidate_id".to_string(), AttributeValue::Integer(245)),
+    ])
+}
+
+fn setup() -> (CredentialSchema, Issuer, ECAccumulator, Holder) {
+    let schema = exam_candidate_schema().with_validity().unwrap();
+    let issuer = Issuer::for_schema(&schema);
+    let acc = ECAccumulator::new(issuer.get_g1());
+    let period = ValidityPeriod::new(FROM, UNTIL).unwrap();
+    let cred = issuer.issue_with_validity(&acc, &schema, &values(), period).unwrap();
+    (schema, issuer, acc, Holder::new(cred))
+}
+
+#[test]
+fn validity_is_signed() {
+    let (schema, issuer, acc, holder) = setup();
+    assert_eq!(schema.validity_indices(), Some((6, 7)));
+    assert_eq!(exam_candidate_schema().validity_indices(), None);
+    assert_ne!(schema.id(), exam_candidate_schema().id());
+
+    let cred = holder.get_cred();
+    assert_eq!(cred.get_validity(), ValidityPeriod::new(FROM, UNTIL));
+    assert_eq!(validity_from_messages(&schema, cred.get_message()), cred.get_validity());
+    assert!(holder.verify_mem(issuer.get_pk(), issuer.get_sig_param(), acc.get_alpha(), issuer.get_g2(), issuer.get_j()));
+    assert_eq!(ValidityPeriod::new(UNTIL, FROM), None);
+}
+
+#[test]
+fn expiry_proof_at_verifier_time() {
+    let (schema, issuer, _, holder) = setup();
+    let (g, h, _, _) = issuer.get_g_h_k_z();
+    let (pk, param) = (issuer.get_pk(), issuer.get_sig_param());
+    let session = VerifierChallenge::new("https://verifier.example");
+
+    for now in [FROM, FROM + 86_400 * 100, UNTIL] {
+        let proof = holder.prove_validity(&schema, pk, param, g, h, now, &session).unwrap();
+        assert!(proof.verify(&schema, pk, param, g, h, now, &session));
+        // bound to the reference time and the challenge it was made for
+        assert!(!proof.verify(&schema, pk, param, g, h, now + 1, &session));
+        assert!(!proof.verify(&schema, pk, param, g, h, now, &VerifierChallenge::new("https://verifier.example")));
+    }
+
+    assert_eq!(holder.prove_validity(&schema, pk, param, g, h, FROM - 1, &session).err(), Some(ValidityError::NotYetValid));
+    assert_eq!(holder.prove_validity(&schema, pk, param, g, h, UNTIL + 1, &session).err(), Some(ValidityError::Expired));
+
+    // the verifier's schema must have validity and match the signed schema id
+    let proof = holder.prove_validity(&schema, pk, param, g, h, FROM + 1, &session).unwrap();
+    assert!(!proof.verify(&exam_candidate_schema(), pk, param, g, h, FROM + 1, &session));
+    let other = Issuer::for_schema(&schema);
+    assert!(!proof.verify(&schema, other.get_pk(), other.get_sig_param(), g, h, FROM + 1, &session));
+}
+
+#[test]
+fn credentials_without_validity() {
+    let schema = exam_candidate_schema();
+    let issuer = Issuer::for_schema(&schema);
+    let acc = ECAccumulator::new(issuer.get_g1());
+    let holder = Holder::new(issuer.issue_with_schema(&acc, &schema, &values()).unwrap());
+    let (g, h, _, _) = issuer.get_g_h_k_z();
+
+    assert_eq!(holder.get_cred().get_validity(), None);
+    assert_eq!(
+        holder.prove_validity(&schema, issuer.get_pk(), issuer.get_sig_param(), g, h, FROM, &VerifierChallenge::new("verifier")).err(),
+        Some(ValidityError::NoValidityPeriod)
+    );
+    let period = ValidityPeriod::new(FROM, UNTIL).unwrap();
+    assert!(issuer.issue_with_validity(&acc, &schema, &values(), period).is_err());
+}
@@ -0,0 +1,150 @@
+use ark_bls12_381::G1Projective;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::credential::Credential;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::presentation::*;
+use accumulator_impl::replay::VerifierChallenge;
+use accumulator_impl::schema::*;
+use accumulator_impl::validity::ValidityPeriod;
+use accumulator_impl::vc::*;
+
+const FROM: u64 = 1_767_225_600; // 2026-01-01T00:00:00Z
+const UNTIL: u64 = 1_798_761_599; // 2026-12-31T23:59:59Z
+const MAX_DATETIME: u64 = 253_402_300_799; // 9999-12-31T23:59:59Z
+
+fn values() -> BTreeMap<String, AttributeValue> {
+    BTreeMap::from([
+        ("name".to_string(), AttributeValue::String("Alice".to_string())),
+        ("dob".to_string(), AttributeValue::Date("2004-11-15".to_string())),
+        ("age".to_string(), AttributeValue::Integer(21)),
+        ("score".to_string(), AttributeValue::Integer(75)),
+        ("candidate_id".to_string(), AttributeValue::Integer(245)),
+    ])
+}
+
+#[test]
+fn dates_and_times() {
+    for date in ["0001-01-01", "1970-01-01", "2000-02-29", "2004-11-15", "9999-12-31"] {
+        assert_eq!(day_number_to_date(parse_date(date).unwrap()).as_deref(), Some(date));
+    }
+    assert_eq!(day_number_to_date(0), None);
+    assert_eq!(to_datetime(FROM).unwrap(), "2026-01-01T00:00:00Z");
+    assert_eq!(to_datetime(UNTIL).unwrap(), "2026-12-31T23:59:59Z");
+    assert_eq!(to_datetime(MAX_DATETIME).unwrap(), "9999-12-31T23:59:59Z");
+    assert_eq!(to_datetime(MAX_DATETIME + 1), Err(VcError::DateOutOfRange(MAX_DATETIME + 1)));
+    assert_eq!(parse_datetime("2026-12-31T23:59:59Z"), Some(UNTIL));
+    assert_eq!(parse_datetime("2026-12-31T23:59:59+01:00"), None);
+    assert_eq!(parse_datetime("2026-12-31T24:00:00Z"), None);
+}
+
+#[test]
+fn credential_round_trip() {
+    let schema = exam_candidate_schema().with_validity().unwrap();
+    let issuer = Issuer::for_schema(&schema);
+    let keys = issuer.public_keys();
+    let acc = ECAccumulator::new(issuer.get_g1());
+    let period = ValidityPeriod::new(FROM, UNTIL).unwrap();
+    let cred = issuer.issue_with_validity(&acc, &schema, &values(), period).unwrap();
+
+    let vc = VerifiableCredential::from_credential(&cred, &schema, &keys, &values()).unwrap();
+    let doc: Value = serde_json::from_str(&vc.to_json()).unwrap();
+    assert_eq!(doc["@context"], json!([VC_CONTEXT_V2]));
+    assert_eq!(doc["credentialSubject"]["name"], json!("Alice"));
+    assert_eq!(doc["credentialSubject"]["score"], json!(75));
+    assert_eq!(doc["credentialSubject"].get("valid_from"), None);
+    assert_eq!(doc["validUntil"], json!("2026-12-31T23:59:59Z"));
+    assert_eq!(doc["proof"]["cryptosuite"], json!(CRYPTOSUITE));
+    assert_eq!(doc["proof"]["messageIndices"]["name"], json!(schema.message_index("name").unwrap()));
+    assert_eq!(doc["proof"]["messageIndices"][HOLDER_COMMITMENT], json!(schema.message_count() - 1));
+    assert_eq!(doc["credentialStatus"]["statusPurpose"], json!("revocation"));
+    // the holder secret stays out of the document
+    assert!(!vc.to_json().contains(&format!("{}", cred.get_x_val())));
+
+    let vc = VerifiableCredential::from_json(&vc.to_json()).unwrap();
+    assert_eq!(vc.verify(&schema, &keys), Ok(()));
+    let back: Credential = vc.to_credential(&schema, &keys, *cred.get_x_val(), *cred.get_r()).unwrap();
+    assert_eq!(back.get_message(), cred.get_message());
+    assert_eq!(back.get_witness(), cred.get_witness());
+    assert_eq!(back.get_validity(), Some(period));
+
+    // the signed integer has no dateTime form past year 9999
+    let far = ValidityPeriod::new(FROM, MAX_DATETIME + 1).unwrap();
+    let far_cred = issuer.issue_with_validity(&acc, &schema, &values(), far).unwrap();
+    assert_eq!(
+        VerifiableCredential::from_credential(&far_cred, &schema, &keys, &values()).err(),
+        Some(VcError::DateOutOfRange(MAX_DATETIME + 1))
+    );
+    assert_eq!(
+        vc.to_credential::<G1Projective>(&schema, &keys, *cred.get_r(), *cred.get_x_val()).err(),
+        Some(VcError::CommitmentMismatch)
+    );
+
+    // String attributes cannot be read back from the signature
+    let mut partial = values();
+    partial.remove("name");
+    partial.remove("age");
+    assert_eq!(
+        VerifiableCredential::from_credential(&cred, &schema, &keys, &partial).err(),
+        Some(VcError::MissingValue("name".to_string()))
+    );
+    partial.insert("name".to_string(), AttributeValue::String("Mallory".to_string()));
+    assert_eq!(
+        VerifiableCredential::from_credential(&cred, &schema, &keys, &partial).err(),
+        Some(VcError::SubjectMismatch("name".to_string()))
+    );
+
+    // edited claims no longer carry the issuer's signature
+    let mut forged = vc.clone();
+    forged.credential_subject.insert("score".to_string(), json!(99));
+    assert_eq!(forged.verify(&schema, &keys), Err(VcError::InvalidSignature));
+    let mut forged = vc.clone();
+    forged.valid_until = Some(to_datetime(UNTIL + 86_400).unwrap());
+    assert_eq!(forged.verify(&schema, &keys), Err(VcError::InvalidSignature));
+    assert_eq!(vc.verify(&exam_candidate_schema(), &keys), Err(VcError::WrongSchema));
+    assert_eq!(vc.verify(&schema, &Issuer::for_schema(&schema).public_keys()), Err(VcError::WrongIssuer));
+}
+
+#[test]
+fn presentation_as_vp() {
+    let schema = exam_candidate_schema();
+    let issuer = Issuer::for_schema(&schema);
+    let keys = issuer.public_keys();
+    let acc = ECAccumulator::new(issuer.get_g1());
+    let cred = issuer.issue_with_schema(&acc, &schema, &values()).unwrap();
+    let state = AccumulatorState::from(&acc);
+
+    let request = PresentationRequest {
+        reveal: vec!["name".to_string(), "dob".to_string()],
+        predicates: vec![Predicate { attribute: "score".to_string(), min: Some(60), max: Some(100) }],
+        scope: Some("exam-board".to_string()),
+        ..PresentationRequest::new(&VerifierChallenge::new("https://verifier.example"))
+    };
+    let presentation = Presentation::create(&request, &cred, &keys, &schema, &state).unwrap();
+    // dob decodes from the signed day number, name has to be supplied
+    assert_eq!(
+        VerifiablePresentation::from_presentation(&presentation, &schema, &BTreeMap::new()).err(),
+        Some(VcError::MissingValue("name".to_string()))
+    );
+    let vp = VerifiablePresentation::from_presentation(&presentation, &schema, &values()).unwrap();
+    let doc: Value = serde_json::from_str(&vp.to_json()).unwrap();
+    assert_eq!(doc["type"], json!(["VerifiablePresentation"]));
+    assert_eq!(doc["holder"], json!(format!("urn:vac:pseudonym:{}", presentation.pseudonym.clone().unwrap())));
+    let credential = &doc["verifiableCredential"][0];
+    assert_eq!(credential["credentialSubject"], json!({ "name": "Alice", "dob": "2004-11-15" }));
+    assert_eq!(credential["proof"]["challenge"], json!(request.nonce));
+    assert_eq!(credential["proof"]["domain"], json!("https://verifier.example"));
+    assert_eq!(credential["credentialStatus"]["epoch"], json!(state.epoch));
+
+    let (received, disclosed) = VerifiablePresentation::from_json(&vp.to_json()).unwrap().to_presentation(&schema).unwrap();
+    assert_eq!(received, presentation);
+    assert_eq!(disclosed["dob"], AttributeValue::Date("2004-11-15".to_string()));
+    assert_eq!(request.validate(&received, &keys, &schema, &state), Ok(()));
+
+    let mut forged = vp.clone();
+    forged.verifiable_credential[0].credential_subject.insert("name".to_string(), json!("Mallory"));
+    let (received, _) = forged.to_presentation(&schema).unwrap();
+    assert_eq!(request.validate(&received, &keys, &schema, &state), Err(PresentationError::InvalidProof));
+}
@@ -0,0 +1,214 @@
+use ark_bls12_381::Fr;
+use ark_std::UniformRand;
+use rand::thread_rng;
+use std::collections::BTreeMap;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::helper::verify_witness;
+use accumulator_impl::holder::Holder;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::keystore::KdfParams;
+use accumulator_impl::revocation::merkle::{SparseMerkleTree, DEFAULT_DEPTH};
+use accumulator_impl::schema::*;
+use accumulator_impl::validity::ValidityPeriod;
+use accumulator_impl::wallet::*;
+
+const PASSPHRASE: &str = "correct horse battery staple";
+const KDF: KdfParams = KdfParams { m_cost: 64, t_cost: 1, p_cost: 1 };
+
+fn messages() -> Vec<Fr> {
+    let mut rng = thread_rng();
+    (0..5).map(|_| Fr::rand(&mut rng)).collect()
+}
+
+fn values() -> BTreeMap<String, AttributeValue> {
+    BTreeMap::from([
+        ("name".to_string(), AttributeValue::String("Alice".to_string())),
+        ("dob".to_string(), AttributeValue::Date("2004-11-15".to_string())),
+        ("age".to_string(), AttributeValue::Integer(21)),
+        ("score".to_string(), AttributeValue::Integer(75)),
+        ("candidate_id".to_string(), AttributeValue::Integer(245)),
+    ])
+}
+
+fn wallet_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("holder_wallet_{}_{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn credentials_survive_a_reopen() {
+    let path = wallet_path("reopen");
+    let schema = exam_candidate_schema().with_validity().unwrap();
+    let school = Issuer::for_schema(&schema);
+    let club = Issuer::with_key_id(3);
+    let school_acc = ECAccumulator::new(school.get_g1());
+    let club_acc = ECAccumulator::new(club.get_g1());
+
+    let period = ValidityPeriod::new(1_000, 2_000).unwrap();
+    let diploma = school.issue_with_validity(&school_acc, &schema, &values(), period).unwrap();
+    let x = *diploma.get_x_val();
+    let (diploma_id, card_id) = {
+        let mut wallet = Wallet::<ECAccumulator>::create_with_kdf(&path, PASSPHRASE, KDF).unwrap();
+        let diploma_id = wallet.add(diploma, &school.public_keys(), Some(&schema)).unwrap();
+        let card_id = wallet.add(club.gen_witness_n_cred(&club_acc, messages()), &club.public_keys(), None).unwrap();
+        // a credential is filed under the key that signed it
+        assert!(wallet.add(club.gen_witness_n_cred(&club_acc, messages()), &school.public_keys(), None).is_err());
+        wallet.save().unwrap();
+        (diploma_id, card_id)
+    };
+
+    assert!(Wallet::<ECAccumulator>::create_with_kdf(&path, PASSPHRASE, KDF).is_err());
+    assert!(Wallet::<ECAccumulator>::open(&path, "wrong passphrase").is_err());
+    let raw = std::fs::read_to_string(&path).unwrap();
+    assert!(!raw.contains(&school.public_keys().fingerprint()));
+
+    let wallet = Wallet::<ECAccumulator>::open(&path, PASSPHRASE).unwrap();
+    assert_eq!(wallet.len(), 2);
+    let diploma = wallet.get(&diploma_id).unwrap();
+    assert_eq!(*diploma.get_cred().get_x_val(), x);
+    assert_eq!(diploma.get_cred().get_validity(), Some(period));
+    assert_eq!(diploma.schema_id, Some(schema.id()));
+
+    let keys = club.public_keys();
+    let card = Holder::new(wallet.get(&card_id).unwrap().get_cred().clone_secret());
+    assert!(card.verify_mem(&keys.pk, &keys.sig_param, club_acc.get_alpha(), &keys.g2, &keys.j));
+}
+
+#[test]
+fn query_by_issuer_schema_and_time() {
+    let path = wallet_path("query");
+    let schema = exam_candidate_schema().with_validity().unwrap();
+    let school = Issuer::for_schema(&schema);
+    let club = Issuer::new();
+    let acc = ECAccumulator::new(school.get_g1());
+
+    let mut wallet = Wallet::<ECAccumulator>::create_with_kdf(&path, PASSPHRASE, KDF).unwrap();
+    let old = ValidityPeriod::new(1_000, 2_000).unwrap();
+    let new = ValidityPeriod::new(1_500, 3_000).unwrap();
+    let old_id = wallet.add(school.issue_with_validity(&acc, &schema, &values(), old).unwrap(), &school.public_keys(), Some(&schema)).unwrap();
+    let new_id = wallet.add(school.issue_with_validity(&acc, &schema, &values(), new).unwrap(), &school.public_keys(), Some(&schema)).unwrap();
+    let card_id = wallet.add(club.gen_witness_n_cred(&acc, messages()), &club.public_keys(), None).unwrap();
+
+    let school_id = school.public_keys().fingerprint();
+    assert_eq!(wallet.issuers().len(), 2);
+    assert_eq!(wallet.by_issuer(&school_id).len(), 2);
+    assert_eq!(wallet.by_schema(&schema.id()).len(), 2);
+
+    let ids = |query: &CredentialQuery| wallet.query(query).iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+    assert_eq!(ids(&CredentialQuery::default()), vec![old_id.clone(), new_id.clone(), card_id.clone()]);
+    let mut query = CredentialQuery { schemas: vec![schema.id()], valid_at: Some(2_500), ..Default::default() };
+    assert_eq!(ids(&query), vec![new_id.clone()]);
+    query.valid_at = Some(1_700);
+    assert_eq!(ids(&query), vec![old_id.clone(), new_id.clone()]);
+    query.issuers = vec![club.public_keys().fingerprint()];
+    assert!(ids(&query).is_empty());
+
+    assert!(wallet.remove(&old_id));
+    assert!(!wallet.remove(&old_id));
+    assert_eq!(wallet.by_issuer(&school_id).len(), 1);
+}
+
+#[test]
+fn refresh_from_an_update_feed() {
+    let path = wallet_path("refresh");
+    let issuer = Issuer::new();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let mut wallet = Wallet::<ECAccumulator>::create_with_kdf(&path, PASSPHRASE, KDF).unwrap();
+    let keys = issuer.public_keys();
+    let ids: Vec<String> = (0..3)
+        .map(|_| wallet.add(issuer.gen_witness_n_cred(&acc, messages()), &keys, None).unwrap())
+        .collect();
+
+    // someone else's credential and then the wallet's second one are revoked
+    let stranger = issuer.gen_witness_n_cred(&acc, messages());
+    let feed = vec![
+        issuer.revoke_a_cred(&mut acc, &stranger).unwrap(),
+        issuer.revoke_handle(&mut acc, wallet.get(&ids[1]).unwrap().get_cred().get_x_val()).unwrap(),
+    ];
+    let params = keys.clone();
+    assert_eq!(wallet.refresh_witnesses(&keys.fingerprint(), &params, &feed), vec![ids[1].clone()]);
+    assert!(wallet.get(&ids[1]).unwrap().revoked);
+    assert_eq!(wallet.query(&CredentialQuery::default()).len(), 2);
+
+    for id in [&ids[0], &ids[2]] {
+        let cred = wallet.get(id).unwrap().get_cred();
+        assert!(verify_witness(acc.get_alpha(), cred.get_x_val(), cred.get_witness(), &params.g2, &params.j));
+    }
+
+    // revoked state is kept across a save
+    wallet.save().unwrap();
+    drop(wallet);
+    let wallet = Wallet::<ECAccumulator>::open(&path, PASSPHRASE).unwrap();
+    assert!(wallet.get(&ids[1]).unwrap().revoked);
+}
+
+#[test]
+fn sync_from_an_update_log() {
+    let path = wallet_path("sync");
+    let issuer = Issuer::new();
+    let keys = issuer.public_keys();
+    let params = keys.clone();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let mut wallet = Wallet::<ECAccumulator>::create_with_kdf(&path, PASSPHRASE, KDF).unwrap();
+    let mut log = Vec::new();
+    let revoke_stranger = |acc: &mut ECAccumulator, log: &mut Vec<_>| {
+        let stranger = issuer.gen_witness_n_cred(&*acc, messages());
+        log.push((acc.get_epoch() + 1, issuer.revoke_a_cred(acc, &stranger).unwrap()));
+    };
+
+    // one credential from before the first revocation, one from after it
+    let early = wallet.add_at_epoch(issuer.gen_witness_n_cred(&acc, messages()), &keys, None, acc.get_epoch()).unwrap();
+    revoke_stranger(&mut acc, &mut log);
+    let late = wallet.add_at_epoch(issuer.gen_witness_n_cred(&acc, messages()), &keys, None, acc.get_epoch()).unwrap();
+    revoke_stranger(&mut acc, &mut log);
+    assert_eq!(wallet.get(&late).unwrap().epoch, Some(1));
+
+    // each takes only the updates past its epoch, and syncing again is a no-op
+    for _ in 0..2 {
+        assert!(wallet.sync_witnesses(&keys.fingerprint(), &params, &log).unwrap().is_empty());
+        for id in [&early, &late] {
+            let entry = wallet.get(id).unwrap();
+            assert_eq!(entry.epoch, Some(2));
+            assert!(verify_witness(acc.get_alpha(), entry.get_cred().get_x_val(), entry.get_cred().get_witness(), &params.g2, &params.j));
+        }
+    }
+
+    let x = *wallet.get(&late).unwrap().get_cred().get_x_val();
+    log.push((3, issuer.revoke_handle(&mut acc, &x).unwrap()));
+    revoke_stranger(&mut acc, &mut log);
+    assert_eq!(wallet.sync_witnesses(&keys.fingerprint(), &params, &log).unwrap(), vec![late.clone()]);
+    assert_eq!(wallet.get(&early).unwrap().epoch, Some(4));
+
+    // the epochs are kept across a save
+    wallet.save().unwrap();
+    drop(wallet);
+    let mut wallet = Wallet::<ECAccumulator>::open(&path, PASSPHRASE).unwrap();
+    assert_eq!(wallet.get(&early).unwrap().epoch, Some(4));
+    assert!(wallet.get(&late).unwrap().revoked);
+
+    // a credential of unknown epoch cannot tell which updates it needs: the
+    // sync is refused and no witness moves
+    let unknown = wallet.add(issuer.gen_witness_n_cred(&acc, messages()), &keys, None).unwrap();
+    revoke_stranger(&mut acc, &mut log);
+    let witness = *wallet.get(&unknown).unwrap().get_cred().get_witness();
+    assert!(wallet.sync_witnesses(&keys.fingerprint(), &params, &log).is_err());
+    assert_eq!(*wallet.get(&unknown).unwrap().get_cred().get_witness(), witness);
+    assert_eq!(wallet.get(&early).unwrap().epoch, Some(4));
+}
+
+#[test]
+fn merkle_backed_wallet() {
+    let path = wallet_path("merkle");
+    let issuer = Issuer::new();
+    let tree = SparseMerkleTree::new(DEFAULT_DEPTH);
+    let mut wallet = Wallet::<SparseMerkleTree>::create_with_kdf(&path, PASSPHRASE, KDF).unwrap();
+    let id = wallet.add(issuer.gen_witness_n_cred(&tree, messages()), &issuer.public_keys(), None).unwrap();
+    let witness = wallet.get(&id).unwrap().get_cred().get_witness().clone();
+    wallet.save().unwrap();
+    drop(wallet);
+
+    let wallet = Wallet::<SparseMerkleTree>::open(&path, PASSPHRASE).unwrap();
+    assert_eq!(wallet.get(&id).unwrap().get_cred().get_witness(), &witness);
+}
@@ -0,0 +1,126 @@
+use ark_bls12_381::{Fr, G1Projective};
+use ark_std::UniformRand;
+use rand::thread_rng;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::credential::{par_batch_update_witnesses, Credential};
+use accumulator_impl::helper::verify_witness;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::keystore::KdfParams;
+use accumulator_impl::wallet::Wallet;
+
+const KDF: KdfParams = KdfParams { m_cost: 64, t_cost: 1, p_cost: 1 };
+
+fn messages() -> Vec<Fr> {
+    let mut rng = thread_rng();
+    (0..5).map(|_| Fr::rand(&mut rng)).collect()
+}
+
+// Revokes `count` fresh credentials nobody else holds; returns the log as
+// (alpha_i, delta_i)
+fn revoke_strangers(issuer: &Issuer, acc: &mut ECAccumulator, count: usize) -> Vec<(G1Projective, Fr)> {
+    (0..count)
+        .map(|_| {
+            let stranger = issuer.gen_witness_n_cred(&*acc, messages());
+            let update = issuer.revoke_a_cred(acc, &stranger).unwrap();
+            (update.new_alpha, update.delta)
+        })
+        .collect()
+}
+
+#[test]
+fn batch_matches_one_update_at_a_time() {
+    let issuer = Issuer::new();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let params = issuer.public_keys();
+    let cred = issuer.gen_witness_n_cred(&acc, messages());
+
+    let mut batched = cred.clone_secret();
+    assert!(batched.batch_update_witness(&[]));
+    assert_eq!(batched.get_witness(), cred.get_witness());
+
+    for m in [1, 2, 7] {
+        let log = revoke_strangers(&issuer, &mut acc, m);
+        let mut one_by_one = batched.clone_secret();
+        for (alpha, delta) in log.iter() {
+            one_by_one.update_witness(*delta, *alpha);
+        }
+        assert!(batched.batch_update_witness(&log));
+        assert_eq!(batched.get_witness(), one_by_one.get_witness());
+        assert!(verify_witness(acc.get_alpha(), batched.get_x_val(), batched.get_witness(), &params.g2, &params.j));
+    }
+
+    // a log revoking the holder leaves the witness alone
+    let mut log = revoke_strangers(&issuer, &mut acc, 2);
+    let update = issuer.revoke_a_cred(&mut acc, &batched).unwrap();
+    log.push((update.new_alpha, update.delta));
+    let before = *batched.get_witness();
+    assert!(!batched.batch_update_witness(&log));
+    assert_eq!(*batched.get_witness(), before);
+}
+
+#[test]
+fn population_refresh_in_parallel() {
+    let issuer = Issuer::new();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let params = issuer.public_keys();
+    let mut population: Vec<Credential> = (0..40).map(|_| issuer.gen_witness_n_cred(&acc, messages())).collect();
+
+    let mut log = revoke_strangers(&issuer, &mut acc, 5);
+    for i in [3, 17] {
+        let update = issuer.revoke_a_cred(&mut acc, &population[i]).unwrap();
+        log.push((update.new_alpha, update.delta));
+    }
+    log.extend(revoke_strangers(&issuer, &mut acc, 5));
+
+    let mut serial: Vec<Credential> = population.iter().map(|c| c.clone_secret()).collect();
+    let serial_live: Vec<bool> = serial.iter_mut().map(|c| c.batch_update_witness(&log)).collect();
+    let live = par_batch_update_witnesses(&mut population, &log);
+    assert_eq!(live, serial_live);
+    assert_eq!(live.iter().filter(|l| !**l).count(), 2);
+    assert!(!live[3] && !live[17]);
+    for (cred, (other, live)) in population.iter().zip(serial.iter().zip(live)) {
+        assert_eq!(cred.get_witness(), other.get_witness());
+        assert_eq!(live, verify_witness(acc.get_alpha(), cred.get_x_val(), cred.get_witness(), &params.g2, &params.j));
+    }
+}
+
+#[test]
+fn wallet_refresh_in_parallel() {
+    let issuer = Issuer::new();
+    let keys = issuer.public_keys();
+    let params = keys.clone();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let path = |name: &str| {
+        let path = std::env::temp_dir().join(format!("witness_update_{}_{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    };
+    let mut serial = Wallet::<ECAccumulator>::create_with_kdf(path("serial"), "pass", KDF).unwrap();
+    let mut parallel = Wallet::<ECAccumulator>::create_with_kdf(path("parallel"), "pass", KDF).unwrap();
+    let ids: Vec<String> = (0..6)
+        .map(|_| {
+            let cred = issuer.gen_witness_n_cred(&acc, messages());
+            parallel.add(cred.clone_secret(), &keys, None).unwrap();
+            serial.add(cred, &keys, None).unwrap()
+        })
+        .collect();
+
+    let stranger = issuer.gen_witness_n_cred(&acc, messages());
+    let feed = vec![
+        issuer.revoke_a_cred(&mut acc, &stranger).unwrap(),
+        issuer.revoke_handle(&mut acc, serial.get(&ids[4]).unwrap().get_cred().get_x_val()).unwrap(),
+        issuer.revoke_handle(&mut acc, serial.get(&ids[1]).unwrap().get_cred().get_x_val()).unwrap(),
+    ];
+    let revoked = parallel.par_refresh_witnesses(&keys.fingerprint(), &feed);
+    assert_eq!(revoked, vec![ids[1].clone(), ids[4].clone()]);
+    assert_eq!(serial.refresh_witnesses(&keys.fingerprint(), &params, &feed), revoked);
+    assert!(parallel.par_refresh_witnesses("unknown issuer", &feed).is_empty());
+    for id in ids.iter() {
+        let (p, s) = (parallel.get(id).unwrap(), serial.get(id).unwrap());
+        assert_eq!(p.revoked, s.revoked);
+        if !p.revoked {
+            assert_eq!(p.get_cred().get_witness(), s.get_cred().get_witness());
+        }
+    }
+}
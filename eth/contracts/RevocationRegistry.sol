@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+// Accumulator state of one credential issuer, so verifiers can read an
+// authoritative accumulator value and epoch, and holders the revocations
+// they need to update their witnesses.
+//
+// alpha is a BLS12-381 G1 point in the EIP-2537 layout (x then y, each a
+// 48-byte field element left-padded to 64 bytes; all zeros for infinity),
+// delta a revoked x, a BLS12-381 scalar, big-endian. The epoch is the
+// accumulator epoch: every revocation moves it on by one. Only the issuer
+// can write; the Rust side is eth_client::registry.
+//
+// Build: solc --bin --abi RevocationRegistry.sol -o build
+contract RevocationRegistry {
+    uint256 private constant G1_LEN = 128;
+
+    address public immutable issuer;
+    uint64 public epoch;
+    bytes public alpha; // empty until the first checkpoint
+    bytes32 public alphaHash; // keccak256(alpha), for contracts that only compare
+    bytes32[] public deltas; // every revoked delta, in order
+
+    // The accumulator values in between are only in the events: holders
+    // update their witnesses from them
+    event Checkpoint(uint64 indexed epoch, bytes alpha);
+    event Revoked(uint64 indexed epoch, bytes32 delta, bytes newAlpha);
+
+    error NotIssuer();
+    error StaleCheckpoint(uint64 current, uint64 given);
+    error WrongEpoch(uint64 current, uint64 given);
+    error BadBatch();
+    error BadAlpha();
+
+    modifier onlyIssuer() {
+        if (msg.sender != issuer) revert NotIssuer();
+        _;
+    }
+
+    constructor(address issuer_) {
+        issuer = issuer_;
+    }
+
+    // Length and padding only: the BLS12-381 precompiles are not on every chain
+    function checkAlpha(bytes calldata value) private pure {
+        if (value.length != G1_LEN) revert BadAlpha();
+        if (bytes16(value[0:16]) != bytes16(0) || bytes16(value[64:80]) != bytes16(0)) revert BadAlpha();
+    }
+
+    function setAlpha(bytes calldata value) private {
+        alpha = value;
+        alphaHash = keccak256(value);
+    }
+
+    // Sets the accumulator outright, for changes the revocation log cannot
+    // express (the first value, reinstatements, key rotation). Only moves
+    // forward, except for the first one
+    function publishCheckpoint(uint64 newEpoch, bytes calldata newAlpha) external onlyIssuer {
+        checkAlpha(newAlpha);
+        if (alpha.length != 0 && newEpoch <= epoch) revert StaleCheckpoint(epoch, newEpoch);
+        epoch = newEpoch;
+        setAlpha(newAlpha);
+        emit Checkpoint(newEpoch, newAlpha);
+    }
+
+    // Appends revocations fromEpoch + 1 .. fromEpoch + newDeltas.length, each
+    // with the accumulator value after it. fromEpoch must be the current
+    // epoch, so a batch sent twice or out of order reverts
+    function revokeBatch(uint64 fromEpoch, bytes32[] calldata newDeltas, bytes[] calldata newAlphas) external onlyIssuer {
+        if (alpha.length == 0 || fromEpoch != epoch) revert WrongEpoch(epoch, fromEpoch);
+        if (newDeltas.length == 0 || newDeltas.length != newAlphas.length) revert BadBatch();
+        for (uint256 i = 0; i < newDeltas.length; i++) {
+            checkAlpha(newAlphas[i]);
+            deltas.push(newDeltas[i]);
+            emit Revoked(fromEpoch + uint64(i) + 1, newDeltas[i], newAlphas[i]);
+        }
+        epoch = fromEpoch + uint64(newDeltas.length);
+        setAlpha(newAlphas[newAlphas.length - 1]);
+    }
+
+    function deltaCount() external view returns (uint256) {
+        return deltas.length;
+    }
+}
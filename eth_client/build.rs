@@ -0,0 +1,27 @@
+// Compiles eth/contracts/RevocationRegistry.sol with solc (SOLC, or solc on
+// the PATH) and hands the init code to the crate as hex in
+// REGISTRY_SOLC_BIN, for registry::solc_bytecode. Without solc the crate
+// still builds, with a warning, and the test against the compiled contract
+// cannot run (it is #[ignore]d); a contract that does not compile fails the build. Shanghai
+// is what the dev node's EVM runs.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn main() {
+    let contract = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("../eth/contracts/RevocationRegistry.sol");
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-changed={}", contract.display());
+    println!("cargo:rerun-if-env-changed=SOLC");
+
+    let solc = env::var("SOLC").unwrap_or_else(|_| "solc".to_string());
+    match Command::new(&solc).args(["--bin", "--optimize", "--evm-version", "shanghai", "--overwrite", "-o"]).arg(&out).arg(&contract).status() {
+        Ok(status) if status.success() => {
+            let bin = std::fs::read_to_string(out.join("RevocationRegistry.bin")).unwrap();
+            println!("cargo:rustc-env=REGISTRY_SOLC_BIN={}", bin.trim());
+        }
+        Ok(status) => panic!("{} failed on {}: {}", solc, contract.display(), status),
+        Err(_) => println!("cargo:warning=solc not found (set SOLC): RevocationRegistry.sol not compiled, its EVM test cannot run"),
+    }
+}
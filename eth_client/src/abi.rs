@@ -0,0 +1,179 @@
+// ABI encoding of what the issuer publishes on chain and of the proofs
+// holders submit.
+//
+//   alpha     BLS12-381 G1 point in the EIP-2537 layout: x then y, each a
+//             48-byte big-endian field element left-padded to 64 bytes;
+//             128 zero bytes for the point at infinity. The BLS12-381
+//             precompiles take it as is
+//   delta     the revoked x, a BLS12-381 scalar, as a big-endian bytes32
+//   Groth16   BN254 proofs in the calldata layout of the verifier snarkjs
+//             exports (`snarkjs zkey export solidityverifier`):
+//             verifyProof(uint256[2] pA, uint256[2][2] pB, uint256[2] pC,
+//             uint256[n] pubSignals), with the Fq2 coordinates of pB written
+//             c1 first, as the EVM pairing precompile expects
+//
+// IRevocationRegistry is the interface of the issuer's registry contract,
+// eth/contracts/RevocationRegistry.sol; the epoch there is the accumulator
+// epoch (one per revocation).
+
+use alloy_primitives::{keccak256, Bytes, B256, U256};
+use alloy_sol_types::sol;
+use ark_bls12_381::{Fq, Fr, G1Affine, G1Projective};
+use ark_bn254::Bn254;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_groth16::Proof;
+use std::error::Error;
+
+use accumulator_impl::presentation::AccumulatorState;
+use accumulator_impl::revocation::AccRevocation;
+
+sol! {
+    interface IRevocationRegistry {
+        function issuer() external view returns (address);
+        function epoch() external view returns (uint64);
+        function alpha() external view returns (bytes);
+        function alphaHash() external view returns (bytes32);
+        function deltas(uint256 index) external view returns (bytes32);
+        function deltaCount() external view returns (uint256);
+        // Sets the accumulator outright, for changes the revocation log cannot
+        // express (the first value, reinstatements, key rotation)
+        function publishCheckpoint(uint64 epoch, bytes alpha) external;
+        // Appends revocations; fromEpoch must be the current epoch, and each
+        // delta comes with the accumulator value after it
+        function revokeBatch(uint64 fromEpoch, bytes32[] deltas, bytes[] newAlphas) external;
+
+        event Checkpoint(uint64 indexed epoch, bytes alpha);
+        event Revoked(uint64 indexed epoch, bytes32 delta, bytes newAlpha);
+    }
+}
+
+pub const G1_LEN: usize = 128;
+
+// Big-endian bytes of a field element, refused unless reduced
+fn field_from_be<F: PrimeField>(bytes: &[u8]) -> Result<F, Box<dyn Error>> {
+    let modulus = F::MODULUS.to_bytes_be();
+    if bytes.len() != modulus.len() || bytes >= &modulus[..] {
+        return Err("not a field element".into());
+    }
+    Ok(F::from_be_bytes_mod_order(bytes))
+}
+
+pub fn g1_to_eip2537(point: &G1Projective) -> Bytes {
+    let mut bytes = vec![0u8; G1_LEN];
+    if let Some((x, y)) = point.into_affine().xy() {
+        bytes[16..64].copy_from_slice(&x.into_bigint().to_bytes_be());
+        bytes[80..128].copy_from_slice(&y.into_bigint().to_bytes_be());
+    }
+    bytes.into()
+}
+
+pub fn g1_from_eip2537(bytes: &[u8]) -> Result<G1Projective, Box<dyn Error>> {
+    if bytes.len() != G1_LEN {
+        return Err(format!("G1 point is {} bytes, expected {}", bytes.len(), G1_LEN).into());
+    }
+    if bytes.iter().all(|b| *b == 0) {
+        return Ok(G1Projective::zero());
+    }
+    let coordinate = |padded: &[u8]| -> Result<Fq, Box<dyn Error>> {
+        if padded[..16].iter().any(|b| *b != 0) {
+            return Err("G1 coordinate is not zero-padded".into());
+        }
+        field_from_be(&padded[16..])
+    };
+    let point = G1Affine::new_unchecked(coordinate(&bytes[..64])?, coordinate(&bytes[64..])?);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err("G1 point is not in the prime order subgroup".into());
+    }
+    Ok(point.into())
+}
+
+pub fn fr_to_bytes32(x: &Fr) -> B256 {
+    B256::from_slice(&x.into_bigint().to_bytes_be())
+}
+
+pub fn fr_from_bytes32(bytes: &B256) -> Result<Fr, Box<dyn Error>> {
+    field_from_be(&bytes.0)
+}
+
+pub fn checkpoint_call(state: &AccumulatorState) -> IRevocationRegistry::publishCheckpointCall {
+    IRevocationRegistry::publishCheckpointCall { epoch: state.epoch, alpha: g1_to_eip2537(&state.alpha) }
+}
+
+// Revocations from_epoch + 1 ..= from_epoch + updates.len(), in order
+pub fn revocations_call(from_epoch: u64, updates: &[AccRevocation]) -> IRevocationRegistry::revokeBatchCall {
+    IRevocationRegistry::revokeBatchCall {
+        fromEpoch: from_epoch,
+        deltas: updates.iter().map(|u| fr_to_bytes32(&u.delta)).collect(),
+        newAlphas: updates.iter().map(|u| g1_to_eip2537(&u.new_alpha)).collect(),
+    }
+}
+
+fn to_u256<F: PrimeField>(f: &F) -> U256 {
+    U256::from_be_slice(&f.into_bigint().to_bytes_be())
+}
+
+pub fn groth16_signature(public_inputs: usize) -> String {
+    format!("verifyProof(uint256[2],uint256[2][2],uint256[2],uint256[{}])", public_inputs)
+}
+
+pub fn selector(signature: &str) -> [u8; 4] {
+    keccak256(signature.as_bytes())[..4].try_into().unwrap()
+}
+
+// All the arguments are static, so the calldata is the selector followed by
+// one word per coordinate and input. Infinity is (0, 0), as in snarkjs
+pub fn groth16_calldata(proof: &Proof<Bn254>, public_inputs: &[ark_bn254::Fr]) -> Vec<u8> {
+    let g1 = |p: &ark_bn254::G1Affine| p.xy().map_or([U256::ZERO; 2], |(x, y)| [to_u256(x), to_u256(y)]);
+    let mut words = g1(&proof.a).to_vec();
+    match proof.b.xy() {
+        Some((x, y)) => words.extend([to_u256(&x.c1), to_u256(&x.c0), to_u256(&y.c1), to_u256(&y.c0)]),
+        None => words.extend([U256::ZERO; 4]),
+    }
+    words.extend(g1(&proof.c));
+    words.extend(public_inputs.iter().map(to_u256));
+
+    let mut calldata = selector(&groth16_signature(public_inputs.len())).to_vec();
+    for word in words {
+        calldata.extend_from_slice(&word.to_be_bytes::<32>());
+    }
+    calldata
+}
+
+// Inverse of groth16_calldata; the points are checked to be on the curve and
+// in the subgroup
+pub fn groth16_from_calldata(calldata: &[u8]) -> Result<(Proof<Bn254>, Vec<ark_bn254::Fr>), Box<dyn Error>> {
+    if calldata.len() < 4 + 8 * 32 || !(calldata.len() - 4).is_multiple_of(32) {
+        return Err("calldata is not a verifyProof call".into());
+    }
+    let words: Vec<&[u8]> = calldata[4..].chunks(32).collect();
+    let inputs = words.len() - 8;
+    if calldata[..4] != selector(&groth16_signature(inputs)) {
+        return Err("calldata is not a verifyProof call".into());
+    }
+    let g1 = |x: &[u8], y: &[u8]| -> Result<ark_bn254::G1Affine, Box<dyn Error>> {
+        let (x, y) = (field_from_be::<ark_bn254::Fq>(x)?, field_from_be::<ark_bn254::Fq>(y)?);
+        if x.is_zero() && y.is_zero() {
+            return Ok(ark_bn254::G1Affine::identity());
+        }
+        let point = ark_bn254::G1Affine::new_unchecked(x, y);
+        if !point.is_on_curve() {
+            return Err("G1 point is not on the curve".into());
+        }
+        Ok(point)
+    };
+    let fq2 = |c1: &[u8], c0: &[u8]| -> Result<ark_bn254::Fq2, Box<dyn Error>> {
+        Ok(ark_bn254::Fq2::new(field_from_be(c0)?, field_from_be(c1)?))
+    };
+    let (bx, by) = (fq2(words[2], words[3])?, fq2(words[4], words[5])?);
+    let b = match bx.is_zero() && by.is_zero() {
+        true => ark_bn254::G2Affine::identity(),
+        false => ark_bn254::G2Affine::new_unchecked(bx, by),
+    };
+    if !b.is_on_curve() || !b.is_in_correct_subgroup_assuming_on_curve() {
+        return Err("G2 point is not in the prime order subgroup".into());
+    }
+    let proof = Proof { a: g1(words[0], words[1])?, b, c: g1(words[6], words[7])? };
+    let public_inputs = words[8..].iter().map(|w| field_from_be(w)).collect::<Result<_, _>>()?;
+    Ok((proof, public_inputs))
+}
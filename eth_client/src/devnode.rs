@@ -0,0 +1,372 @@
+// In-process stand-in for the geth dev node of eth/, for tests and runs
+// without Docker: JSON-RPC over HTTP on a localhost port, one unlocked
+// account, every transaction mined at once in a block of its own.
+//
+// Contract creations run on an EVM (SputnikVM, Shanghai rules), so the
+// solc output of RevocationRegistry.sol (registry::solc_bytecode) deploys
+// the real contract. Next to it are models written in Rust, for builds
+// without solc: the revocation registry (the rules of the contract: issuer
+// only, revocations appended at the current epoch, checkpoints only move
+// forward, with its events), deployed when the init code is
+// REGISTRY_BYTECODE, and a snarkjs Groth16 verifier for a given verifying
+// key. A call that reverts is a JSON-RPC error for eth_call and a receipt
+// with status 0 for a transaction.
+
+use alloy_primitives::{keccak256, Address, Bytes, LogData, B256, U256};
+use alloy_sol_types::{SolCall, SolEvent, SolInterface, SolValue};
+use ark_bn254::Bn254;
+use ark_groth16::{Groth16, VerifyingKey};
+use ark_snark::SNARK;
+use evm::backend::{ApplyBackend, MemoryAccount, MemoryBackend, MemoryVicinity};
+use evm::executor::stack::{MemoryStackState, StackExecutor, StackSubstateMetadata};
+use evm::{Config, CreateScheme, ExitReason};
+use primitive_types::{H160, U256 as EvmU256};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use crate::abi::{self, IRevocationRegistry, IRevocationRegistry::IRevocationRegistryCalls, G1_LEN};
+
+pub const CHAIN_ID: u64 = 15555; // eth/genesis.json
+
+// Stands in for the solc output of RevocationRegistry.sol: init code is this
+// followed by the issuer address, as RevocationRegistry::deploy sends it
+pub const REGISTRY_BYTECODE: &[u8] = b"devnode:RevocationRegistry";
+
+struct Registry {
+    issuer: Address,
+    epoch: u64,
+    alpha: Bytes, // empty until the first checkpoint
+    deltas: Vec<B256>,
+}
+
+enum Contract {
+    Registry(Registry),
+    Groth16Verifier(Box<VerifyingKey<Bn254>>),
+}
+
+const GAS_LIMIT: u64 = 30_000_000;
+
+#[derive(Default)]
+struct Chain {
+    block: u64,
+    contracts: BTreeMap<Address, Contract>, // the Rust models; code on the EVM is in `accounts`
+    accounts: BTreeMap<H160, MemoryAccount>,
+    receipts: BTreeMap<B256, Value>,
+    logs: Vec<(Address, LogData)>,
+}
+
+pub struct DevNode {
+    url: String,
+    account: Address,
+    chain: Arc<Mutex<Chain>>,
+}
+
+// Length and padding, as the contract checks them
+fn valid_alpha(alpha: &[u8]) -> bool {
+    alpha.len() == G1_LEN && alpha[..16].iter().chain(&alpha[64..80]).all(|b| *b == 0)
+}
+
+impl Registry {
+    // Ok(output) or Err(revert reason); `write` applies state changes and
+    // appends the events to `logs`
+    fn execute(&mut self, from: Address, calldata: &[u8], write: bool, logs: &mut Vec<LogData>) -> Result<Vec<u8>, String> {
+        let call = IRevocationRegistryCalls::abi_decode(calldata).map_err(|e| e.to_string())?;
+        match call {
+            IRevocationRegistryCalls::issuer(_) => Ok(IRevocationRegistry::issuerCall::abi_encode_returns(&self.issuer)),
+            IRevocationRegistryCalls::epoch(_) => Ok(IRevocationRegistry::epochCall::abi_encode_returns(&self.epoch)),
+            IRevocationRegistryCalls::alpha(_) => Ok(IRevocationRegistry::alphaCall::abi_encode_returns(&self.alpha)),
+            IRevocationRegistryCalls::alphaHash(_) => {
+                // the contract's slot is zero until the first checkpoint
+                let hash = if self.alpha.is_empty() { B256::ZERO } else { keccak256(&self.alpha) };
+                Ok(IRevocationRegistry::alphaHashCall::abi_encode_returns(&hash))
+            }
+            IRevocationRegistryCalls::deltas(call) => {
+                let delta = usize::try_from(call.index).ok().and_then(|i| self.deltas.get(i)).ok_or("index out of bounds")?;
+                Ok(IRevocationRegistry::deltasCall::abi_encode_returns(delta))
+            }
+            IRevocationRegistryCalls::deltaCount(_) => Ok(IRevocationRegistry::deltaCountCall::abi_encode_returns(&U256::from(self.deltas.len()))),
+            IRevocationRegistryCalls::publishCheckpoint(call) => {
+                if from != self.issuer {
+                    return Err("not the issuer".into());
+                }
+                if !valid_alpha(&call.alpha) {
+                    return Err("bad alpha".into());
+                }
+                if !self.alpha.is_empty() && call.epoch <= self.epoch {
+                    return Err("stale checkpoint".into());
+                }
+                if write {
+                    logs.push(IRevocationRegistry::Checkpoint { epoch: call.epoch, alpha: call.alpha.clone() }.encode_log_data());
+                    self.epoch = call.epoch;
+                    self.alpha = call.alpha;
+                }
+                Ok(Vec::new())
+            }
+            IRevocationRegistryCalls::revokeBatch(call) => {
+                if from != self.issuer {
+                    return Err("not the issuer".into());
+                }
+                if self.alpha.is_empty() || call.fromEpoch != self.epoch {
+                    return Err("wrong epoch".into());
+                }
+                if call.deltas.is_empty() || call.deltas.len() != call.newAlphas.len() {
+                    return Err("bad batch".into());
+                }
+                if !call.newAlphas.iter().all(|alpha| valid_alpha(alpha)) {
+                    return Err("bad alpha".into());
+                }
+                if write {
+                    for (i, (delta, alpha)) in call.deltas.iter().zip(&call.newAlphas).enumerate() {
+                        let epoch = call.fromEpoch + i as u64 + 1;
+                        logs.push(IRevocationRegistry::Revoked { epoch, delta: *delta, newAlpha: alpha.clone() }.encode_log_data());
+                    }
+                    self.epoch += call.deltas.len() as u64;
+                    self.alpha = call.newAlphas.last().unwrap().clone();
+                    self.deltas.extend(call.deltas);
+                }
+                Ok(Vec::new())
+            }
+        }
+    }
+}
+
+// Like the snarkjs verifier: false for a proof that does not verify or does
+// not decode, never a revert
+fn verify_groth16(vk: &VerifyingKey<Bn254>, calldata: &[u8]) -> Vec<u8> {
+    let valid = match abi::groth16_from_calldata(calldata) {
+        Ok((proof, inputs)) => inputs.len() + 1 == vk.gamma_abc_g1.len() && Groth16::<Bn254>::verify(vk, &inputs, &proof).unwrap_or(false),
+        Err(_) => false,
+    };
+    let mut word = vec![0u8; 32];
+    word[31] = valid as u8;
+    word
+}
+
+impl Chain {
+    fn execute(&mut self, from: Address, to: Address, calldata: &[u8], write: bool) -> Result<Vec<u8>, String> {
+        let mut logs = Vec::new();
+        let output = match self.contracts.get_mut(&to) {
+            Some(Contract::Registry(registry)) => registry.execute(from, calldata, write, &mut logs)?,
+            Some(Contract::Groth16Verifier(vk)) => verify_groth16(vk, calldata),
+            None => return self.run_evm(from, Some(to), calldata, write).map(|output| output.0),
+        };
+        self.logs.extend(logs.into_iter().map(|log| (to, log)));
+        Ok(output)
+    }
+
+    fn create(&mut self, from: Address, init_code: &[u8]) -> Result<Address, String> {
+        match init_code.strip_prefix(REGISTRY_BYTECODE) {
+            Some(args) => {
+                let issuer = Address::abi_decode(args).map_err(|e| e.to_string())?;
+                Ok(self.deploy(Contract::Registry(Registry { issuer, epoch: 0, alpha: Bytes::new(), deltas: Vec::new() })))
+            }
+            None => self.run_evm(from, None, init_code, true).map(|output| output.1),
+        }
+    }
+
+    // Output and the address called or created, or Err(revert data or the
+    // exit reason). `write` keeps the state changes and the logs
+    fn run_evm(&mut self, from: Address, to: Option<Address>, data: &[u8], write: bool) -> Result<(Vec<u8>, Address), String> {
+        let config = Config::shanghai();
+        let vicinity = MemoryVicinity {
+            gas_price: EvmU256::zero(),
+            origin: H160(from.into()),
+            chain_id: CHAIN_ID.into(),
+            block_hashes: Vec::new(),
+            block_number: self.block.into(),
+            block_coinbase: H160::zero(),
+            block_timestamp: self.block.into(),
+            block_difficulty: EvmU256::zero(),
+            block_randomness: None,
+            block_gas_limit: GAS_LIMIT.into(),
+            block_base_fee_per_gas: EvmU256::zero(),
+        };
+        let mut backend = MemoryBackend::new(&vicinity, self.accounts.clone());
+        let state = MemoryStackState::new(StackSubstateMetadata::new(GAS_LIMIT, &config), &mut backend);
+        let mut executor = StackExecutor::new_with_precompiles(state, &config, &());
+        let (address, (reason, output)) = match to {
+            Some(to) => (to, executor.transact_call(H160(from.into()), H160(to.into()), EvmU256::zero(), data.to_vec(), GAS_LIMIT, Vec::new())),
+            None => {
+                let created = executor.create_address(CreateScheme::Legacy { caller: H160(from.into()) });
+                (Address::from(created.0), executor.transact_create(H160(from.into()), EvmU256::zero(), data.to_vec(), GAS_LIMIT, Vec::new()))
+            }
+        };
+        match reason {
+            ExitReason::Succeed(_) => {}
+            ExitReason::Revert(_) => return Err(format!("0x{}", hex::encode(output))),
+            other => return Err(format!("{:?}", other)),
+        }
+        if write {
+            let (values, logs) = executor.into_state().deconstruct();
+            let logs: Vec<_> = logs.into_iter().collect();
+            self.logs.extend(logs.iter().map(|log| {
+                let topics = log.topics.iter().map(|topic| B256::from(topic.0)).collect();
+                (Address::from(log.address.0), LogData::new_unchecked(topics, log.data.clone().into()))
+            }));
+            backend.apply(values, logs, false);
+            self.accounts = backend.state().clone();
+        }
+        Ok((output, address))
+    }
+
+    fn deploy(&mut self, contract: Contract) -> Address {
+        let address = Address::from_slice(&keccak256((self.contracts.len() as u64).to_be_bytes())[12..]);
+        self.contracts.insert(address, contract);
+        address
+    }
+}
+
+fn field<T: FromStr>(value: &Value, name: &str) -> Result<T, String>
+where
+    T::Err: Display,
+{
+    let text = value.get(name).and_then(Value::as_str).ok_or_else(|| format!("missing {}", name))?;
+    text.parse().map_err(|e: T::Err| format!("{}: {}", name, e))
+}
+
+fn calldata(tx: &Value) -> Result<Vec<u8>, String> {
+    hex::decode(field::<String>(tx, "data")?.trim_start_matches("0x")).map_err(|e| e.to_string())
+}
+
+fn handle_rpc(chain: &Mutex<Chain>, account: Address, method: &str, params: &Value) -> Result<Value, String> {
+    let mut chain = chain.lock().unwrap();
+    let tx = &params[0];
+    match method {
+        "eth_chainId" => Ok(json!(format!("{:#x}", CHAIN_ID))),
+        "eth_accounts" => Ok(json!([account])),
+        "eth_blockNumber" => Ok(json!(format!("{:#x}", chain.block))),
+        "eth_call" => {
+            let from = if tx.get("from").is_some() { field(tx, "from")? } else { Address::ZERO };
+            let output = chain.execute(from, field(tx, "to")?, &calldata(tx)?, false).map_err(|reason| format!("execution reverted: {}", reason))?;
+            Ok(json!(format!("0x{}", hex::encode(output))))
+        }
+        "eth_sendTransaction" => {
+            let from: Address = field(tx, "from")?;
+            if from != account {
+                return Err(format!("unknown account {}", from));
+            }
+            let calldata = calldata(tx)?;
+            let (status, created) = match tx.get("to") {
+                Some(_) => (chain.execute(from, field(tx, "to")?, &calldata, true).is_ok(), None),
+                None => match chain.create(from, &calldata) {
+                    Ok(address) => (true, Some(address)),
+                    Err(_) => (false, None),
+                },
+            };
+            chain.block += 1;
+            let hash = keccak256([&chain.block.to_be_bytes()[..], &calldata].concat());
+            let receipt = json!({
+                "transactionHash": hash,
+                "blockNumber": format!("{:#x}", chain.block),
+                "gasUsed": format!("{:#x}", 21000 + 16 * calldata.len()),
+                "status": if status { "0x1" } else { "0x0" },
+                "contractAddress": created,
+            });
+            chain.receipts.insert(hash, receipt);
+            Ok(json!(hash))
+        }
+        "eth_getTransactionReceipt" => {
+            let hash: B256 = params[0].as_str().ok_or("missing hash")?.parse().map_err(|e| format!("hash: {}", e))?;
+            Ok(chain.receipts.get(&hash).cloned().unwrap_or(Value::Null))
+        }
+        // Filters on address and first topic only; the block range is ignored
+        "eth_getLogs" => {
+            let address: Address = field(tx, "address")?;
+            let topic0: Option<B256> = tx["topics"][0].as_str().map(str::parse).transpose().map_err(|e| format!("topics: {}", e))?;
+            let logs: Vec<Value> = chain
+                .logs
+                .iter()
+                .filter(|(emitter, log)| *emitter == address && topic0.is_none_or(|t| log.topics().first() == Some(&t)))
+                .map(|(emitter, log)| json!({ "address": emitter, "topics": log.topics(), "data": format!("0x{}", hex::encode(&log.data)) }))
+                .collect();
+            Ok(json!(logs))
+        }
+        _ => Err(format!("the method {} does not exist/is not available", method)),
+    }
+}
+
+// One JSON-RPC request per connection
+fn serve(chain: &Mutex<Chain>, account: Address, stream: TcpStream) -> Result<(), Box<dyn Error>> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut length = 0;
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse()?;
+            }
+        }
+    }
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body)?;
+    let request: Value = serde_json::from_slice(&body)?;
+    let method = request["method"].as_str().unwrap_or_default();
+    let response = match handle_rpc(chain, account, method, &request["params"]) {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": request["id"], "result": result }),
+        Err(message) => json!({ "jsonrpc": "2.0", "id": request["id"], "error": { "code": -32000, "message": message } }),
+    }
+    .to_string();
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.len(),
+        response
+    )?;
+    Ok(())
+}
+
+impl DevNode {
+    // Listens on a free localhost port until the process exits
+    pub fn start() -> Result<DevNode, Box<dyn Error>> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let account = Address::from_slice(&keccak256(b"vac devnode account")[12..]);
+        let chain = Arc::new(Mutex::new(Chain::default()));
+        let shared = chain.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = serve(&shared, account, stream);
+            }
+        });
+        Ok(DevNode { url, account, chain })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    // The unlocked account, eth_accounts[0]
+    pub fn account(&self) -> Address {
+        self.account
+    }
+
+    pub fn deploy_registry(&self, issuer: Address) -> Address {
+        let registry = Registry { issuer, epoch: 0, alpha: Bytes::new(), deltas: Vec::new() };
+        self.chain.lock().unwrap().deploy(Contract::Registry(registry))
+    }
+
+    pub fn deploy_groth16_verifier(&self, vk: VerifyingKey<Bn254>) -> Address {
+        self.chain.lock().unwrap().deploy(Contract::Groth16Verifier(Box::new(vk)))
+    }
+
+    // Revoked deltas the registry at `address` holds, in order
+    pub fn registry_deltas(&self, address: Address) -> Vec<B256> {
+        match self.chain.lock().unwrap().contracts.get(&address) {
+            Some(Contract::Registry(registry)) => registry.deltas.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
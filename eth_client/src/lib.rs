@@ -0,0 +1,15 @@
+// Publishing accumulator state to Ethereum and submitting Groth16 proofs,
+// over the node's JSON-RPC:
+//
+//   abi       ABI encoding of accumulator values, revocation batches and
+//             BN254 Groth16 proofs; the registry contract interface
+//   rpc       EthClient: calls, transactions, receipts, contract creation
+//             and event logs
+//   registry  bindings for the revocation registry contract in
+//             eth/contracts: issuer writes, verifier and holder reads
+//   devnode   a local stand-in for the geth dev node, for tests
+
+pub mod abi;
+pub mod devnode;
+pub mod registry;
+pub mod rpc;
@@ -0,0 +1,165 @@
+// Bindings for eth/contracts/RevocationRegistry.sol: one issuer's
+// accumulator on chain. The issuer writes through it (checkpoints,
+// revocations as Issuer::revoke_a_cred returns them, or a whole log at
+// once); verifiers read the authoritative epoch and alpha; holders read the
+// revocations since their witness's epoch from the Revoked events.
+
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::{SolCall, SolEvent, SolValue};
+use ark_bls12_381::{Fr, G1Projective};
+use std::error::Error;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::presentation::AccumulatorState;
+use accumulator_impl::revocation::AccRevocation;
+
+use crate::abi::{self, IRevocationRegistry};
+use crate::rpc::{EthClient, Receipt};
+
+// Revocations per revokeBatch transaction, well under the block gas limit
+pub const MAX_BATCH: usize = 64;
+
+// Init code of RevocationRegistry.sol as solc --bin writes it, if build.rs
+// found solc
+pub fn solc_bytecode() -> Option<Vec<u8>> {
+    option_env!("REGISTRY_SOLC_BIN").map(|bin| hex::decode(bin).expect("solc writes hex"))
+}
+
+pub struct RevocationRegistry<'a> {
+    client: &'a EthClient,
+    address: Address,
+}
+
+impl<'a> RevocationRegistry<'a> {
+    pub fn at(client: &'a EthClient, address: Address) -> Self {
+        RevocationRegistry { client, address }
+    }
+
+    // Deploys the contract for `issuer`; bytecode is the output of
+    // solc --bin for RevocationRegistry.sol
+    pub fn deploy(client: &'a EthClient, bytecode: &[u8], issuer: Address) -> Result<Self, Box<dyn Error>> {
+        let init_code = [bytecode, &issuer.abi_encode()].concat();
+        let (address, _) = client.deploy(&init_code)?;
+        Ok(RevocationRegistry::at(client, address))
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    fn view<C: SolCall>(&self, call: C) -> Result<C::Return, Box<dyn Error>> {
+        Ok(C::abi_decode_returns(&self.client.call(self.address, &call.abi_encode())?)?)
+    }
+
+    pub fn issuer(&self) -> Result<Address, Box<dyn Error>> {
+        self.view(IRevocationRegistry::issuerCall {})
+    }
+
+    pub fn epoch(&self) -> Result<u64, Box<dyn Error>> {
+        self.view(IRevocationRegistry::epochCall {})
+    }
+
+    // The accumulator as of the latest block; an error before the first
+    // checkpoint
+    pub fn state(&self) -> Result<AccumulatorState, Box<dyn Error>> {
+        let alpha = self.view(IRevocationRegistry::alphaCall {})?;
+        if alpha.is_empty() {
+            return Err("the registry has no checkpoint yet".into());
+        }
+        Ok(AccumulatorState { epoch: self.epoch()?, alpha: abi::g1_from_eip2537(&alpha)? })
+    }
+
+    // Every revoked delta, in order
+    pub fn deltas(&self) -> Result<Vec<Fr>, Box<dyn Error>> {
+        let count: u64 = self.view(IRevocationRegistry::deltaCountCall {})?.try_into()?;
+        (0..count).map(|i| abi::fr_from_bytes32(&self.view(IRevocationRegistry::deltasCall { index: U256::from(i) })?)).collect()
+    }
+
+    // The revocations after `since`, as (epoch, update), from the Revoked
+    // events: what a holder whose witness is at epoch `since` applies with
+    // Holder::update_witness. Empty if the registry has no revocations past it.
+    // An error if a checkpoint was published after `since`: it set alpha
+    // outright, no update gets a witness across it and the holder has to be
+    // given a new one
+    pub fn revocations_since(&self, since: u64) -> Result<Vec<(u64, AccRevocation)>, Box<dyn Error>> {
+        for log in self.client.logs(self.address, IRevocationRegistry::Checkpoint::SIGNATURE_HASH)? {
+            let event = IRevocationRegistry::Checkpoint::decode_log_data(&log)?;
+            if event.epoch > since {
+                return Err(format!("checkpoint at epoch {} after epoch {}: the witness needs a resync with the issuer", event.epoch, since).into());
+            }
+        }
+        let mut revocations = Vec::new();
+        for log in self.client.logs(self.address, IRevocationRegistry::Revoked::SIGNATURE_HASH)? {
+            let event = IRevocationRegistry::Revoked::decode_log_data(&log)?;
+            if event.epoch > since {
+                let update = AccRevocation { delta: abi::fr_from_bytes32(&event.delta)?, new_alpha: abi::g1_from_eip2537(&event.newAlpha)? };
+                revocations.push((event.epoch, update));
+            }
+        }
+        Ok(revocations)
+    }
+
+    pub fn publish_checkpoint(&self, state: &AccumulatorState) -> Result<Receipt, Box<dyn Error>> {
+        self.client.transact(self.address, &abi::checkpoint_call(state).abi_encode())
+    }
+
+    // Revocations from_epoch + 1 ..= from_epoch + updates.len(); from_epoch
+    // must be the on-chain epoch
+    pub fn publish_revocations(&self, from_epoch: u64, updates: &[AccRevocation]) -> Result<Receipt, Box<dyn Error>> {
+        self.client.transact(self.address, &abi::revocations_call(from_epoch, updates).abi_encode())
+    }
+
+    // Revokes x in the issuer's accumulator and publishes the update. The
+    // registry must be at the accumulator's epoch. If the transaction fails
+    // the accumulator is revoked all the same: the error says so, and sync
+    // with the issuer's log catches the registry up
+    pub fn revoke(&self, issuer: &Issuer, accumulator: &mut ECAccumulator, x: &Fr) -> Result<AccRevocation, Box<dyn Error>> {
+        let from_epoch = accumulator.get_epoch();
+        let on_chain = self.epoch()?;
+        if on_chain != from_epoch {
+            return Err(format!("the registry is at epoch {} and the accumulator at {}: sync first", on_chain, from_epoch).into());
+        }
+        let update = issuer.revoke_handle(accumulator, x).map_err(|e| format!("revocation failed: {:?}", e))?;
+        self.publish_revocations(from_epoch, std::slice::from_ref(&update))
+            .map_err(|e| format!("revoked at epoch {} but not published: {}", from_epoch + 1, e))?;
+        Ok(update)
+    }
+
+    // Brings the registry up to the issuer's revocation log, (epoch, update)
+    // in epoch order as AccumulatorFile::log gives it: reads the on-chain
+    // epoch and sends what comes after it, MAX_BATCH at a time. Returns the
+    // epoch the registry ends at
+    pub fn sync(&self, log: &[(u64, AccRevocation)]) -> Result<u64, Box<dyn Error>> {
+        let mut on_chain = self.epoch()?;
+        for batch in pending_revocations(on_chain, log)?.chunks(MAX_BATCH) {
+            let updates: Vec<AccRevocation> = batch.iter().map(|(_, update)| update.clone()).collect();
+            self.publish_revocations(on_chain, &updates)?;
+            on_chain += updates.len() as u64;
+        }
+        Ok(on_chain)
+    }
+
+    // Whether the chain holds this accumulator value at this epoch
+    pub fn matches(&self, epoch: u64, alpha: &G1Projective) -> Result<bool, Box<dyn Error>> {
+        let state = self.state()?;
+        Ok(state.epoch == epoch && state.alpha == *alpha)
+    }
+}
+
+// The part of the log the chain has not seen. An on-chain epoch past the end
+// of the log, or a log with gaps, means the chain and the issuer disagree
+pub fn pending_revocations(on_chain: u64, log: &[(u64, AccRevocation)]) -> Result<&[(u64, AccRevocation)], Box<dyn Error>> {
+    let last = log.last().map_or(0, |(epoch, _)| *epoch);
+    if on_chain > last {
+        return Err(format!("the registry is at epoch {} but the issuer's log ends at {}", on_chain, last).into());
+    }
+    let start = log.partition_point(|(epoch, _)| *epoch <= on_chain);
+    let pending = &log[start..];
+    for (i, (epoch, _)) in pending.iter().enumerate() {
+        if *epoch != on_chain + 1 + i as u64 {
+            return Err(format!("the issuer's log has no revocation for epoch {}", on_chain + 1 + i as u64).into());
+        }
+    }
+    Ok(pending)
+}
@@ -0,0 +1,160 @@
+// JSON-RPC client for the geth node in eth/ (or any node that signs for its
+// unlocked accounts): transactions go out with eth_sendTransaction from the
+// account given at construction, as the scripts in eth/scripts do, so no
+// private key is handled here.
+
+use alloy_primitives::{Address, LogData, B256};
+use ark_bn254::Bn254;
+use ark_groth16::Proof;
+use serde_json::{json, Value};
+use std::cell::Cell;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+use crate::abi;
+
+const RECEIPT_POLL: Duration = Duration::from_millis(500);
+const RECEIPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Receipt {
+    pub transaction_hash: B256,
+    pub block_number: u64,
+    pub gas_used: u64,
+    pub contract_address: Option<Address>, // for a contract creation
+}
+
+pub struct EthClient {
+    url: String,
+    from: Address,
+    agent: ureq::Agent,
+    next_id: Cell<u64>,
+}
+
+fn quantity(value: &Value) -> Result<u64, Box<dyn Error>> {
+    let text = value.as_str().ok_or_else(|| format!("{} is not a quantity", value))?;
+    Ok(u64::from_str_radix(text.trim_start_matches("0x"), 16)?)
+}
+
+fn data(value: &Value) -> Result<Vec<u8>, Box<dyn Error>> {
+    let text = value.as_str().ok_or_else(|| format!("{} is not hex data", value))?;
+    Ok(hex::decode(text.trim_start_matches("0x"))?)
+}
+
+fn hex_data(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+impl EthClient {
+    pub fn new(url: &str, from: Address) -> Self {
+        EthClient { url: url.to_string(), from, agent: ureq::Agent::new(), next_id: Cell::new(1) }
+    }
+
+    // Sends from the node's first account, the funded coinbase of the dev setup
+    pub fn with_node_account(url: &str) -> Result<Self, Box<dyn Error>> {
+        let client = EthClient::new(url, Address::ZERO);
+        let accounts = client.request("eth_accounts", json!([]))?;
+        let first = accounts.get(0).and_then(Value::as_str).ok_or("the node has no accounts")?;
+        Ok(EthClient { from: first.parse()?, ..client })
+    }
+
+    pub fn from(&self) -> Address {
+        self.from
+    }
+
+    pub fn request(&self, method: &str, params: Value) -> Result<Value, Box<dyn Error>> {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        let body = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let response: Value = self.agent.post(&self.url).send_json(body)?.into_json()?;
+        if let Some(error) = response.get("error") {
+            let message = error.get("message").and_then(Value::as_str).map_or_else(|| error.to_string(), str::to_string);
+            return Err(format!("{}: {}", method, message).into());
+        }
+        response.get("result").cloned().ok_or_else(|| format!("{}: response has no result", method).into())
+    }
+
+    pub fn chain_id(&self) -> Result<u64, Box<dyn Error>> {
+        quantity(&self.request("eth_chainId", json!([]))?)
+    }
+
+    pub fn block_number(&self) -> Result<u64, Box<dyn Error>> {
+        quantity(&self.request("eth_blockNumber", json!([]))?)
+    }
+
+    // eth_call against the latest block
+    pub fn call(&self, to: Address, calldata: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+        let tx = json!({ "from": self.from, "to": to, "data": hex_data(calldata) });
+        data(&self.request("eth_call", json!([tx, "latest"]))?)
+    }
+
+    pub fn send_transaction(&self, to: Address, calldata: &[u8]) -> Result<B256, Box<dyn Error>> {
+        self.send(json!({ "from": self.from, "to": to, "data": hex_data(calldata) }))
+    }
+
+    fn send(&self, tx: Value) -> Result<B256, Box<dyn Error>> {
+        Ok(self.request("eth_sendTransaction", json!([tx]))?.as_str().ok_or("transaction hash missing")?.parse()?)
+    }
+
+    // Waits for the transaction to be mined; a reverted one is an error
+    pub fn wait_for_receipt(&self, tx: B256) -> Result<Receipt, Box<dyn Error>> {
+        let start = Instant::now();
+        loop {
+            let receipt = self.request("eth_getTransactionReceipt", json!([tx]))?;
+            if !receipt.is_null() {
+                if quantity(&receipt["status"])? != 1 {
+                    return Err(format!("transaction {} reverted", tx).into());
+                }
+                return Ok(Receipt {
+                    transaction_hash: tx,
+                    block_number: quantity(&receipt["blockNumber"])?,
+                    gas_used: quantity(&receipt["gasUsed"])?,
+                    contract_address: receipt["contractAddress"].as_str().map(str::parse).transpose()?,
+                });
+            }
+            if start.elapsed() > RECEIPT_TIMEOUT {
+                return Err(format!("transaction {} not mined after {:?}", tx, RECEIPT_TIMEOUT).into());
+            }
+            std::thread::sleep(RECEIPT_POLL);
+        }
+    }
+
+    pub fn transact(&self, to: Address, calldata: &[u8]) -> Result<Receipt, Box<dyn Error>> {
+        self.wait_for_receipt(self.send_transaction(to, calldata)?)
+    }
+
+    // Creates a contract: init code is the compiled bytecode (solc --bin)
+    // followed by the ABI-encoded constructor arguments
+    pub fn deploy(&self, init_code: &[u8]) -> Result<(Address, Receipt), Box<dyn Error>> {
+        let receipt = self.wait_for_receipt(self.send(json!({ "from": self.from, "data": hex_data(init_code) }))?)?;
+        let address = receipt.contract_address.ok_or("the receipt has no contract address")?;
+        Ok((address, receipt))
+    }
+
+    // Logs of `address` with the given first topic (the event signature hash),
+    // oldest first
+    pub fn logs(&self, address: Address, topic0: B256) -> Result<Vec<LogData>, Box<dyn Error>> {
+        let filter = json!({ "address": address, "topics": [topic0], "fromBlock": "0x0", "toBlock": "latest" });
+        let logs = self.request("eth_getLogs", json!([filter]))?;
+        let logs = logs.as_array().ok_or("eth_getLogs: not a list")?;
+        logs.iter()
+            .map(|log| {
+                let topics = log["topics"].as_array().ok_or("log has no topics")?;
+                let topics = topics.iter().map(|t| Ok(t.as_str().ok_or("bad topic")?.parse()?)).collect::<Result<Vec<B256>, Box<dyn Error>>>()?;
+                Ok(LogData::new_unchecked(topics, data(&log["data"])?.into()))
+            })
+            .collect()
+    }
+
+    // Sends the proof to a snarkjs-exported verifier as a transaction, which
+    // records the verification (and its gas) on chain. The verifier does not
+    // revert on a wrong proof: check it with verify_groth16 first
+    pub fn submit_groth16(&self, verifier: Address, proof: &Proof<Bn254>, public_inputs: &[ark_bn254::Fr]) -> Result<Receipt, Box<dyn Error>> {
+        self.transact(verifier, &abi::groth16_calldata(proof, public_inputs))
+    }
+
+    pub fn verify_groth16(&self, verifier: Address, proof: &Proof<Bn254>, public_inputs: &[ark_bn254::Fr]) -> Result<bool, Box<dyn Error>> {
+        let output = self.call(verifier, &abi::groth16_calldata(proof, public_inputs))?;
+        Ok(output.len() == 32 && output[31] == 1 && output[..31].iter().all(|b| *b == 0))
+    }
+}
@@ -0,0 +1,237 @@
+use alloy_primitives::{Address, B256, U256};
+use alloy_sol_types::SolCall;
+use ark_bls12_381::{Fr, G1Projective};
+use ark_ff::UniformRand;
+use ark_groth16::Groth16;
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use ark_std::Zero;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::holder::Holder;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::presentation::AccumulatorState;
+use accumulator_impl::revocation::{AccRevocation, RevocationScheme};
+use accumulator_impl::transcript::{new_transcript, MEMBERSHIP_PROOF};
+use eth_client::abi::*;
+use eth_client::devnode::{DevNode, REGISTRY_BYTECODE};
+use eth_client::registry::{pending_revocations, solc_bytecode, RevocationRegistry};
+use eth_client::rpc::EthClient;
+use prover::circuit::CubeCircuit;
+
+// Revokes `count` fresh credentials; returns the log as (epoch, update)
+fn revoke(issuer: &Issuer, acc: &mut ECAccumulator, count: usize) -> Vec<(u64, AccRevocation)> {
+    let mut rng = StdRng::seed_from_u64(7);
+    (0..count)
+        .map(|_| {
+            let cred = issuer.gen_witness_n_cred(&*acc, (0..5).map(|_| Fr::rand(&mut rng)).collect());
+            let update = issuer.revoke_a_cred(acc, &cred).unwrap();
+            (acc.get_epoch(), update)
+        })
+        .collect()
+}
+
+#[test]
+fn encodings_roundtrip() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for point in [G1Projective::rand(&mut rng), G1Projective::zero()] {
+        let bytes = g1_to_eip2537(&point);
+        assert_eq!(bytes.len(), G1_LEN);
+        assert_eq!(g1_from_eip2537(&bytes).unwrap(), point);
+    }
+    let mut bad = g1_to_eip2537(&G1Projective::rand(&mut rng)).to_vec();
+    bad[127] ^= 1;
+    assert!(g1_from_eip2537(&bad).is_err());
+    bad[0] = 1;
+    assert!(g1_from_eip2537(&bad).is_err());
+
+    let x = Fr::rand(&mut rng);
+    assert_eq!(fr_from_bytes32(&fr_to_bytes32(&x)).unwrap(), x);
+    assert!(fr_from_bytes32(&[0xff; 32].into()).is_err());
+
+    let issuer = Issuer::new();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let log = revoke(&issuer, &mut acc, 3);
+    let updates: Vec<AccRevocation> = log.iter().map(|(_, u)| u.clone()).collect();
+    let call = IRevocationRegistry::revokeBatchCall::abi_decode(&revocations_call(0, &updates).abi_encode()).unwrap();
+    assert_eq!(call.fromEpoch, 0);
+    assert_eq!(fr_from_bytes32(&call.deltas[2]).unwrap(), updates[2].delta);
+    assert_eq!(g1_from_eip2537(&call.newAlphas[2]).unwrap(), *acc.get_alpha());
+}
+
+#[test]
+fn pending_revocations_reconcile_with_the_chain() {
+    let issuer = Issuer::new();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let log = revoke(&issuer, &mut acc, 4);
+
+    assert_eq!(pending_revocations(0, &log).unwrap().len(), 4);
+    assert_eq!(pending_revocations(3, &log).unwrap()[0].0, 4);
+    assert!(pending_revocations(4, &log).unwrap().is_empty());
+    assert!(pending_revocations(5, &log).unwrap_err().to_string().contains("epoch 5"));
+    let gap = [&log[..1], &log[2..]].concat();
+    assert!(pending_revocations(0, &gap).is_err());
+}
+
+// The rules of RevocationRegistry.sol, for `registry` of the client's
+// account and `others` of another issuer; returns the deltas published
+fn publish_and_sync(client: &EthClient, registry: &RevocationRegistry, others: &RevocationRegistry) -> Vec<Fr> {
+    let issuer = Issuer::new();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    assert!(registry.state().is_err());
+    registry.publish_checkpoint(&AccumulatorState::from(&acc)).unwrap();
+    assert_eq!(registry.epoch().unwrap(), 0);
+
+    let log = revoke(&issuer, &mut acc, 3);
+    assert_eq!(registry.sync(&log[..2]).unwrap(), 2);
+    let block = client.block_number().unwrap();
+    // only the revocation the chain has not seen goes out
+    assert_eq!(registry.sync(&log).unwrap(), 3);
+    assert_eq!(client.block_number().unwrap(), block + 1);
+    assert_eq!(registry.sync(&log).unwrap(), 3);
+    assert_eq!(client.block_number().unwrap(), block + 1);
+
+    assert_eq!(registry.state().unwrap(), AccumulatorState::from(&acc));
+    assert!(registry.matches(3, acc.get_alpha()).unwrap());
+    assert!(!registry.matches(2, acc.get_alpha()).unwrap());
+    let deltas: Vec<Fr> = log.iter().map(|(_, u)| u.delta).collect();
+    assert_eq!(registry.deltas().unwrap(), deltas);
+
+    // the registry reverts out-of-order batches and other senders
+    let stale = revocations_call(1, &[log[2].1.clone()]).abi_encode();
+    assert!(client.transact(registry.address(), &stale).unwrap_err().to_string().contains("reverted"));
+    assert!(others.publish_checkpoint(&AccumulatorState::from(&acc)).unwrap_err().to_string().contains("reverted"));
+    assert!(registry.sync(&log[..2]).is_err());
+    deltas
+}
+
+#[test]
+fn publish_and_sync_the_registry() {
+    let node = DevNode::start().unwrap();
+    let client = EthClient::with_node_account(node.url()).unwrap();
+    assert_eq!(client.from(), node.account());
+    assert_eq!(client.chain_id().unwrap(), 15555);
+    let registry = RevocationRegistry::at(&client, node.deploy_registry(client.from()));
+    let others = RevocationRegistry::at(&client, node.deploy_registry(Address::repeat_byte(1)));
+
+    let deltas = publish_and_sync(&client, &registry, &others);
+    let from_node: Vec<Fr> = node.registry_deltas(registry.address()).iter().map(|d| fr_from_bytes32(d).unwrap()).collect();
+    assert_eq!(from_node, deltas);
+}
+
+#[test]
+#[ignore = "needs solc at build time (SOLC or solc on PATH); run with --ignored"]
+fn solidity_registry_on_the_evm() {
+    let bytecode = solc_bytecode().expect("solc was not found at build time, RevocationRegistry.sol was not compiled");
+    let node = DevNode::start().unwrap();
+    let client = EthClient::with_node_account(node.url()).unwrap();
+    let registry = RevocationRegistry::deploy(&client, &bytecode, client.from()).unwrap();
+    assert_eq!(registry.issuer().unwrap(), client.from());
+    let others = RevocationRegistry::deploy(&client, &bytecode, Address::repeat_byte(1)).unwrap();
+    publish_and_sync(&client, &registry, &others);
+
+    let issuer = Issuer::new();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let mut rng = StdRng::seed_from_u64(2);
+    let cred = issuer.gen_witness_n_cred(&acc, (0..5).map(|_| Fr::rand(&mut rng)).collect());
+    let fresh = RevocationRegistry::deploy(&client, &bytecode, client.from()).unwrap();
+    fresh.publish_checkpoint(&AccumulatorState::from(&acc)).unwrap();
+    let update = fresh.revoke(&issuer, &mut acc, cred.get_x_val()).unwrap();
+    assert_eq!(fresh.revocations_since(0).unwrap(), vec![(1, update)]);
+    fresh.publish_checkpoint(&AccumulatorState::from(&acc)).unwrap();
+    assert!(fresh.revocations_since(0).unwrap_err().to_string().contains("checkpoint at epoch 1"));
+}
+
+#[test]
+fn evm_contracts_run_on_the_devnode() {
+    // copies the 28-byte runtime behind it and returns it; the runtime
+    // reverts on empty calldata, and otherwise logs and returns 42
+    let init_code = hex::decode("601c600c600039601c6000f3").unwrap();
+    let runtime = hex::decode("3615601657602a600052600160206000a160206000f35b60006000fd").unwrap();
+    let node = DevNode::start().unwrap();
+    let client = EthClient::with_node_account(node.url()).unwrap();
+    let (contract, _) = client.deploy(&[init_code, runtime].concat()).unwrap();
+
+    let answer = U256::from(42).to_be_bytes_vec();
+    assert_eq!(client.call(contract, b"?").unwrap(), answer);
+    assert!(client.logs(contract, B256::with_last_byte(1)).unwrap().is_empty());
+    let block = client.block_number().unwrap();
+    assert_eq!(client.transact(contract, b"?").unwrap().block_number, block + 1);
+    let logs = client.logs(contract, B256::with_last_byte(1)).unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].data.to_vec(), answer);
+
+    assert!(client.call(contract, &[]).is_err());
+    assert!(client.transact(contract, &[]).unwrap_err().to_string().contains("reverted"));
+    assert_eq!(client.logs(contract, B256::with_last_byte(1)).unwrap().len(), 1);
+    // creations that revert deploy nothing
+    assert!(client.deploy(&hex::decode("60006000fd").unwrap()).is_err());
+}
+
+#[test]
+fn revoke_through_the_registry() {
+    let node = DevNode::start().unwrap();
+    let client = EthClient::with_node_account(node.url()).unwrap();
+    let registry = RevocationRegistry::deploy(&client, REGISTRY_BYTECODE, client.from()).unwrap();
+    assert_eq!(registry.issuer().unwrap(), client.from());
+    // other code runs on the EVM, and init code that reverts deploys nothing
+    assert!(RevocationRegistry::deploy(&client, &hex::decode("60006000fd").unwrap(), client.from()).is_err());
+
+    let issuer = Issuer::new();
+    let mut acc = ECAccumulator::new(issuer.get_g1());
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut creds: Vec<_> = (0..4).map(|_| issuer.gen_witness_n_cred(&acc, (0..5).map(|_| Fr::rand(&mut rng)).collect())).collect();
+    registry.publish_checkpoint(&AccumulatorState::from(&acc)).unwrap();
+    let mut holder = Holder::<ECAccumulator>::from_cred(creds.remove(0));
+    let x = *holder.get_cred().get_x_val();
+
+    let first = registry.revoke(&issuer, &mut acc, creds[0].get_x_val()).unwrap();
+    let second = registry.revoke(&issuer, &mut acc, creds[1].get_x_val()).unwrap();
+    assert_eq!(registry.state().unwrap(), AccumulatorState::from(&acc));
+    assert_eq!(registry.revocations_since(0).unwrap(), vec![(1, first), (2, second.clone())]);
+    assert_eq!(registry.revocations_since(1).unwrap(), vec![(2, second)]);
+    assert!(registry.revocations_since(2).unwrap().is_empty());
+
+    // a holder catches up from the chain alone
+    let params = issuer.public_keys();
+    for (_, update) in registry.revocations_since(0).unwrap() {
+        assert!(holder.update_witness(&params, &update));
+    }
+    let state = registry.state().unwrap();
+    let transcript = new_transcript(MEMBERSHIP_PROOF);
+    let proof = ECAccumulator::prove(&params, &state.alpha, &x, holder.get_cred().get_witness(), &mut transcript.clone()).unwrap();
+    assert!(ECAccumulator::verify(&params, &state.alpha, &proof, &mut transcript.clone()));
+
+    // a revoked credential stays revoked, and the registry must be at the accumulator's epoch
+    assert!(registry.revoke(&issuer, &mut acc, creds[0].get_x_val()).unwrap_err().to_string().contains("AlreadyRevoked"));
+    assert_eq!(registry.epoch().unwrap(), 2);
+    issuer.revoke_a_cred(&mut acc, &creds[2]).unwrap();
+    assert!(registry.revoke(&issuer, &mut acc, &x).unwrap_err().to_string().contains("sync first"));
+
+    // past a checkpoint the log cannot carry a witness, the holder is told so
+    registry.publish_checkpoint(&AccumulatorState::from(&acc)).unwrap();
+    assert!(registry.revocations_since(1).unwrap_err().to_string().contains("checkpoint at epoch 3"));
+    assert!(registry.revocations_since(3).unwrap().is_empty());
+}
+
+#[test]
+fn groth16_proofs_on_chain() {
+    let rng = &mut StdRng::seed_from_u64(0);
+    let x = ark_bn254::Fr::rand(rng);
+    let y = x * x * x + x + ark_bn254::Fr::from(5u64);
+    let circuit = CubeCircuit { x: Some(x), y: Some(y) };
+    let (pk, vk) = Groth16::<ark_bn254::Bn254>::setup(circuit.clone(), rng).unwrap();
+    let proof = Groth16::<ark_bn254::Bn254>::prove(&pk, circuit, rng).unwrap();
+
+    let calldata = groth16_calldata(&proof, &[y]);
+    assert_eq!(calldata.len(), 4 + 9 * 32);
+    assert_eq!(groth16_from_calldata(&calldata).unwrap(), (proof.clone(), vec![y]));
+
+    let node = DevNode::start().unwrap();
+    let client = EthClient::with_node_account(node.url()).unwrap();
+    let verifier = node.deploy_groth16_verifier(vk);
+    assert!(client.verify_groth16(verifier, &proof, &[y]).unwrap());
+    assert!(!client.verify_groth16(verifier, &proof, &[y + ark_bn254::Fr::from(1u64)]).unwrap());
+    let receipt = client.submit_groth16(verifier, &proof, &[y]).unwrap();
+    assert_eq!(receipt.block_number, client.block_number().unwrap());
+}
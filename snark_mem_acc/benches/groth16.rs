@@ -0,0 +1,79 @@
+// Groth16 proving and verification of the membership statement (CarolCircuit,
+// six hashed terms as public inputs) on BLS12-381 and on BN254, the curve the
+// on-chain verifier uses. Setup is done once per curve, outside the timings.
+//
+//   cargo bench -p prover --bench groth16
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_bn254::{Bn254, Fr as FrBN};
+use ark_groth16::{prepare_verifying_key, Groth16};
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use ark_std::UniformRand;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use prover::CarolCircuit::{CarolCircuit, CarolCircuitBn254};
+
+// every term satisfied: lhs == rhs
+fn carol_bls(rng: &mut StdRng) -> (CarolCircuit, Vec<Fr>) {
+    let t: Vec<Fr> = (0..6).map(|_| Fr::rand(rng)).collect();
+    let circuit = CarolCircuit {
+        term_1_lhs_hash: t[0], term_1_rhs_hash: t[0],
+        term_2_lhs_hash: t[1], term_2_rhs_hash: t[1],
+        term_3_lhs_hash: t[2], term_3_rhs_hash: t[2],
+        term_4_lhs_hash: t[3], term_4_rhs_hash: t[3],
+        term_5_lhs_hash: t[4], term_5_rhs_hash: t[4],
+        term_6_lhs_hash: t[5], term_6_rhs_hash: t[5],
+    };
+    (circuit, t.iter().flat_map(|t| [*t, *t]).collect())
+}
+
+fn carol_bn(rng: &mut StdRng) -> (CarolCircuitBn254, Vec<FrBN>) {
+    let t: Vec<FrBN> = (0..6).map(|_| FrBN::rand(rng)).collect();
+    let circuit = CarolCircuitBn254 {
+        term_1_lhs_hash: t[0], term_1_rhs_hash: t[0],
+        term_2_lhs_hash: t[1], term_2_rhs_hash: t[1],
+        term_3_lhs_hash: t[2], term_3_rhs_hash: t[2],
+        term_4_lhs_hash: t[3], term_4_rhs_hash: t[3],
+        term_5_lhs_hash: t[4], term_5_rhs_hash: t[4],
+        term_6_lhs_hash: t[5], term_6_rhs_hash: t[5],
+    };
+    (circuit, t.iter().flat_map(|t| [*t, *t]).collect())
+}
+
+fn bls12_381(c: &mut Criterion) {
+    let rng = &mut StdRng::seed_from_u64(0u64);
+    let (circuit, public_inputs) = carol_bls(rng);
+    let (pk, vk) = Groth16::<Bls12_381>::setup(circuit.clone(), rng).unwrap();
+    let pvk = prepare_verifying_key(&vk);
+    let proof = Groth16::<Bls12_381>::prove(&pk, circuit.clone(), rng).unwrap();
+
+    let mut group = c.benchmark_group("groth16/bls12_381");
+    group.bench_function("prove", |b| {
+        b.iter_batched(|| circuit.clone(), |circuit| Groth16::<Bls12_381>::prove(&pk, circuit, rng).unwrap(), BatchSize::SmallInput)
+    });
+    group.bench_function("verify", |b| {
+        b.iter(|| assert!(Groth16::<Bls12_381>::verify_with_processed_vk(&pvk, &public_inputs, &proof).unwrap()))
+    });
+    group.finish();
+}
+
+fn bn254(c: &mut Criterion) {
+    let rng = &mut StdRng::seed_from_u64(0u64);
+    let (circuit, public_inputs) = carol_bn(rng);
+    let (pk, vk) = Groth16::<Bn254>::setup(circuit.clone(), rng).unwrap();
+    let pvk = prepare_verifying_key(&vk);
+    let proof = Groth16::<Bn254>::prove(&pk, circuit.clone(), rng).unwrap();
+
+    let mut group = c.benchmark_group("groth16/bn254");
+    group.bench_function("prove", |b| {
+        b.iter_batched(|| circuit.clone(), |circuit| Groth16::<Bn254>::prove(&pk, circuit, rng).unwrap(), BatchSize::SmallInput)
+    });
+    group.bench_function("verify", |b| {
+        b.iter(|| assert!(Groth16::<Bn254>::verify_with_processed_vk(&pvk, &public_inputs, &proof).unwrap()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bls12_381, bn254);
+criterion_main!(benches);
@@ -2,7 +2,6 @@ use ark_r1cs_std::prelude::*;
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
 use ark_bls12_381::Fr;
 use ark_bn254::Fr as OtherFr;
-use ark_ff::Field;
 use ark_r1cs_std::fields::fp::FpVar;
 /// CarolCircuit enforces the six constraints via Poseidon hashes
 #[derive(Clone)]
@@ -69,7 +68,7 @@ impl ConstraintSynthesizer<Fr> for CarolCircuit {
 
 
 #[derive(Clone)]
-pub struct CarolCircuitBNS_254 {
+pub struct CarolCircuitBn254 {
     // 1️⃣ Cx = g^x * h^r
     pub term_1_lhs_hash: OtherFr,
     pub term_1_rhs_hash: OtherFr,
@@ -95,7 +94,7 @@ pub struct CarolCircuitBNS_254 {
     pub term_6_rhs_hash: OtherFr,
 }
 
-impl ConstraintSynthesizer<OtherFr> for CarolCircuitBNS_254 {
+impl ConstraintSynthesizer<OtherFr> for CarolCircuitBn254 {
     fn generate_constraints(self, cs: ConstraintSystemRef<OtherFr>) -> Result<(), SynthesisError> {
         // Allocate each hash as a public input
         let t1_lhs = FpVar::<OtherFr>::new_input(cs.clone(), || Ok(self.term_1_lhs_hash))?;
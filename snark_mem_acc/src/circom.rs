@@ -0,0 +1,287 @@
+// Loader for circuits compiled with `circom --r1cs` and witnesses produced by
+// the generated `generate_witness.js`, so Circom-authored circuits can be
+// proved and verified with ark_groth16 next to the native ones.
+//
+// File layouts follow the iden3 binary formats:
+//   .r1cs: "r1cs" | version | nSections | (type u32, size u64, data)*
+//          header (1), constraints (2), wire -> label map (3)
+//   .wtns: "wtns" | version | nSections | header (1), witness values (2)
+// All integers and field elements are little endian, field elements in
+// canonical (non Montgomery) form.
+
+use ark_ff::{BigInteger, PrimeField};
+use ark_relations::lc;
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable,
+};
+use std::error::Error;
+use std::path::Path;
+
+const R1CS_HEADER: u32 = 1;
+const R1CS_CONSTRAINTS: u32 = 2;
+const R1CS_WIRE2LABEL: u32 = 3;
+const R1CS_CUSTOM_GATES_LIST: u32 = 4;
+const R1CS_CUSTOM_GATES_USE: u32 = 5;
+
+const WTNS_HEADER: u32 = 1;
+const WTNS_DATA: u32 = 2;
+
+// (wire id, coefficient) pairs of one linear combination
+pub type CircomLc<F> = Vec<(usize, F)>;
+
+#[derive(Clone, Debug)]
+pub struct CircomConstraint<F: PrimeField> {
+    pub a: CircomLc<F>,
+    pub b: CircomLc<F>,
+    pub c: CircomLc<F>,
+}
+
+#[derive(Clone, Debug)]
+pub struct R1csFile<F: PrimeField> {
+    pub n_wires: usize,
+    pub n_pub_out: usize,
+    pub n_pub_in: usize,
+    pub n_prv_in: usize,
+    pub n_labels: u64,
+    pub constraints: Vec<CircomConstraint<F>>,
+    // wire id -> signal label id, used to map the .sym file
+    pub wire_to_label: Vec<u64>,
+}
+
+impl<F: PrimeField> R1csFile<F> {
+    // Public signals, i.e. what snarkjs writes to public.json (outputs first, then public inputs)
+    pub fn num_public(&self) -> usize {
+        self.n_pub_out + self.n_pub_in
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Box<dyn Error>> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.data.len())
+            .ok_or("unexpected end of file")?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, Box<dyn Error>> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, Box<dyn Error>> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn field<F: PrimeField>(&mut self, n8: usize) -> Result<F, Box<dyn Error>> {
+        let bytes = self.take(n8)?;
+        let mut bigint = F::BigInt::default();
+        let limbs = bigint.as_mut();
+        if n8 > limbs.len() * 8 {
+            return Err(format!("field element of {} bytes does not fit the scalar field", n8).into());
+        }
+        for (i, chunk) in bytes.chunks(8).enumerate() {
+            let mut limb = [0u8; 8];
+            limb[..chunk.len()].copy_from_slice(chunk);
+            limbs[i] = u64::from_le_bytes(limb);
+        }
+        F::from_bigint(bigint).ok_or_else(|| "field element is not reduced".into())
+    }
+}
+
+// (section type, contents) in file order
+type Sections<'a> = Vec<(u32, &'a [u8])>;
+
+// Splits an iden3 binary file into its sections, checking magic and version
+fn read_sections<'a>(
+    data: &'a [u8],
+    magic: &[u8; 4],
+    max_version: u32,
+) -> Result<Sections<'a>, Box<dyn Error>> {
+    let mut reader = Reader::new(data);
+    if reader.take(4)? != magic {
+        return Err(format!("not a {} file", String::from_utf8_lossy(magic)).into());
+    }
+    let version = reader.u32()?;
+    if version == 0 || version > max_version {
+        return Err(format!("unsupported {} version {}", String::from_utf8_lossy(magic), version).into());
+    }
+    let n_sections = reader.u32()?;
+    let mut sections = Vec::with_capacity(n_sections as usize);
+    for _ in 0..n_sections {
+        let kind = reader.u32()?;
+        let size = usize::try_from(reader.u64()?)?;
+        sections.push((kind, reader.take(size)?));
+    }
+    Ok(sections)
+}
+
+fn section<'a>(sections: &[(u32, &'a [u8])], kind: u32) -> Result<&'a [u8], Box<dyn Error>> {
+    let mut found = sections.iter().filter(|(k, _)| *k == kind);
+    let (_, data) = found.next().ok_or_else(|| format!("missing section {}", kind))?;
+    if found.next().is_some() {
+        return Err(format!("section {} appears more than once", kind).into());
+    }
+    Ok(data)
+}
+
+// Reads the field size and prime of a header and checks the prime is F's modulus
+fn read_prime<F: PrimeField>(reader: &mut Reader) -> Result<usize, Box<dyn Error>> {
+    let n8 = reader.u32()? as usize;
+    let prime = reader.take(n8)?;
+    let modulus = F::MODULUS.to_bytes_le();
+    let matches = prime.len() <= modulus.len()
+        && prime == &modulus[..prime.len()]
+        && modulus[prime.len()..].iter().all(|b| *b == 0);
+    if !matches {
+        return Err("file was compiled for a different prime field".into());
+    }
+    Ok(n8)
+}
+
+fn read_lc<F: PrimeField>(reader: &mut Reader, n8: usize, n_wires: usize) -> Result<CircomLc<F>, Box<dyn Error>> {
+    let n_terms = reader.u32()? as usize;
+    let mut terms = Vec::with_capacity(n_terms);
+    for _ in 0..n_terms {
+        let wire = reader.u32()? as usize;
+        if wire >= n_wires {
+            return Err(format!("constraint uses wire {} but there are only {} wires", wire, n_wires).into());
+        }
+        terms.push((wire, reader.field(n8)?));
+    }
+    Ok(terms)
+}
+
+pub fn parse_r1cs<F: PrimeField>(data: &[u8]) -> Result<R1csFile<F>, Box<dyn Error>> {
+    let sections = read_sections(data, b"r1cs", 1)?;
+    if sections.iter().any(|(k, _)| *k == R1CS_CUSTOM_GATES_LIST || *k == R1CS_CUSTOM_GATES_USE) {
+        return Err("custom gates are PLONK only and cannot be used with Groth16".into());
+    }
+
+    let mut header = Reader::new(section(&sections, R1CS_HEADER)?);
+    let n8 = read_prime::<F>(&mut header)?;
+    let n_wires = header.u32()? as usize;
+    let n_pub_out = header.u32()? as usize;
+    let n_pub_in = header.u32()? as usize;
+    let n_prv_in = header.u32()? as usize;
+    let n_labels = header.u64()?;
+    let n_constraints = header.u32()? as usize;
+    if 1 + n_pub_out + n_pub_in + n_prv_in > n_wires {
+        return Err("header declares more signals than wires".into());
+    }
+
+    let mut body = Reader::new(section(&sections, R1CS_CONSTRAINTS)?);
+    let mut constraints = Vec::with_capacity(n_constraints);
+    for _ in 0..n_constraints {
+        constraints.push(CircomConstraint {
+            a: read_lc(&mut body, n8, n_wires)?,
+            b: read_lc(&mut body, n8, n_wires)?,
+            c: read_lc(&mut body, n8, n_wires)?,
+        });
+    }
+
+    // the label map is optional for proving, keep it when present
+    let mut wire_to_label = Vec::new();
+    if let Ok(labels) = section(&sections, R1CS_WIRE2LABEL) {
+        let mut labels = Reader::new(labels);
+        for _ in 0..n_wires {
+            wire_to_label.push(labels.u64()?);
+        }
+    }
+
+    Ok(R1csFile { n_wires, n_pub_out, n_pub_in, n_prv_in, n_labels, constraints, wire_to_label })
+}
+
+pub fn parse_wtns<F: PrimeField>(data: &[u8]) -> Result<Vec<F>, Box<dyn Error>> {
+    let sections = read_sections(data, b"wtns", 2)?;
+
+    let mut header = Reader::new(section(&sections, WTNS_HEADER)?);
+    let n8 = read_prime::<F>(&mut header)?;
+    let n_witness = header.u32()? as usize;
+
+    let mut body = Reader::new(section(&sections, WTNS_DATA)?);
+    let witness = (0..n_witness).map(|_| body.field(n8)).collect::<Result<Vec<F>, _>>()?;
+    if witness.first() != Some(&F::one()) {
+        return Err("witness must start with the constant 1 signal".into());
+    }
+    Ok(witness)
+}
+
+pub fn read_r1cs<F: PrimeField>(path: impl AsRef<Path>) -> Result<R1csFile<F>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let data = std::fs::read(path)?;
+    parse_r1cs(&data).map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+pub fn read_wtns<F: PrimeField>(path: impl AsRef<Path>) -> Result<Vec<F>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let data = std::fs::read(path)?;
+    parse_wtns(&data).map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+// A Circom circuit as an arkworks circuit. Without a witness it can still be
+// used for key generation.
+#[derive(Clone, Debug)]
+pub struct CircomCircuit<F: PrimeField> {
+    pub r1cs: R1csFile<F>,
+    pub witness: Option<Vec<F>>,
+}
+
+impl<F: PrimeField> CircomCircuit<F> {
+    pub fn new(r1cs: R1csFile<F>, witness: Option<Vec<F>>) -> Result<Self, Box<dyn Error>> {
+        if let Some(w) = &witness
+            && w.len() != r1cs.n_wires
+        {
+            return Err(format!("witness has {} values but the circuit has {} wires", w.len(), r1cs.n_wires).into());
+        }
+        Ok(CircomCircuit { r1cs, witness })
+    }
+
+    pub fn from_files(r1cs_path: impl AsRef<Path>, wtns_path: Option<&Path>) -> Result<Self, Box<dyn Error>> {
+        let r1cs = read_r1cs(r1cs_path)?;
+        let witness = wtns_path.map(read_wtns).transpose()?;
+        Self::new(r1cs, witness)
+    }
+
+    // The public inputs in the order Groth16 expects them (same as snarkjs public.json)
+    pub fn public_inputs(&self) -> Option<Vec<F>> {
+        self.witness.as_ref().map(|w| w[1..=self.r1cs.num_public()].to_vec())
+    }
+}
+
+impl<F: PrimeField> ConstraintSynthesizer<F> for CircomCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        let n_public = self.r1cs.num_public();
+        let value = |i: usize| {
+            self.witness.as_ref().map(|w| w[i]).ok_or(SynthesisError::AssignmentMissing)
+        };
+
+        // wire 0 is the constant 1, then public signals, then everything else
+        let mut wires = Vec::with_capacity(self.r1cs.n_wires);
+        wires.push(Variable::One);
+        for i in 1..self.r1cs.n_wires {
+            let var = if i <= n_public {
+                cs.new_input_variable(|| value(i))?
+            } else {
+                cs.new_witness_variable(|| value(i))?
+            };
+            wires.push(var);
+        }
+
+        let to_lc = |terms: &CircomLc<F>| {
+            terms.iter().fold(lc!(), |lc: LinearCombination<F>, (wire, coeff)| lc + (*coeff, wires[*wire]))
+        };
+        for constraint in &self.r1cs.constraints {
+            cs.enforce_constraint(to_lc(&constraint.a), to_lc(&constraint.b), to_lc(&constraint.c))?;
+        }
+        Ok(())
+    }
+}
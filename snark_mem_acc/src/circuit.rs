@@ -1,25 +1,7 @@
 use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable};
-use ark_relations::r1cs::ConstraintSystem;
 use ark_ff::Field;
-use ark_std::marker::PhantomData; 
-use ark_snark::CircuitSpecificSetupSNARK;
-use ark_snark::SNARK;
 use ark_relations::r1cs::LinearCombination;
-use ark_relations::r1cs::ConstraintLayer;
-use ark_bls12_381::{Bls12_381, G1Projective, G2Projective, Fr};
-use ark_r1cs_std::fields::fp::FpVar;
 // Define your circuit struct
-use ark_r1cs_std::{
-    alloc::{AllocVar, AllocationMode},
-    eq::EqGadget,
-    R1CSVar,
-};
-use ark_sponge::{
-    poseidon::{PoseidonSponge, PoseidonConfig},
-    CryptographicSponge,
-};
-
-use ark_std::vec::Vec;
 
 #[derive(Clone)]
 pub struct CubeCircuit<F: Field> {
@@ -68,13 +50,11 @@ impl<F: Field + Clone> ConstraintSynthesizer<F> for CubeCircuit<F> {
 
         let five = F::from(5u64);
         // Enforce x_cubed + x + 5 = y
-        let result = cs.enforce_constraint(
+        cs.enforce_constraint(
             LinearCombination::new() + x_cubed + x_var + (five, Variable::One),
             LinearCombination::new() + Variable::One,                           // b = 1
         LinearCombination::new() + y_var,                                  // c
-        );
-
-        result
+        )
 }
 
 }
@@ -1,65 +1,319 @@
-use serde::Serialize;
-use ark_bls12_381::{Bls12_381, Fr, Fq, Fq2, Fq12, G1Projective, G2Projective};
-use ark_groth16::{Proof};
-use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
-use ark_ff::PrimeField;
-use ark_ff::BigInteger;
+use serde::{Deserialize, Serialize};
+use ark_ec::pairing::{Pairing, PairingOutput};
+use ark_ec::AffineRepr;
+use ark_groth16::{Groth16, Proof, VerifyingKey};
+use ark_snark::SNARK;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use num_bigint::BigUint;
 use std::fs::File;
 use ark_serialize::Write;
 use std::collections::BTreeMap;
-use std::io::BufWriter;
+use std::error::Error;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
 use serde_json::json;
-#[derive(Serialize)]
+
+// File names used by `snarkjs groth16 prove` / `snarkjs zkey export verificationkey`
+pub const SNARKJS_PROOF_FILE: &str = "proof.json";
+pub const SNARKJS_PUBLIC_FILE: &str = "public.json";
+pub const SNARKJS_VK_FILE: &str = "verification_key.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SnarkjsProof {
-    protocol: String,
-    curve: String,
-    pi_a: [String; 3],
-    pi_b: [[String; 2]; 3],
-    pi_c: [String; 3],
-}
-
-pub fn proof_to_snarkjs(proof: &Proof<Bls12_381>) -> SnarkjsProof {
-    let a = proof.a; //.into_affine();
-    let b = proof.b ;//.into_affine();
-    let c = proof.c ;//.into_affine();
-
-    let snp = SnarkjsProof {
-        pi_a: [
-            proof.a.x.into_bigint().to_string(),
-            proof.a.y.into_bigint().to_string(),
-            "1".to_string(),
-        ],
-        pi_b: [
-            [
-                proof.b.x.c0.into_bigint().to_string(),
-                proof.b.x.c1.into_bigint().to_string(),
-            ],
-            [
-                proof.b.y.c0.into_bigint().to_string(),
-                proof.b.y.c1.into_bigint().to_string(),
-            ],
-            [
-                "1".to_string(),
-                "0".to_string(),
-            ],
-        ],
-        pi_c: [
-            proof.c.x.into_bigint().to_string(),
-            proof.c.y.into_bigint().to_string(),
-            "1".to_string(),
-        ],
-        protocol: "groth16".to_string(),
-        curve: "bls12_381".to_string(),
+    pub pi_a: [String; 3],
+    pub pi_b: [[String; 2]; 3],
+    pub pi_c: [String; 3],
+    pub protocol: String,
+    pub curve: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SnarkjsVerifyingKey {
+    pub protocol: String,
+    pub curve: String,
+    #[serde(rename = "nPublic")]
+    pub n_public: usize,
+    pub vk_alpha_1: [String; 3],
+    pub vk_beta_2: [[String; 2]; 3],
+    pub vk_gamma_2: [[String; 2]; 3],
+    pub vk_delta_2: [[String; 2]; 3],
+    pub vk_alphabeta_12: [[[String; 2]; 3]; 2],
+    #[serde(rename = "IC")]
+    pub ic: Vec<[String; 3]>,
+}
+
+// Curves snarkjs knows about. The coordinates are written in the same
+// tower layout ffjavascript uses, so the files can be fed to snarkjs as is.
+pub trait SnarkjsCurve: Pairing {
+    // Name snarkjs puts in the "curve" field
+    const SNARKJS_NAME: &'static str;
+
+    fn g1_to_snarkjs(p: &Self::G1Affine) -> [String; 3];
+    fn g1_from_snarkjs(p: &[String; 3]) -> Result<Self::G1Affine, Box<dyn Error>>;
+    fn g2_to_snarkjs(p: &Self::G2Affine) -> [[String; 2]; 3];
+    fn g2_from_snarkjs(p: &[[String; 2]; 3]) -> Result<Self::G2Affine, Box<dyn Error>>;
+    fn gt_to_snarkjs(p: &PairingOutput<Self>) -> [[[String; 2]; 3]; 2];
+}
+
+// Canonical decimal string, "0" for zero (the Display impl of Fp trims it away)
+pub fn field_to_decimal<F: PrimeField>(f: &F) -> String {
+    BigUint::from_bytes_le(&f.into_bigint().to_bytes_le()).to_string()
+}
+
+// Parses a decimal string, rejecting anything that is not a canonical field element
+pub fn field_from_decimal<F: PrimeField>(s: &str) -> Result<F, Box<dyn Error>> {
+    let value = BigUint::parse_bytes(s.trim().as_bytes(), 10)
+        .ok_or_else(|| format!("not a decimal number: {:?}", s))?;
+    let modulus: BigUint = F::MODULUS.into();
+    if value >= modulus {
+        return Err(format!("value {} is not reduced modulo the field", s).into());
+    }
+    Ok(F::from_le_bytes_mod_order(&value.to_bytes_le()))
+}
+
+macro_rules! impl_snarkjs_curve {
+    ($engine:ty, $curve:ident, $name:expr) => {
+        impl SnarkjsCurve for $engine {
+            const SNARKJS_NAME: &'static str = $name;
+
+            fn g1_to_snarkjs(p: &Self::G1Affine) -> [String; 3] {
+                match p.xy() {
+                    Some((x, y)) => [field_to_decimal(x), field_to_decimal(y), "1".to_string()],
+                    None => ["0".to_string(), "1".to_string(), "0".to_string()],
+                }
+            }
+
+            fn g1_from_snarkjs(p: &[String; 3]) -> Result<Self::G1Affine, Box<dyn Error>> {
+                let z: $curve::Fq = field_from_decimal(&p[2])?;
+                if z.is_zero() {
+                    return Ok($curve::G1Affine::identity());
+                }
+                if z != $curve::Fq::from(1u64) {
+                    return Err("G1 point must be given in affine form (z = 1)".into());
+                }
+                let point = $curve::G1Affine::new_unchecked(
+                    field_from_decimal(&p[0])?,
+                    field_from_decimal(&p[1])?,
+                );
+                if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+                    return Err("G1 point is not in the prime order subgroup".into());
+                }
+                Ok(point)
+            }
+
+            fn g2_to_snarkjs(p: &Self::G2Affine) -> [[String; 2]; 3] {
+                match p.xy() {
+                    Some((x, y)) => [
+                        [field_to_decimal(&x.c0), field_to_decimal(&x.c1)],
+                        [field_to_decimal(&y.c0), field_to_decimal(&y.c1)],
+                        ["1".to_string(), "0".to_string()],
+                    ],
+                    None => [
+                        ["0".to_string(), "0".to_string()],
+                        ["1".to_string(), "0".to_string()],
+                        ["0".to_string(), "0".to_string()],
+                    ],
+                }
+            }
+
+            fn g2_from_snarkjs(p: &[[String; 2]; 3]) -> Result<Self::G2Affine, Box<dyn Error>> {
+                let fq2 = |c: &[String; 2]| -> Result<$curve::Fq2, Box<dyn Error>> {
+                    Ok($curve::Fq2::new(field_from_decimal(&c[0])?, field_from_decimal(&c[1])?))
+                };
+                let z = fq2(&p[2])?;
+                if z.is_zero() {
+                    return Ok($curve::G2Affine::identity());
+                }
+                if z != $curve::Fq2::from(1u64) {
+                    return Err("G2 point must be given in affine form (z = 1)".into());
+                }
+                let point = $curve::G2Affine::new_unchecked(fq2(&p[0])?, fq2(&p[1])?);
+                if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+                    return Err("G2 point is not in the prime order subgroup".into());
+                }
+                Ok(point)
+            }
+
+            fn gt_to_snarkjs(p: &PairingOutput<Self>) -> [[[String; 2]; 3]; 2] {
+                let fq6 = |c: &$curve::Fq6| {
+                    [c.c0, c.c1, c.c2].map(|e| [field_to_decimal(&e.c0), field_to_decimal(&e.c1)])
+                };
+                [fq6(&p.0.c0), fq6(&p.0.c1)]
+            }
+        }
     };
+}
+
+impl_snarkjs_curve!(ark_bn254::Bn254, ark_bn254, "bn128");
+impl_snarkjs_curve!(ark_bls12_381::Bls12_381, ark_bls12_381, "bls12381");
+
+pub fn proof_to_snarkjs<E: SnarkjsCurve>(proof: &Proof<E>) -> SnarkjsProof {
+    SnarkjsProof {
+        pi_a: E::g1_to_snarkjs(&proof.a),
+        pi_b: E::g2_to_snarkjs(&proof.b),
+        pi_c: E::g1_to_snarkjs(&proof.c),
+        protocol: "groth16".to_string(),
+        curve: E::SNARKJS_NAME.to_string(),
+    }
+}
+
+pub fn vk_to_snarkjs<E: SnarkjsCurve>(vk: &VerifyingKey<E>) -> SnarkjsVerifyingKey {
+    SnarkjsVerifyingKey {
+        protocol: "groth16".to_string(),
+        curve: E::SNARKJS_NAME.to_string(),
+        n_public: vk.gamma_abc_g1.len().saturating_sub(1),
+        vk_alpha_1: E::g1_to_snarkjs(&vk.alpha_g1),
+        vk_beta_2: E::g2_to_snarkjs(&vk.beta_g2),
+        vk_gamma_2: E::g2_to_snarkjs(&vk.gamma_g2),
+        vk_delta_2: E::g2_to_snarkjs(&vk.delta_g2),
+        vk_alphabeta_12: E::gt_to_snarkjs(&E::pairing(vk.alpha_g1, vk.beta_g2)),
+        ic: vk.gamma_abc_g1.iter().map(E::g1_to_snarkjs).collect(),
+    }
+}
+
+pub fn public_inputs_to_snarkjs<F: PrimeField>(public_inputs: &[F]) -> Vec<String> {
+    public_inputs.iter().map(field_to_decimal).collect()
+}
+
+fn check_header<E: SnarkjsCurve>(protocol: &str, curve: &str) -> Result<(), Box<dyn Error>> {
+    if protocol != "groth16" {
+        return Err(format!("unsupported protocol {:?}, expected \"groth16\"", protocol).into());
+    }
+    if curve != E::SNARKJS_NAME {
+        return Err(format!("curve mismatch: file is {:?}, expected {:?}", curve, E::SNARKJS_NAME).into());
+    }
+    Ok(())
+}
+
+pub fn proof_from_snarkjs<E: SnarkjsCurve>(proof: &SnarkjsProof) -> Result<Proof<E>, Box<dyn Error>> {
+    check_header::<E>(&proof.protocol, &proof.curve)?;
+    Ok(Proof {
+        a: E::g1_from_snarkjs(&proof.pi_a)?,
+        b: E::g2_from_snarkjs(&proof.pi_b)?,
+        c: E::g1_from_snarkjs(&proof.pi_c)?,
+    })
+}
+
+// vk_alphabeta_12 is only a cache for the verifier and is recomputed, not trusted
+pub fn vk_from_snarkjs<E: SnarkjsCurve>(vk: &SnarkjsVerifyingKey) -> Result<VerifyingKey<E>, Box<dyn Error>> {
+    check_header::<E>(&vk.protocol, &vk.curve)?;
+    if vk.ic.len() != vk.n_public + 1 {
+        return Err(format!("IC has {} points but nPublic is {}", vk.ic.len(), vk.n_public).into());
+    }
+    Ok(VerifyingKey {
+        alpha_g1: E::g1_from_snarkjs(&vk.vk_alpha_1)?,
+        beta_g2: E::g2_from_snarkjs(&vk.vk_beta_2)?,
+        gamma_g2: E::g2_from_snarkjs(&vk.vk_gamma_2)?,
+        delta_g2: E::g2_from_snarkjs(&vk.vk_delta_2)?,
+        gamma_abc_g1: vk.ic.iter().map(E::g1_from_snarkjs).collect::<Result<_, _>>()?,
+    })
+}
+
+pub fn public_inputs_from_snarkjs<F: PrimeField>(public_inputs: &[String]) -> Result<Vec<F>, Box<dyn Error>> {
+    public_inputs.iter().map(|s| field_from_decimal(s)).collect()
+}
+
+// snarkjs writes its files with JSON.stringify(value, null, 1)
+fn write_snarkjs_json<T: Serialize>(path: &Path, value: &T) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut ser = serde_json::Serializer::with_formatter(&mut writer, formatter);
+    value.serialize(&mut ser)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn read_snarkjs_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, Box<dyn Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    serde_json::from_reader(reader).map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+pub fn write_snarkjs_proof<E: SnarkjsCurve>(proof: &Proof<E>, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+    write_snarkjs_json(path.as_ref(), &proof_to_snarkjs(proof))
+}
+
+pub fn write_snarkjs_vk<E: SnarkjsCurve>(vk: &VerifyingKey<E>, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+    write_snarkjs_json(path.as_ref(), &vk_to_snarkjs(vk))
+}
 
+pub fn write_snarkjs_public<F: PrimeField>(public_inputs: &[F], path: impl AsRef<Path>) -> Result<(), Box<dyn Error>> {
+    write_snarkjs_json(path.as_ref(), &public_inputs_to_snarkjs(public_inputs))
+}
+
+pub fn read_snarkjs_proof<E: SnarkjsCurve>(path: impl AsRef<Path>) -> Result<Proof<E>, Box<dyn Error>> {
+    proof_from_snarkjs(&read_snarkjs_json(path.as_ref())?)
+}
+
+pub fn read_snarkjs_vk<E: SnarkjsCurve>(path: impl AsRef<Path>) -> Result<VerifyingKey<E>, Box<dyn Error>> {
+    vk_from_snarkjs(&read_snarkjs_json(path.as_ref())?)
+}
+
+pub fn read_snarkjs_public<F: PrimeField>(path: impl AsRef<Path>) -> Result<Vec<F>, Box<dyn Error>> {
+    let values: Vec<String> = read_snarkjs_json(path.as_ref())?;
+    public_inputs_from_snarkjs(&values)
+}
+
+// Writes proof.json, public.json and verification_key.json into `dir`,
+// ready for `snarkjs groth16 verify verification_key.json public.json proof.json`
+pub fn export_snarkjs<E: SnarkjsCurve>(
+    dir: impl AsRef<Path>,
+    proof: &Proof<E>,
+    vk: &VerifyingKey<E>,
+    public_inputs: &[E::ScalarField],
+) -> Result<(), Box<dyn Error>> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    write_snarkjs_proof(proof, dir.join(SNARKJS_PROOF_FILE))?;
+    write_snarkjs_public(public_inputs, dir.join(SNARKJS_PUBLIC_FILE))?;
+    write_snarkjs_vk(vk, dir.join(SNARKJS_VK_FILE))?;
+    Ok(())
+}
+
+// A proof with its verifying key and public inputs, as export_snarkjs takes them
+pub type SnarkjsBundle<E> = (Proof<E>, VerifyingKey<E>, Vec<<E as Pairing>::ScalarField>);
 
+// Reads the three snarkjs files back from `dir`
+pub fn import_snarkjs<E: SnarkjsCurve>(
+    dir: impl AsRef<Path>,
+) -> Result<SnarkjsBundle<E>, Box<dyn Error>> {
+    let dir = dir.as_ref();
+    let proof = read_snarkjs_proof(dir.join(SNARKJS_PROOF_FILE))?;
+    let public_inputs = read_snarkjs_public(dir.join(SNARKJS_PUBLIC_FILE))?;
+    let vk = read_snarkjs_vk(dir.join(SNARKJS_VK_FILE))?;
+    Ok((proof, vk, public_inputs))
+}
 
-    let file = File::create("proof_test.json").expect("Unable to create file");
-    serde_json::to_writer_pretty(&file, &snp).expect("Unable to write JSON");
-    let json = serde_json::to_string(&snp).expect("Failed to serialize proof");
-    let file = File::create("proof_test.json").expect("Unable to create file");
-    serde_json::to_writer_pretty(&file, &snp).expect("Unable to write JSON");
-    snp
+// Same check as `snarkjs groth16 verify`, done with arkworks
+pub fn verify_snarkjs<E: SnarkjsCurve>(
+    vk_path: impl AsRef<Path>,
+    public_path: impl AsRef<Path>,
+    proof_path: impl AsRef<Path>,
+) -> Result<bool, Box<dyn Error>> {
+    verify_snarkjs_json::<E>(
+        &read_snarkjs_json(vk_path.as_ref())?,
+        &read_snarkjs_json::<Vec<String>>(public_path.as_ref())?,
+        &read_snarkjs_json(proof_path.as_ref())?,
+    )
+}
+
+// verify_snarkjs on the decoded JSON, for proofs that do not come as files
+pub fn verify_snarkjs_json<E: SnarkjsCurve>(
+    vk: &SnarkjsVerifyingKey,
+    public_inputs: &[String],
+    proof: &SnarkjsProof,
+) -> Result<bool, Box<dyn Error>> {
+    let vk = vk_from_snarkjs::<E>(vk)?;
+    let public_inputs = public_inputs_from_snarkjs::<E::ScalarField>(public_inputs)?;
+    let proof = proof_from_snarkjs::<E>(proof)?;
+    if public_inputs.len() + 1 != vk.gamma_abc_g1.len() {
+        return Err(format!(
+            "expected {} public inputs, got {}",
+            vk.gamma_abc_g1.len() - 1,
+            public_inputs.len()
+        )
+        .into());
+    }
+    Ok(Groth16::<E>::verify(&vk, &public_inputs, &proof)?)
 }
 
 pub fn export_public_inputs_to_json<F: PrimeField>(
@@ -71,7 +325,7 @@ pub fn export_public_inputs_to_json<F: PrimeField>(
 
     writeln!(writer, "[")?;
     for (i, value) in public_inputs.iter().enumerate() {
-        let s = value.to_string();
+        let s = field_to_decimal(value);
         if i + 1 != public_inputs.len() {
             writeln!(writer, "  \"{}\",", s)?;
         } else {
@@ -104,9 +358,9 @@ pub fn export_public_inputs_to_json_field<F: PrimeField>(
 
     let file = File::create(path).expect("Unable to create file");
     serde_json::to_writer_pretty(&file, &json_value).expect("Unable to write JSON");
-    
 
-    
+
+
 }
 
 // pub fn export_pk_vk_binary<P: CanonicalSerialize, V: CanonicalSerialize>(
@@ -141,4 +395,4 @@ pub fn export_public_inputs_to_json_field<F: PrimeField>(
 //     let vk = V::deserialize_unchecked(&mut vk_reader)?;
 
 //     Ok((pk, vk))
-// }
\ No newline at end of file
+// }
@@ -0,0 +1,133 @@
+// On-disk store for Groth16 keys, shared by native arkworks circuits and
+// circuits loaded from Circom. Keys live in `<dir>/<name>.pk` / `<name>.vk`
+// next to a small `<name>.shape` file recording the constraint system they
+// were generated for, so a changed circuit never reuses stale keys.
+
+use ark_ec::pairing::Pairing;
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisMode,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::CircuitSpecificSetupSNARK;
+use sha2::{Digest, Sha256};
+use ark_std::rand::{CryptoRng, RngCore};
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+pub struct KeyStore {
+    dir: PathBuf,
+}
+
+impl KeyStore {
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(KeyStore { dir: dir.as_ref().to_path_buf() })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, name: &str, ext: &str) -> Result<PathBuf, Box<dyn Error>> {
+        let valid = !name.is_empty()
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+            && !name.starts_with('.');
+        if !valid {
+            return Err(format!("invalid key name {:?}", name).into());
+        }
+        Ok(self.dir.join(format!("{}.{}", name, ext)))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        ["pk", "vk", "shape"].iter().all(|ext| self.path(name, ext).map(|p| p.exists()).unwrap_or(false))
+    }
+
+    pub fn save<E: Pairing>(&self, name: &str, pk: &ProvingKey<E>, shape: &str) -> Result<(), Box<dyn Error>> {
+        write_key(&self.path(name, "pk")?, pk)?;
+        write_key(&self.path(name, "vk")?, &pk.vk)?;
+        std::fs::write(self.path(name, "shape")?, shape)?;
+        Ok(())
+    }
+
+    pub fn load_pk<E: Pairing>(&self, name: &str) -> Result<ProvingKey<E>, Box<dyn Error>> {
+        read_key(&self.path(name, "pk")?)
+    }
+
+    pub fn load_vk<E: Pairing>(&self, name: &str) -> Result<VerifyingKey<E>, Box<dyn Error>> {
+        read_key(&self.path(name, "vk")?)
+    }
+
+    // Returns the stored keys for `name`, running the Groth16 setup (and
+    // storing the result) when they are missing or were made for another circuit.
+    pub fn load_or_setup<E, C, R>(
+        &self,
+        name: &str,
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<(ProvingKey<E>, VerifyingKey<E>), Box<dyn Error>>
+    where
+        E: Pairing,
+        C: ConstraintSynthesizer<E::ScalarField> + Clone,
+        R: RngCore + CryptoRng,
+    {
+        let shape = circuit_shape::<E::ScalarField, _>(circuit.clone())?;
+        if self.contains(name) && std::fs::read_to_string(self.path(name, "shape")?)? == shape {
+            let pk = self.load_pk::<E>(name)?;
+            let vk = pk.vk.clone();
+            return Ok((pk, vk));
+        }
+        let (pk, vk) = Groth16::<E>::setup(circuit, rng)?;
+        self.save(name, &pk, &shape)?;
+        Ok((pk, vk))
+    }
+}
+
+// Number of public inputs, witnesses and constraints, plus a digest of the
+// constraint matrices
+pub fn circuit_shape<F, C>(circuit: C) -> Result<String, Box<dyn Error>>
+where
+    F: ark_ff::PrimeField,
+    C: ConstraintSynthesizer<F>,
+{
+    let cs = ConstraintSystem::<F>::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    cs.set_mode(SynthesisMode::Setup);
+    circuit.generate_constraints(cs.clone())?;
+    cs.finalize();
+    let matrices = cs.to_matrices().ok_or("constraint system has no matrices")?;
+
+    let mut bytes = Vec::new();
+    for matrix in [&matrices.a, &matrices.b, &matrices.c] {
+        for row in matrix {
+            (row.len() as u64).serialize_uncompressed(&mut bytes)?;
+            for (coeff, index) in row {
+                coeff.serialize_uncompressed(&mut bytes)?;
+                (*index as u64).serialize_uncompressed(&mut bytes)?;
+            }
+        }
+    }
+    Ok(format!(
+        "inputs={} witnesses={} constraints={} digest={}",
+        matrices.num_instance_variables,
+        matrices.num_witness_variables,
+        matrices.num_constraints,
+        hex::encode(Sha256::digest(&bytes)),
+    ))
+}
+
+fn write_key<K: CanonicalSerialize>(path: &Path, key: &K) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    key.serialize_uncompressed(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+// Key files can be edited or swapped on disk, so points are checked to be on
+// the curve and in the prime-order subgroup
+fn read_key<K: CanonicalDeserialize>(path: &Path) -> Result<K, Box<dyn Error>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    Ok(K::deserialize_uncompressed(&mut reader)?)
+}
@@ -1,3 +1,6 @@
+#[allow(non_snake_case)]
 pub mod CarolCircuit;
 pub mod circuit;
-pub mod export_proof;
\ No newline at end of file
+pub mod export_proof;
+pub mod circom;
+pub mod key_store;
\ No newline at end of file
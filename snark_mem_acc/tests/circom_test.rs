@@ -0,0 +1,103 @@
+use ark_bn254::{Bn254, Fr as FrBN};
+use ark_groth16::Groth16;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_snark::SNARK;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use std::path::PathBuf;
+
+use prover::circom::*;
+use prover::circuit::CubeCircuit;
+use prover::export_proof::read_snarkjs_public;
+use prover::key_store::KeyStore;
+
+fn manifest_path(rel: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(rel)
+}
+
+fn carol_circuit() -> CircomCircuit<FrBN> {
+    CircomCircuit::from_files(
+        manifest_path("src/build_carol/CarolCircuit.r1cs"),
+        Some(&manifest_path("src/build_carol/witness.wtns")),
+    )
+    .unwrap()
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("circom_keys_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn loads_carol_circuit_and_witness() {
+    let circuit = carol_circuit();
+    assert_eq!(circuit.r1cs.num_public(), 12);
+    assert_eq!(circuit.r1cs.wire_to_label.len(), circuit.r1cs.n_wires);
+
+    // the witness matches the public.json snarkjs wrote for it
+    let public = read_snarkjs_public::<FrBN>(manifest_path("src/build_carol/public_input_254.json")).unwrap();
+    assert_eq!(circuit.public_inputs().unwrap(), public);
+
+    let cs = ConstraintSystem::<FrBN>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn tampered_witness_is_not_satisfied() {
+    let mut circuit = carol_circuit();
+    let witness = circuit.witness.as_mut().unwrap();
+    witness[1] += FrBN::from(1u64);
+
+    let cs = ConstraintSystem::<FrBN>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn rejects_wrong_field_and_bad_files() {
+    let r1cs = std::fs::read(manifest_path("src/build_carol/CarolCircuit.r1cs")).unwrap();
+    assert!(parse_r1cs::<ark_bls12_381::Fr>(&r1cs).is_err());
+    assert!(parse_r1cs::<FrBN>(&r1cs[..r1cs.len() - 1]).is_err());
+    assert!(parse_wtns::<FrBN>(&r1cs).is_err());
+
+    let r1cs = read_r1cs::<FrBN>(manifest_path("src/build_carol/CarolCircuit.r1cs")).unwrap();
+    assert!(CircomCircuit::new(r1cs, Some(vec![FrBN::from(1u64)])).is_err());
+}
+
+#[test]
+fn proves_circom_circuit_with_shared_key_store() {
+    let rng = &mut StdRng::seed_from_u64(0u64);
+    let store = KeyStore::new(scratch_dir("shared")).unwrap();
+
+    // keys are generated from the r1cs alone, without a witness
+    let setup_circuit = CircomCircuit::new(carol_circuit().r1cs, None).unwrap();
+    let (pk, vk) = store.load_or_setup::<Bn254, _, _>("carol", setup_circuit.clone(), rng).unwrap();
+
+    let circuit = carol_circuit();
+    let public = circuit.public_inputs().unwrap();
+    let proof = Groth16::<Bn254>::prove(&pk, circuit, rng).unwrap();
+    assert!(Groth16::<Bn254>::verify(&vk, &public, &proof).unwrap());
+
+    // second lookup reuses the stored keys
+    let (_, vk_again) = store.load_or_setup::<Bn254, _, _>("carol", setup_circuit, rng).unwrap();
+    assert_eq!(vk, vk_again);
+
+    // a native circuit lives in the same store; a different circuit under the
+    // same name gets fresh keys
+    let x = FrBN::from(3u64);
+    let cube = CubeCircuit { x: Some(x), y: Some(x * x * x + x + FrBN::from(5u64)) };
+    let (_, vk_cube) = store.load_or_setup::<Bn254, _, _>("cube", cube.clone(), rng).unwrap();
+    assert_eq!(vk_cube.gamma_abc_g1.len(), 2);
+    let (_, vk_replaced) = store.load_or_setup::<Bn254, _, _>("carol", cube, rng).unwrap();
+    assert_ne!(vk, vk_replaced);
+
+    // a key file whose points were tampered with is rejected on load
+    let vk_path = store.dir().join("cube.vk");
+    let mut bytes = std::fs::read(&vk_path).unwrap();
+    bytes[0] ^= 1;
+    std::fs::write(&vk_path, bytes).unwrap();
+    assert!(store.load_vk::<Bn254>("cube").is_err());
+
+    std::fs::remove_dir_all(store.dir()).unwrap();
+}
@@ -0,0 +1,104 @@
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ff::UniformRand;
+use ark_groth16::Groth16;
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use std::path::PathBuf;
+
+use prover::circuit::CubeCircuit;
+use prover::export_proof::*;
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("snarkjs_export_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn roundtrip<E: SnarkjsCurve>(name: &str) {
+    let rng = &mut StdRng::seed_from_u64(0u64);
+    let x = E::ScalarField::rand(rng);
+    let y = x * x * x + x + E::ScalarField::from(5u64);
+    let circuit = CubeCircuit { x: Some(x), y: Some(y) };
+
+    let (pk, vk) = Groth16::<E>::setup(circuit.clone(), rng).unwrap();
+    let proof = Groth16::<E>::prove(&pk, circuit, rng).unwrap();
+
+    let dir = scratch_dir(name);
+    export_snarkjs(&dir, &proof, &vk, &[y]).unwrap();
+
+    let (proof2, vk2, inputs2) = import_snarkjs::<E>(&dir).unwrap();
+    assert_eq!(proof, proof2);
+    assert_eq!(vk, vk2);
+    assert_eq!(inputs2, vec![y]);
+
+    assert!(verify_snarkjs::<E>(
+        dir.join(SNARKJS_VK_FILE),
+        dir.join(SNARKJS_PUBLIC_FILE),
+        dir.join(SNARKJS_PROOF_FILE),
+    )
+    .unwrap());
+
+    // wrong public input must not verify
+    write_snarkjs_public(&[y + E::ScalarField::from(1u64)], dir.join(SNARKJS_PUBLIC_FILE)).unwrap();
+    assert!(!verify_snarkjs::<E>(
+        dir.join(SNARKJS_VK_FILE),
+        dir.join(SNARKJS_PUBLIC_FILE),
+        dir.join(SNARKJS_PROOF_FILE),
+    )
+    .unwrap());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn snarkjs_roundtrip_bn254() {
+    roundtrip::<Bn254>("bn254");
+}
+
+#[test]
+fn snarkjs_roundtrip_bls12_381() {
+    roundtrip::<Bls12_381>("bls12_381");
+}
+
+#[test]
+fn snarkjs_files_use_snarkjs_layout() {
+    let rng = &mut StdRng::seed_from_u64(0u64);
+    let x = <Bn254 as Pairing>::ScalarField::rand(rng);
+    let y = x * x * x + x + <Bn254 as Pairing>::ScalarField::from(5u64);
+    let circuit = CubeCircuit { x: Some(x), y: Some(y) };
+    let (pk, vk) = Groth16::<Bn254>::setup(circuit.clone(), rng).unwrap();
+    let proof = Groth16::<Bn254>::prove(&pk, circuit, rng).unwrap();
+
+    let dir = scratch_dir("layout");
+    export_snarkjs(&dir, &proof, &vk, &[y]).unwrap();
+
+    let vk_json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join(SNARKJS_VK_FILE)).unwrap()).unwrap();
+    assert_eq!(vk_json["protocol"], "groth16");
+    assert_eq!(vk_json["curve"], "bn128");
+    assert_eq!(vk_json["nPublic"], 1);
+    assert_eq!(vk_json["IC"].as_array().unwrap().len(), 2);
+    assert_eq!(vk_json["vk_alphabeta_12"].as_array().unwrap().len(), 2);
+
+    let public = std::fs::read_to_string(dir.join(SNARKJS_PUBLIC_FILE)).unwrap();
+    assert_eq!(public, format!("[\n \"{}\"\n]", field_to_decimal(&y)));
+
+    // proofs for one curve are rejected by the other
+    assert!(read_snarkjs_proof::<Bls12_381>(dir.join(SNARKJS_PROOF_FILE)).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn verifies_proof_generated_by_snarkjs() {
+    let build = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/build_carol");
+    let valid = verify_snarkjs::<Bn254>(
+        build.join("verification_key.json"),
+        build.join("public_input_254.json"),
+        build.join("proof.json"),
+    )
+    .unwrap();
+    assert!(valid);
+}
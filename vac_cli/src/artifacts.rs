@@ -0,0 +1,151 @@
+// Files the roles hand each other. All JSON; group elements and credentials
+// are hex of their compressed (CanonicalSerialize) bytes.
+//
+//   issuer.pub.json   IssuerFile: public keys of the current key version and
+//                     the schema it issues under; for holders and verifiers
+//   cred.json         CredentialFile: one issued credential with its witness
+//                     and the epoch the witness is current at. Contains the
+//                     holder's secrets x and r: hand it over privately and
+//                     delete it once imported
+//   acc.json          AccumulatorFile: the accumulator value and every
+//                     revocation so far, (epoch, delta, new alpha) in order.
+//                     The issuer keeps its own copy in <dir>/accumulator.json
+//                     and publishes it with `vac acc publish`
+//
+// Requests and presentations use their own JSON encodings
+// (PresentationRequest::to_json, Presentation::to_json).
+
+use ark_bls12_381::{Fr, G1Projective};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::credential::Credential;
+use accumulator_impl::keystore::IssuerPublicKeys;
+use accumulator_impl::presentation::AccumulatorState;
+use accumulator_impl::revocation::AccRevocation;
+use accumulator_impl::schema::CredentialSchema;
+
+pub fn read<T: DeserializeOwned>(path: &Path) -> Result<T, Box<dyn Error>> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(serde_json::from_str(&json).map_err(|e| format!("{}: {}", path.display(), e))?)
+}
+
+// Written next to the target and renamed over it, never half-written
+pub fn write<T: Serialize>(path: &Path, value: &T) -> Result<(), Box<dyn Error>> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(value)?)?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+fn to_hex<T: CanonicalSerialize>(value: &T) -> String {
+    let mut bytes = Vec::new();
+    value.serialize_compressed(&mut bytes).unwrap();
+    hex::encode(bytes)
+}
+
+fn from_hex<T: CanonicalDeserialize>(value: &str) -> Result<T, Box<dyn Error>> {
+    Ok(T::deserialize_compressed(&hex::decode(value)?[..])?)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IssuerFile {
+    pub key_id: u32,
+    pub fingerprint: String, // IssuerPublicKeys::fingerprint, the issuer id in wallets and requests
+    pub public_keys: String, // hex of IssuerPublicKeys::to_bytes
+    pub schema: CredentialSchema,
+}
+
+impl IssuerFile {
+    pub fn new(keys: &IssuerPublicKeys, schema: &CredentialSchema) -> Self {
+        IssuerFile { key_id: keys.key_id, fingerprint: keys.fingerprint(), public_keys: hex::encode(keys.to_bytes()), schema: schema.clone() }
+    }
+
+    // Checked against the fingerprint, so an edited file is refused
+    pub fn keys(&self) -> Result<IssuerPublicKeys, Box<dyn Error>> {
+        let keys = IssuerPublicKeys::from_bytes(self.key_id, &hex::decode(&self.public_keys)?)?;
+        if keys.fingerprint() != self.fingerprint {
+            return Err("issuer public keys do not match their fingerprint".into());
+        }
+        Ok(keys)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CredentialFile {
+    pub id: String, // registry id, for revocation
+    pub issuer: String,
+    pub schema_id: String,
+    pub epoch: u64,
+    pub credential: String, // hex of Credential::to_bytes
+}
+
+impl CredentialFile {
+    pub fn new(id: &str, issuer: &IssuerPublicKeys, schema: &CredentialSchema, epoch: u64, cred: &Credential) -> Self {
+        CredentialFile { id: id.to_string(), issuer: issuer.fingerprint(), schema_id: schema.id(), epoch, credential: hex::encode(cred.to_bytes()) }
+    }
+
+    pub fn credential(&self) -> Result<Credential, Box<dyn Error>> {
+        Credential::from_bytes(&hex::decode(&self.credential)?)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UpdateEntry {
+    pub epoch: u64, // epoch after the revocation
+    pub delta: String, // hex of the revoked x
+    pub new_alpha: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccumulatorFile {
+    pub issuer: String,
+    pub epoch: u64,
+    pub alpha: String,
+    pub updates: Vec<UpdateEntry>,
+}
+
+impl AccumulatorFile {
+    pub fn new(issuer: &IssuerPublicKeys, acc: &ECAccumulator) -> Self {
+        AccumulatorFile { issuer: issuer.fingerprint(), epoch: acc.get_epoch(), alpha: to_hex(acc.get_alpha()), updates: Vec::new() }
+    }
+
+    // The issuer's accumulator; the revoked deltas are the ones in the log
+    pub fn accumulator(&self) -> Result<ECAccumulator, Box<dyn Error>> {
+        let deltas = self.updates.iter().map(|u| from_hex::<Fr>(&u.delta)).collect::<Result<Vec<_>, _>>()?;
+        Ok(ECAccumulator { alpha: from_hex(&self.alpha)?, deltas, epoch: self.epoch })
+    }
+
+    // Every revocation moves the accumulator one epoch on
+    pub fn record(&mut self, update: &AccRevocation) {
+        self.epoch += 1;
+        self.alpha = to_hex(&update.new_alpha);
+        self.updates.push(UpdateEntry { epoch: self.epoch, delta: to_hex(&update.delta), new_alpha: self.alpha.clone() });
+    }
+
+    pub fn log(&self) -> Result<Vec<(u64, AccRevocation)>, Box<dyn Error>> {
+        self.updates
+            .iter()
+            .map(|u| Ok((u.epoch, AccRevocation { delta: from_hex(&u.delta)?, new_alpha: from_hex::<G1Projective>(&u.new_alpha)? })))
+            .collect()
+    }
+
+    pub fn state(&self) -> Result<AccumulatorState, Box<dyn Error>> {
+        Ok(AccumulatorState { epoch: self.epoch, alpha: from_hex(&self.alpha)? })
+    }
+
+    // Accumulator value at an earlier epoch, as far as the log goes back
+    pub fn state_at(&self, epoch: u64) -> Result<Option<AccumulatorState>, Box<dyn Error>> {
+        if epoch == self.epoch {
+            return Ok(Some(self.state()?));
+        }
+        match self.updates.iter().find(|u| u.epoch == epoch) {
+            Some(u) => Ok(Some(AccumulatorState { epoch, alpha: from_hex(&u.new_alpha)? })),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+// Holder commands, on an encrypted wallet file (accumulator_impl::wallet)
+// created by the first import.
+
+use std::error::Error;
+use std::path::Path;
+
+use accumulator_impl::presentation::{Presentation, PresentationRequest};
+use accumulator_impl::wallet::Wallet;
+
+use crate::artifacts::{self, AccumulatorFile, CredentialFile, IssuerFile};
+
+fn issuer_accumulator(issuer: &IssuerFile, acc: &Path) -> Result<AccumulatorFile, Box<dyn Error>> {
+    let acc: AccumulatorFile = artifacts::read(acc)?;
+    if acc.issuer != issuer.fingerprint {
+        return Err("the accumulator was published by another issuer".into());
+    }
+    Ok(acc)
+}
+
+pub fn import(wallet: &Path, issuer: &Path, cred: &Path, passphrase: &str) -> Result<(), Box<dyn Error>> {
+    let issuer: IssuerFile = artifacts::read(issuer)?;
+    let file: CredentialFile = artifacts::read(cred)?;
+    if file.issuer != issuer.fingerprint || file.schema_id != issuer.schema.id() {
+        return Err("the credential was issued by another issuer or under another schema".into());
+    }
+    let mut wallet: Wallet = match wallet.exists() {
+        true => Wallet::open(wallet, passphrase)?,
+        false => Wallet::create(wallet, passphrase)?,
+    };
+    let id = wallet.add_at_epoch(file.credential()?, &issuer.keys()?, Some(&issuer.schema), file.epoch)?;
+    wallet.save()?;
+    println!("{}", id);
+    Ok(())
+}
+
+// Brings the witnesses of the issuer's credentials up to the published epoch
+pub fn update(wallet: &Path, issuer: &Path, acc: &Path, passphrase: &str) -> Result<(), Box<dyn Error>> {
+    let issuer: IssuerFile = artifacts::read(issuer)?;
+    let acc = issuer_accumulator(&issuer, acc)?;
+    let keys = issuer.keys()?;
+    let mut wallet: Wallet = Wallet::open(wallet, passphrase)?;
+    let revoked = wallet.sync_witnesses(&keys.fingerprint(), &keys, &acc.log()?)?;
+    wallet.save()?;
+    for id in revoked.iter() {
+        println!("revoked {}", id);
+    }
+    println!("epoch {}", acc.epoch);
+    Ok(())
+}
+
+// Answers the request with the first wallet credential of the issuer that can
+pub fn present(wallet: &Path, issuer: &Path, acc: &Path, request: &Path, out: &Path, passphrase: &str) -> Result<(), Box<dyn Error>> {
+    let issuer: IssuerFile = artifacts::read(issuer)?;
+    let acc = issuer_accumulator(&issuer, acc)?;
+    let request = PresentationRequest::from_json(&std::fs::read_to_string(request)?)?;
+    let wallet = Wallet::open(wallet, passphrase)?;
+    let presentation = Presentation::from_wallet(&wallet, &request, &issuer.keys()?, &issuer.schema, &acc.state()?)
+        .map_err(|e| format!("cannot answer the request: {:?}", e))?;
+    std::fs::write(out, presentation.to_json())?;
+    Ok(())
+}
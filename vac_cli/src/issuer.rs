@@ -0,0 +1,148 @@
+// Issuer commands. Everything the issuer keeps lives in one directory:
+//
+//   issuer_keys.json   the encrypted keystore (accumulator_impl::keystore)
+//   schema.json        the schema credentials are issued under
+//   registry/          the credential registry (accumulator_impl::registry)
+//   accumulator.json   the accumulator and its revocation log (AccumulatorFile)
+//   groth16_vk.json    optional, the snarkjs verifying key `vac serve` checks
+//                      Groth16 proofs against
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use accumulator_impl::acc::ECAccumulator;
+use accumulator_impl::issuer::Issuer;
+use accumulator_impl::keystore::{IssuerKeystore, KEYSTORE_FILE};
+use accumulator_impl::registry::CredentialRegistry;
+use accumulator_impl::schema::{AttributeValue, CredentialSchema};
+use prover::export_proof::SnarkjsVerifyingKey;
+
+use crate::artifacts::{self, AccumulatorFile, CredentialFile, IssuerFile};
+
+const SCHEMA_FILE: &str = "schema.json";
+const REGISTRY_DIR: &str = "registry";
+const ACCUMULATOR_FILE: &str = "accumulator.json";
+const GROTH16_VK_FILE: &str = "groth16_vk.json";
+
+// An opened issuer directory. The registry stays open (and locked) while
+// this is alive, so one process at a time works on a directory
+pub struct IssuerDir {
+    dir: PathBuf,
+    pub issuer: Issuer,
+    pub schema: CredentialSchema,
+    pub registry: CredentialRegistry,
+    pub state: AccumulatorFile,
+}
+
+impl IssuerDir {
+    pub fn open(dir: &Path, passphrase: &str) -> Result<Self, Box<dyn Error>> {
+        if !dir.join(KEYSTORE_FILE).exists() {
+            return Err(format!("{} is not an issuer directory, run `vac issuer init` first", dir.display()).into());
+        }
+        let issuer = IssuerKeystore::new(dir)?.load(passphrase)?;
+        let schema = CredentialSchema::from_json(&std::fs::read_to_string(dir.join(SCHEMA_FILE))?)?;
+        let registry = CredentialRegistry::open(dir.join(REGISTRY_DIR))?;
+        let state = artifacts::read(&dir.join(ACCUMULATOR_FILE))?;
+        Ok(IssuerDir { dir: dir.to_path_buf(), issuer, schema, registry, state })
+    }
+
+    pub fn groth16_vk(&self) -> Result<Option<SnarkjsVerifyingKey>, Box<dyn Error>> {
+        let path = self.dir.join(GROTH16_VK_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        artifacts::read(&path).map(Some)
+    }
+
+    pub fn public_file(&self) -> IssuerFile {
+        IssuerFile::new(&self.issuer.public_keys(), &self.schema)
+    }
+
+    // Issues a credential on the current accumulator and records it in the
+    // registry under `metadata`
+    pub fn issue(&self, values: &BTreeMap<String, AttributeValue>, metadata: BTreeMap<String, String>) -> Result<CredentialFile, Box<dyn Error>> {
+        let acc = self.state.accumulator()?;
+        let cred = self.issuer.issue_with_schema(&acc, &self.schema, values).map_err(|e| format!("{:?}", e))?;
+        let id = self.registry.register(&cred, metadata)?;
+        Ok(CredentialFile::new(&id, &self.issuer.public_keys(), &self.schema, acc.get_epoch(), &cred))
+    }
+
+    // Revokes one credential by registry id, or every active one whose
+    // metadata has name = value, and saves the accumulator. Returns the
+    // revoked ids with the epoch each revocation moved to. The new
+    // accumulator.json is staged before the registry commits and renamed
+    // over the old one after, so a failure on either side changes neither
+    pub fn revoke(&mut self, id: Option<&str>, attribute: Option<(&str, &str)>) -> Result<Vec<(String, u64)>, Box<dyn Error>> {
+        let mut acc = self.state.accumulator()?;
+        let pending = match (id, attribute) {
+            (Some(id), None) => self.registry.prepare_revoke(&self.issuer, &acc, id)?,
+            (None, Some((name, value))) => self.registry.prepare_revoke_by_attribute(&self.issuer, &acc, name, value)?,
+            _ => return Err("give either a credential id or --meta name=value".into()),
+        };
+
+        let mut state = self.state.clone();
+        let mut epochs = Vec::new();
+        for (id, update) in pending.updates.iter() {
+            state.record(update);
+            epochs.push((id.clone(), state.epoch));
+        }
+        let path = self.dir.join(ACCUMULATOR_FILE);
+        let staged = path.with_extension("staged");
+        artifacts::write(&staged, &state)?;
+        if let Err(e) = self.registry.commit(pending, &mut acc) {
+            let _ = std::fs::remove_file(&staged);
+            return Err(e);
+        }
+        std::fs::rename(staged, path)?;
+        debug_assert_eq!(state.epoch, acc.get_epoch());
+        self.state = state;
+        Ok(epochs)
+    }
+}
+
+// Fresh keys for the schema and an empty accumulator; writes the public
+// issuer file to `out`
+pub fn init(dir: &Path, schema: &Path, out: &Path, passphrase: &str) -> Result<(), Box<dyn Error>> {
+    if dir.join(KEYSTORE_FILE).exists() {
+        return Err(format!("{} already holds an issuer", dir.display()).into());
+    }
+    let schema = CredentialSchema::from_json(&std::fs::read_to_string(schema)?)?;
+    let issuer = Issuer::for_schema(&schema);
+    IssuerKeystore::new(dir)?.save(&issuer, passphrase)?;
+    std::fs::write(dir.join(SCHEMA_FILE), schema.to_json())?;
+
+    let keys = issuer.public_keys();
+    let acc = ECAccumulator::new(issuer.get_g1());
+    artifacts::write(&dir.join(ACCUMULATOR_FILE), &AccumulatorFile::new(&keys, &acc))?;
+    artifacts::write(out, &IssuerFile::new(&keys, &schema))?;
+    println!("issuer {} (key {})", keys.fingerprint(), keys.key_id);
+    Ok(())
+}
+
+// Issues a credential with the attribute values in `values` (a JSON object)
+pub fn issue(dir: &Path, values: &Path, metadata: BTreeMap<String, String>, out: &Path, passphrase: &str) -> Result<(), Box<dyn Error>> {
+    let dir = IssuerDir::open(dir, passphrase)?;
+    let values = dir.schema.values_from_json(&std::fs::read_to_string(values)?)?;
+    let file = dir.issue(&values, metadata)?;
+    artifacts::write(out, &file)?;
+    println!("{}", file.id);
+    Ok(())
+}
+
+// The revocations are visible once published
+pub fn revoke(dir: &Path, id: Option<&str>, attribute: Option<(&str, &str)>, passphrase: &str) -> Result<(), Box<dyn Error>> {
+    for (id, epoch) in IssuerDir::open(dir, passphrase)?.revoke(id, attribute)? {
+        println!("revoked {} (epoch {})", id, epoch);
+    }
+    Ok(())
+}
+
+// Copies the accumulator and its revocation log to `out` for holders and
+// verifiers; needs no passphrase
+pub fn publish(dir: &Path, out: &Path) -> Result<(), Box<dyn Error>> {
+    let state: AccumulatorFile = artifacts::read(&dir.join(ACCUMULATOR_FILE))?;
+    artifacts::write(out, &state)?;
+    println!("epoch {}, {} revocations", state.epoch, state.updates.len());
+    Ok(())
+}
@@ -0,0 +1,232 @@
+// vac: issuer, holder and verifier operations from the command line, passing
+// artifacts between the roles as files (see artifacts.rs), so scenarios can
+// be scripted end to end:
+//
+//   vac issuer init --dir issuer --schema schema.json --out issuer.pub.json
+//   vac issue --dir issuer --values alice.json --out cred.json
+//   vac holder import --wallet alice.wallet --issuer issuer.pub.json --cred cred.json
+//   vac revoke --dir issuer --id cred-00000001
+//   vac acc publish --dir issuer --out acc.json
+//   vac holder update --wallet alice.wallet --issuer issuer.pub.json --acc acc.json
+//   vac request --verifier https://verifier.example --issuer issuer.pub.json --acc acc.json \
+//       --reveal name --predicate 'score>=60' --out request.json
+//   vac holder present --wallet alice.wallet --issuer issuer.pub.json --acc acc.json \
+//       --request request.json --out presentation.json
+//   vac verify --issuer issuer.pub.json --acc acc.json --request request.json \
+//       --presentation presentation.json
+//
+// or runs the issuer and a verifier as a local HTTP service (see server.rs):
+//
+//   vac serve --dir issuer --listen 127.0.0.1:8080
+//
+// The issuer keystore and the wallet are encrypted under --passphrase, or
+// VAC_PASSPHRASE if it is not given.
+
+mod artifacts;
+mod holder;
+mod issuer;
+mod server;
+mod verifier;
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::Path;
+
+use verifier::RequestOptions;
+
+fn file(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name).long(name).value_name("FILE").required(true).help(help)
+}
+
+fn issuer_dir() -> Arg {
+    Arg::new("dir").long("dir").value_name("DIR").required(true).help("Issuer directory")
+}
+
+fn wallet() -> Arg {
+    file("wallet", "Holder wallet, created on first import")
+}
+
+fn cli() -> Command {
+    Command::new("vac")
+        .about("Anonymous credentials with accumulator revocation")
+        .subcommand_required(true)
+        .arg(Arg::new("passphrase").long("passphrase").global(true).help("Keystore or wallet passphrase [default: $VAC_PASSPHRASE]"))
+        .subcommand(
+            Command::new("issuer").about("Issuer setup").subcommand_required(true).subcommand(
+                Command::new("init")
+                    .about("Create issuer keys for a schema and an empty accumulator")
+                    .arg(issuer_dir())
+                    .arg(file("schema", "Credential schema (JSON)"))
+                    .arg(file("out", "Public issuer file to write")),
+            ),
+        )
+        .subcommand(
+            Command::new("issue")
+                .about("Issue a credential")
+                .arg(issuer_dir())
+                .arg(file("values", "Attribute values (JSON object)"))
+                .arg(Arg::new("meta").long("meta").value_name("NAME=VALUE").action(ArgAction::Append).help("Registry metadata, to revoke by"))
+                .arg(file("out", "Credential file to write")),
+        )
+        .subcommand(
+            Command::new("revoke")
+                .about("Revoke credentials; publish the accumulator afterwards")
+                .arg(issuer_dir())
+                .arg(Arg::new("id").long("id").help("Registry id of the credential"))
+                .arg(Arg::new("meta").long("meta").value_name("NAME=VALUE").conflicts_with("id").help("Revoke every credential with this metadata")),
+        )
+        .subcommand(
+            Command::new("acc").about("Accumulator").subcommand_required(true).subcommand(
+                Command::new("publish")
+                    .about("Write the accumulator and its revocation log for holders and verifiers")
+                    .arg(issuer_dir())
+                    .arg(file("out", "Accumulator file to write")),
+            ),
+        )
+        .subcommand(
+            Command::new("holder")
+                .about("Holder wallet")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("import")
+                        .about("Add an issued credential to the wallet")
+                        .arg(wallet())
+                        .arg(file("issuer", "Public issuer file"))
+                        .arg(file("cred", "Credential file")),
+                )
+                .subcommand(
+                    Command::new("update")
+                        .about("Bring the witnesses up to the published accumulator")
+                        .arg(wallet())
+                        .arg(file("issuer", "Public issuer file"))
+                        .arg(file("acc", "Published accumulator file")),
+                )
+                .subcommand(
+                    Command::new("present")
+                        .about("Answer a presentation request")
+                        .arg(wallet())
+                        .arg(file("issuer", "Public issuer file"))
+                        .arg(file("acc", "Published accumulator file"))
+                        .arg(file("request", "Presentation request"))
+                        .arg(file("out", "Presentation file to write")),
+                ),
+        )
+        .subcommand(
+            Command::new("request")
+                .about("Write a presentation request")
+                .arg(Arg::new("verifier").long("verifier").value_name("ID").required(true).help("Verifier identity, e.g. its URL"))
+                .arg(Arg::new("issuer").long("issuer").value_name("FILE").help("Accept only this issuer and its schema"))
+                .arg(Arg::new("acc").long("acc").value_name("FILE").help("Require the epoch of this accumulator file"))
+                .arg(Arg::new("reveal").long("reveal").value_name("ATTR,...").value_delimiter(',').action(ArgAction::Append).help("Attributes to disclose"))
+                .arg(Arg::new("predicate").long("predicate").value_name("ATTR>=V|ATTR<=V").action(ArgAction::Append).help("Range an attribute must lie in"))
+                .arg(Arg::new("scope").long("scope").help("Ask for the holder's pseudonym for this scope"))
+                .arg(file("out", "Request file to write")),
+        )
+        .subcommand(
+            Command::new("verify")
+                .about("Check a presentation against its request")
+                .arg(file("issuer", "Public issuer file"))
+                .arg(file("acc", "Published accumulator file"))
+                .arg(file("request", "Presentation request"))
+                .arg(file("presentation", "Presentation to check")),
+        )
+        .subcommand(
+            Command::new("serve")
+                .about("Serve issuance, revocation, the accumulator and verification over HTTP")
+                .arg(issuer_dir())
+                .arg(Arg::new("listen").long("listen").value_name("ADDR").default_value("127.0.0.1:8080").help("Address to listen on"))
+                .arg(Arg::new("verifier").long("verifier").value_name("ID").help("Verifier identity in requests [default: http://<listen address>]"))
+                .arg(Arg::new("ttl").long("ttl").value_name("SECONDS").value_parser(clap::value_parser!(u64)).default_value("300").help("How long a request stays answerable")),
+        )
+}
+
+fn path<'a>(args: &'a ArgMatches, name: &str) -> &'a Path {
+    Path::new(args.get_one::<String>(name).expect("required argument"))
+}
+
+fn optional_path<'a>(args: &'a ArgMatches, name: &str) -> Option<&'a Path> {
+    args.get_one::<String>(name).map(Path::new)
+}
+
+fn strings(args: &ArgMatches, name: &str) -> Vec<String> {
+    args.get_many::<String>(name).map_or(Vec::new(), |values| values.cloned().collect())
+}
+
+fn name_value(text: &str) -> Result<(&str, &str), Box<dyn Error>> {
+    text.split_once('=').ok_or_else(|| format!("{:?} is not NAME=VALUE", text).into())
+}
+
+fn passphrase(args: &ArgMatches) -> Result<String, Box<dyn Error>> {
+    match args.get_one::<String>("passphrase") {
+        Some(passphrase) => Ok(passphrase.clone()),
+        None => std::env::var("VAC_PASSPHRASE").map_err(|_| "no passphrase: give --passphrase or set VAC_PASSPHRASE".into()),
+    }
+}
+
+fn run(args: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    match args.subcommand().expect("subcommand required") {
+        ("issuer", args) => match args.subcommand().expect("subcommand required") {
+            ("init", args) => issuer::init(path(args, "dir"), path(args, "schema"), path(args, "out"), &passphrase(args)?),
+            _ => unreachable!(),
+        },
+        ("issue", args) => {
+            let mut metadata = BTreeMap::new();
+            for meta in strings(args, "meta") {
+                let (name, value) = name_value(&meta)?;
+                metadata.insert(name.to_string(), value.to_string());
+            }
+            issuer::issue(path(args, "dir"), path(args, "values"), metadata, path(args, "out"), &passphrase(args)?)
+        }
+        ("revoke", args) => {
+            let meta = args.get_one::<String>("meta").map(|m| name_value(m)).transpose()?;
+            let id = args.get_one::<String>("id").map(String::as_str);
+            issuer::revoke(path(args, "dir"), id, meta, &passphrase(args)?)
+        }
+        ("acc", args) => match args.subcommand().expect("subcommand required") {
+            ("publish", args) => issuer::publish(path(args, "dir"), path(args, "out")),
+            _ => unreachable!(),
+        },
+        ("holder", args) => match args.subcommand().expect("subcommand required") {
+            ("import", args) => holder::import(path(args, "wallet"), path(args, "issuer"), path(args, "cred"), &passphrase(args)?),
+            ("update", args) => holder::update(path(args, "wallet"), path(args, "issuer"), path(args, "acc"), &passphrase(args)?),
+            ("present", args) => holder::present(
+                path(args, "wallet"),
+                path(args, "issuer"),
+                path(args, "acc"),
+                path(args, "request"),
+                path(args, "out"),
+                &passphrase(args)?,
+            ),
+            _ => unreachable!(),
+        },
+        ("request", args) => {
+            let predicates = strings(args, "predicate").iter().map(|p| verifier::parse_predicate(p)).collect::<Result<Vec<_>, _>>()?;
+            let options = RequestOptions {
+                verifier: args.get_one::<String>("verifier").expect("required argument"),
+                issuer: optional_path(args, "issuer"),
+                acc: optional_path(args, "acc"),
+                reveal: strings(args, "reveal"),
+                predicates,
+                scope: args.get_one::<String>("scope").cloned(),
+            };
+            verifier::request(options, path(args, "out"))
+        }
+        ("verify", args) => verifier::verify(path(args, "issuer"), path(args, "acc"), path(args, "request"), path(args, "presentation")),
+        ("serve", args) => server::serve(
+            path(args, "dir"),
+            args.get_one::<String>("listen").expect("default value"),
+            args.get_one::<String>("verifier").map(String::as_str),
+            *args.get_one::<u64>("ttl").expect("default value"),
+            &passphrase(args)?,
+        ),
+        _ => unreachable!(),
+    }
+}
+
+fn main() {
+    if let Err(e) = run(&cli().get_matches()) {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,272 @@
+// `vac serve`: the issuer and a verifier as a local HTTP service on an issuer
+// directory made by `vac issuer init`. JSON in and out, in the encodings the
+// other commands pass around as files:
+//
+//   GET  /issuer                       IssuerFile
+//   POST /credentials                  {"values": {..}, "metadata": {..}} -> 201 CredentialFile
+//   POST /credentials/{id}/revoke      -> {"id", "epoch"}; 404 unknown, 409 already revoked
+//   GET  /accumulator                  checkpoint: {"issuer", "epoch", "alpha"}
+//   GET  /accumulator/updates?since=N  AccumulatorFile with the revocations after epoch N
+//   POST /requests                     {"reveal", "predicates", "scope"} -> PresentationRequest
+//   POST /presentations/verify         Presentation -> {"valid", "reason"}
+//   POST /groth16/verify               {"public", "proof"} as snarkjs writes them
+//                                      -> {"valid", "reason"}; 404 without a key
+//
+// Malformed input gets a 4xx status and {"error": ".."}; a proof that does
+// not verify is a 200 with "valid": false and the reason.
+//
+// Groth16 proofs are checked against the verifying key in the issuer
+// directory (groth16_vk.json), never one sent with the proof: whoever
+// sends the key can make any statement verify.
+//
+// The verifier keeps the requests it hands out. A presentation is checked
+// against the stored request its nonce belongs to, never against a copy sent
+// back by the client, and each request is answered once (ReplayCache) within
+// --ttl seconds.
+//
+// Issuance and revocation are not authenticated: keep the service on
+// localhost, as the default --listen does.
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use accumulator_impl::keystore::IssuerPublicKeys;
+use accumulator_impl::presentation::{Predicate, Presentation, PresentationRequest};
+use accumulator_impl::registry::CredentialStatus;
+use accumulator_impl::replay::ReplayCache;
+use prover::export_proof::{verify_snarkjs_json, vk_from_snarkjs, SnarkjsProof, SnarkjsVerifyingKey};
+
+use crate::artifacts::{AccumulatorFile, CredentialFile, IssuerFile};
+use crate::issuer::IssuerDir;
+
+struct Service {
+    dir: IssuerDir,
+    keys: IssuerPublicKeys,
+    groth16_vk: Option<SnarkjsVerifyingKey>,
+    replay: ReplayCache,
+    requests: BTreeMap<String, (PresentationRequest, u64)>, // nonce -> request, expiry
+}
+
+// One lock for all of it: issuance and revocation move the accumulator the
+// verifier checks against
+type Shared = Arc<Mutex<Service>>;
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+fn internal(e: Box<dyn Error>) -> ApiError {
+    ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+#[derive(Deserialize)]
+struct IssueBody {
+    values: serde_json::Value,
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+struct Revoked {
+    id: String,
+    epoch: u64,
+}
+
+#[derive(Serialize)]
+struct Checkpoint {
+    issuer: String,
+    epoch: u64,
+    alpha: String,
+}
+
+#[derive(Deserialize)]
+struct Since {
+    #[serde(default)]
+    since: u64,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct RequestBody {
+    reveal: Vec<String>,
+    predicates: Vec<Predicate>,
+    scope: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Groth16Body {
+    public: Vec<String>,
+    proof: SnarkjsProof,
+}
+
+#[derive(Serialize)]
+struct Verdict {
+    valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+impl Verdict {
+    fn rejected(reason: String) -> Json<Verdict> {
+        Json(Verdict { valid: false, reason: Some(reason) })
+    }
+}
+
+async fn issuer_file(State(service): State<Shared>) -> Json<IssuerFile> {
+    Json(service.lock().unwrap().dir.public_file())
+}
+
+async fn issue(State(service): State<Shared>, Json(body): Json<IssueBody>) -> Result<(StatusCode, Json<CredentialFile>), ApiError> {
+    let service = service.lock().unwrap();
+    let schema = &service.dir.schema;
+    let values = schema.values_from_json(&body.values.to_string()).map_err(|e| ApiError(StatusCode::BAD_REQUEST, e.to_string()))?;
+    schema.encode(&values).map_err(|e| ApiError(StatusCode::BAD_REQUEST, format!("{:?}", e)))?;
+    let file = service.dir.issue(&values, body.metadata).map_err(internal)?;
+    Ok((StatusCode::CREATED, Json(file)))
+}
+
+async fn revoke(State(service): State<Shared>, Path(id): Path<String>) -> Result<Json<Revoked>, ApiError> {
+    let mut service = service.lock().unwrap();
+    match service.dir.registry.get(&id).map_err(internal)? {
+        None => return Err(ApiError(StatusCode::NOT_FOUND, format!("unknown credential {}", id))),
+        Some(record) if record.status == CredentialStatus::Revoked => {
+            return Err(ApiError(StatusCode::CONFLICT, format!("credential {} is already revoked", id)))
+        }
+        Some(_) => {}
+    }
+    let (id, epoch) = service.dir.revoke(Some(&id), None).map_err(internal)?.remove(0);
+    Ok(Json(Revoked { id, epoch }))
+}
+
+async fn checkpoint(State(service): State<Shared>) -> Json<Checkpoint> {
+    let state = &service.lock().unwrap().dir.state;
+    Json(Checkpoint { issuer: state.issuer.clone(), epoch: state.epoch, alpha: state.alpha.clone() })
+}
+
+// The current value with only the part of the log a holder at epoch `since`
+// has not applied yet
+async fn updates(State(service): State<Shared>, Query(Since { since }): Query<Since>) -> Json<AccumulatorFile> {
+    let mut state = service.lock().unwrap().dir.state.clone();
+    state.updates.retain(|u| u.epoch > since);
+    Json(state)
+}
+
+// Requests accept only this issuer and schema, at the current epoch
+async fn new_request(State(service): State<Shared>, Json(body): Json<RequestBody>) -> Json<PresentationRequest> {
+    let mut service = service.lock().unwrap();
+    let now = now();
+    service.requests.retain(|_, (_, expiry)| *expiry >= now);
+    let challenge = service.replay.issue(now);
+    let request = PresentationRequest {
+        issuers: vec![service.keys.fingerprint()],
+        schemas: vec![service.dir.schema.id()],
+        reveal: body.reveal,
+        predicates: body.predicates,
+        epoch: Some(service.dir.state.epoch),
+        scope: body.scope,
+        ..PresentationRequest::new(&challenge)
+    };
+    let expiry = now + service.replay.ttl();
+    service.requests.insert(challenge.nonce, (request.clone(), expiry));
+    Json(request)
+}
+
+async fn verify_presentation(State(service): State<Shared>, Json(presentation): Json<Presentation>) -> Result<Json<Verdict>, ApiError> {
+    let mut service = service.lock().unwrap();
+    let Service { dir, keys, replay, requests, .. } = &mut *service;
+    let Some((request, _)) = requests.get(&presentation.nonce) else {
+        return Ok(Verdict::rejected("no pending request with this nonce".to_string()));
+    };
+    let Some(state) = dir.state.state_at(presentation.epoch).map_err(internal)? else {
+        return Ok(Verdict::rejected(format!("no accumulator value for epoch {}", presentation.epoch)));
+    };
+    Ok(match request.validate_once(&presentation, keys, &dir.schema, &state, replay, now()) {
+        Ok(()) => Json(Verdict { valid: true, reason: None }),
+        Err(e) => Verdict::rejected(format!("{:?}", e)),
+    })
+}
+
+fn verify_snarkjs(vk: &SnarkjsVerifyingKey, public: &[String], proof: &SnarkjsProof) -> Result<bool, Box<dyn Error>> {
+    match vk.curve.as_str() {
+        "bn128" => verify_snarkjs_json::<ark_bn254::Bn254>(vk, public, proof),
+        "bls12381" => verify_snarkjs_json::<ark_bls12_381::Bls12_381>(vk, public, proof),
+        curve => Err(format!("unsupported curve {:?}", curve).into()),
+    }
+}
+
+// The issuer directory's key, checked to decode on its curve before serving
+fn groth16_vk(dir: &IssuerDir) -> Result<Option<SnarkjsVerifyingKey>, Box<dyn Error>> {
+    let Some(vk) = dir.groth16_vk()? else {
+        return Ok(None);
+    };
+    match vk.curve.as_str() {
+        "bn128" => drop(vk_from_snarkjs::<ark_bn254::Bn254>(&vk)?),
+        "bls12381" => drop(vk_from_snarkjs::<ark_bls12_381::Bls12_381>(&vk)?),
+        curve => return Err(format!("groth16 verifying key: unsupported curve {:?}", curve).into()),
+    }
+    Ok(Some(vk))
+}
+
+async fn verify_groth16(State(service): State<Shared>, Json(body): Json<Groth16Body>) -> Result<Json<Verdict>, ApiError> {
+    let service = service.lock().unwrap();
+    let Some(vk) = &service.groth16_vk else {
+        return Err(ApiError(StatusCode::NOT_FOUND, "the issuer directory has no Groth16 verifying key".to_string()));
+    };
+    match verify_snarkjs(vk, &body.public, &body.proof) {
+        Ok(true) => Ok(Json(Verdict { valid: true, reason: None })),
+        Ok(false) => Ok(Verdict::rejected("proof does not verify".to_string())),
+        Err(e) => Err(ApiError(StatusCode::BAD_REQUEST, e.to_string())),
+    }
+}
+
+fn router(service: Shared) -> Router {
+    Router::new()
+        .route("/issuer", get(issuer_file))
+        .route("/credentials", post(issue))
+        .route("/credentials/{id}/revoke", post(revoke))
+        .route("/accumulator", get(checkpoint))
+        .route("/accumulator/updates", get(updates))
+        .route("/requests", post(new_request))
+        .route("/presentations/verify", post(verify_presentation))
+        .route("/groth16/verify", post(verify_groth16))
+        .with_state(service)
+}
+
+async fn run(dir: IssuerDir, listen: &str, verifier: Option<&str>, ttl: u64) -> Result<(), Box<dyn Error>> {
+    let listener = tokio::net::TcpListener::bind(listen).await?;
+    let address = format!("http://{}", listener.local_addr()?);
+    let service = Service {
+        keys: dir.issuer.public_keys(),
+        groth16_vk: groth16_vk(&dir)?,
+        dir,
+        replay: ReplayCache::new(verifier.unwrap_or(&address), ttl),
+        requests: BTreeMap::new(),
+    };
+    println!("listening on {}", address);
+    axum::serve(listener, router(Arc::new(Mutex::new(service)))).await?;
+    Ok(())
+}
+
+// Serves until killed. Prints the address it listens on first, so a port 0
+// listener can be found. The verifier identity defaults to that address
+pub fn serve(dir: &std::path::Path, listen: &str, verifier: Option<&str>, ttl: u64, passphrase: &str) -> Result<(), Box<dyn Error>> {
+    let dir = IssuerDir::open(dir, passphrase)?;
+    tokio::runtime::Runtime::new()?.block_on(run(dir, listen, verifier, ttl))
+}
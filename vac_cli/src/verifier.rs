@@ -0,0 +1,80 @@
+// Verifier commands: write a presentation request, check the answer.
+//
+// The challenge in a request is fresh, but these commands keep no state
+// between runs, so they do not enforce that each is answered once
+// (PresentationRequest::validate_once does, for a verifier that keeps its
+// ReplayCache).
+
+use std::error::Error;
+use std::path::Path;
+
+use accumulator_impl::presentation::{Predicate, Presentation, PresentationRequest};
+use accumulator_impl::replay::VerifierChallenge;
+use accumulator_impl::schema::parse_date;
+
+use crate::artifacts::{self, AccumulatorFile, IssuerFile};
+
+// "score>=60", "dob<=2007-01-01": a bound on an Integer or Date attribute
+pub fn parse_predicate(text: &str) -> Result<Predicate, Box<dyn Error>> {
+    let (attribute, value, at_least) = match (text.split_once(">="), text.split_once("<=")) {
+        (Some((attribute, value)), None) => (attribute, value, true),
+        (None, Some((attribute, value))) => (attribute, value, false),
+        _ => return Err(format!("predicate {:?} is not <attribute>>=<value> or <attribute><=<value>", text).into()),
+    };
+    let bound = match value.parse::<u64>() {
+        Ok(bound) => bound,
+        Err(_) => parse_date(value).ok_or_else(|| format!("{:?} is neither a number nor a YYYY-MM-DD date", value))?,
+    };
+    let (min, max) = if at_least { (Some(bound), None) } else { (None, Some(bound)) };
+    Ok(Predicate { attribute: attribute.trim().to_string(), min, max })
+}
+
+pub struct RequestOptions<'a> {
+    pub verifier: &'a str,
+    pub issuer: Option<&'a Path>, // accept only this issuer and its schema
+    pub acc: Option<&'a Path>, // require the epoch this accumulator is at
+    pub reveal: Vec<String>,
+    pub predicates: Vec<Predicate>,
+    pub scope: Option<String>,
+}
+
+pub fn request(options: RequestOptions, out: &Path) -> Result<(), Box<dyn Error>> {
+    let mut request = PresentationRequest {
+        reveal: options.reveal,
+        predicates: options.predicates,
+        scope: options.scope,
+        ..PresentationRequest::new(&VerifierChallenge::new(options.verifier))
+    };
+    if let Some(issuer) = options.issuer {
+        let issuer: IssuerFile = artifacts::read(issuer)?;
+        request.issuers = vec![issuer.fingerprint.clone()];
+        request.schemas = vec![issuer.schema.id()];
+    }
+    if let Some(acc) = options.acc {
+        request.epoch = Some(artifacts::read::<AccumulatorFile>(acc)?.epoch);
+    }
+    std::fs::write(out, request.to_json())?;
+    Ok(())
+}
+
+// Checks the presentation against the request, the issuer's keys and the
+// latest accumulator value, or the one at the epoch the request pins: a
+// presentation at an older epoch could come from a revoked credential
+pub fn verify(issuer: &Path, acc: &Path, request: &Path, presentation: &Path) -> Result<(), Box<dyn Error>> {
+    let issuer: IssuerFile = artifacts::read(issuer)?;
+    let acc: AccumulatorFile = artifacts::read(acc)?;
+    if acc.issuer != issuer.fingerprint {
+        return Err("the accumulator was published by another issuer".into());
+    }
+    let request = PresentationRequest::from_json(&std::fs::read_to_string(request)?)?;
+    let presentation = Presentation::from_json(&std::fs::read_to_string(presentation)?)?;
+    let state = match request.epoch {
+        Some(epoch) => acc.state_at(epoch)?.ok_or_else(|| format!("no accumulator value for epoch {}", epoch))?,
+        None => acc.state()?,
+    };
+    request
+        .validate(&presentation, &issuer.keys()?, &issuer.schema, &state)
+        .map_err(|e| format!("presentation rejected: {:?}", e))?;
+    println!("valid");
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use accumulator_impl::schema::exam_candidate_schema;
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vac_cli_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn vac(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_vac")).args(args).current_dir(dir).env("VAC_PASSPHRASE", "pass").output().unwrap()
+}
+
+// runs a command that has to succeed, returns its stdout
+fn ok(dir: &Path, args: &[&str]) -> String {
+    let output = vac(dir, args);
+    assert!(output.status.success(), "vac {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn fails(dir: &Path, args: &[&str]) -> String {
+    let output = vac(dir, args);
+    assert!(!output.status.success(), "vac {:?} succeeded", args);
+    String::from_utf8(output.stderr).unwrap()
+}
+
+fn write_values(dir: &Path, file: &str, name: &str, score: u64) {
+    let values = format!(r#"{{"name": "{}", "dob": "2004-11-15", "age": 21, "score": {}, "candidate_id": 245}}"#, name, score);
+    std::fs::write(dir.join(file), values).unwrap();
+}
+
+const ISSUER: [&str; 2] = ["--issuer", "issuer.pub.json"];
+
+fn request(dir: &Path) {
+    let args = [&["request", "--verifier", "https://verifier.example", "--acc", "acc.json"], &ISSUER[..]].concat();
+    ok(dir, &[&args[..], &["--reveal", "name", "--predicate", "score>=60", "--scope", "exam-board", "--out", "request.json"]].concat());
+}
+
+fn present(dir: &Path, wallet: &str) -> Output {
+    let args = [&["holder", "present", "--wallet", wallet, "--acc", "acc.json"], &ISSUER[..]].concat();
+    vac(dir, &[&args[..], &["--request", "request.json", "--out", "presentation.json"]].concat())
+}
+
+fn verify(dir: &Path) -> Output {
+    let args = [&["verify", "--acc", "acc.json", "--request", "request.json"], &ISSUER[..]].concat();
+    vac(dir, &[&args[..], &["--presentation", "presentation.json"]].concat())
+}
+
+#[test]
+fn issue_present_revoke_end_to_end() {
+    let dir = scratch_dir("end_to_end");
+    std::fs::write(dir.join("schema.json"), exam_candidate_schema().to_json()).unwrap();
+    write_values(&dir, "alice.json", "Alice", 75);
+    write_values(&dir, "bob.json", "Bob", 80);
+
+    ok(&dir, &["issuer", "init", "--dir", "issuer", "--schema", "schema.json", "--out", "issuer.pub.json"]);
+    assert!(fails(&dir, &["issuer", "init", "--dir", "issuer", "--schema", "schema.json", "--out", "issuer.pub.json"]).contains("already"));
+    ok(&dir, &["issue", "--dir", "issuer", "--values", "alice.json", "--meta", "holder=alice", "--out", "alice.cred.json"]);
+    let bob = ok(&dir, &["issue", "--dir", "issuer", "--values", "bob.json", "--meta", "holder=bob", "--out", "bob.cred.json"]);
+    assert!(bob.starts_with("cred-"));
+    ok(&dir, &[&["holder", "import", "--wallet", "alice.wallet", "--cred", "alice.cred.json"], &ISSUER[..]].concat());
+    ok(&dir, &[&["holder", "import", "--wallet", "bob.wallet", "--cred", "bob.cred.json"], &ISSUER[..]].concat());
+
+    ok(&dir, &["acc", "publish", "--dir", "issuer", "--out", "acc.json"]);
+    request(&dir);
+    assert!(present(&dir, "alice.wallet").status.success());
+    assert_eq!(String::from_utf8(verify(&dir).stdout).unwrap(), "valid\n");
+    let presentation: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("presentation.json")).unwrap()).unwrap();
+    assert!(presentation["pseudonym"].is_string());
+    let wrong = [&["holder", "update", "--wallet", "alice.wallet", "--acc", "acc.json", "--passphrase", "wrong"], &ISSUER[..]].concat();
+    assert!(fails(&dir, &wrong).contains("wrong passphrase"));
+
+    ok(&dir, &["revoke", "--dir", "issuer", "--meta", "holder=bob"]);
+    assert!(fails(&dir, &["revoke", "--dir", "issuer", "--id", bob.trim()]).contains("already revoked"));
+    ok(&dir, &["acc", "publish", "--dir", "issuer", "--out", "acc.json"]);
+    request(&dir);
+
+    // alice's witness is stale until she catches up
+    assert!(!present(&dir, "alice.wallet").status.success());
+    assert_eq!(ok(&dir, &[&["holder", "update", "--wallet", "alice.wallet", "--acc", "acc.json"], &ISSUER[..]].concat()), "epoch 1\n");
+    assert!(present(&dir, "alice.wallet").status.success());
+    assert!(verify(&dir).status.success());
+
+    let updated = ok(&dir, &[&["holder", "update", "--wallet", "bob.wallet", "--acc", "acc.json"], &ISSUER[..]].concat());
+    assert_eq!(updated, "revoked wc-0001\nepoch 1\n");
+    assert!(!present(&dir, "bob.wallet").status.success());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn holders_catch_up_with_the_log() {
+    let dir = scratch_dir("catch_up");
+    std::fs::write(dir.join("schema.json"), exam_candidate_schema().to_json()).unwrap();
+    write_values(&dir, "alice.json", "Alice", 75);
+    write_values(&dir, "other.json", "Other", 50);
+    ok(&dir, &["issuer", "init", "--dir", "issuer", "--schema", "schema.json", "--out", "issuer.pub.json"]);
+
+    // alice is issued after the first revocation, and only takes the second
+    let first = ok(&dir, &["issue", "--dir", "issuer", "--values", "other.json", "--out", "first.cred.json"]);
+    ok(&dir, &["revoke", "--dir", "issuer", "--id", first.trim()]);
+    ok(&dir, &["issue", "--dir", "issuer", "--values", "alice.json", "--out", "alice.cred.json"]);
+    let second = ok(&dir, &["issue", "--dir", "issuer", "--values", "other.json", "--out", "second.cred.json"]);
+    ok(&dir, &["revoke", "--dir", "issuer", "--id", second.trim()]);
+    ok(&dir, &["acc", "publish", "--dir", "issuer", "--out", "acc.json"]);
+
+    ok(&dir, &[&["holder", "import", "--wallet", "alice.wallet", "--cred", "alice.cred.json"], &ISSUER[..]].concat());
+    ok(&dir, &[&["holder", "update", "--wallet", "alice.wallet", "--acc", "acc.json"], &ISSUER[..]].concat());
+    // updating twice applies nothing twice
+    assert_eq!(ok(&dir, &[&["holder", "update", "--wallet", "alice.wallet", "--acc", "acc.json"], &ISSUER[..]].concat()), "epoch 2\n");
+    request(&dir);
+    assert!(present(&dir, "alice.wallet").status.success());
+    assert!(verify(&dir).status.success());
+
+    // the presentation answers only the request it was made for
+    let presentation = std::fs::read_to_string(dir.join("presentation.json")).unwrap();
+    request(&dir);
+    std::fs::write(dir.join("presentation.json"), presentation).unwrap();
+    assert!(String::from_utf8(verify(&dir).stderr).unwrap().contains("NonceMismatch"));
+
+    // a score below the bound cannot be presented
+    ok(&dir, &["issue", "--dir", "issuer", "--values", "other.json", "--out", "third.cred.json"]);
+    ok(&dir, &[&["holder", "import", "--wallet", "other.wallet", "--cred", "third.cred.json"], &ISSUER[..]].concat());
+    assert!(!present(&dir, "other.wallet").status.success());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn revoked_holders_cannot_present_at_an_old_epoch() {
+    let dir = scratch_dir("old_epoch");
+    std::fs::write(dir.join("schema.json"), exam_candidate_schema().to_json()).unwrap();
+    write_values(&dir, "bob.json", "Bob", 80);
+    ok(&dir, &["issuer", "init", "--dir", "issuer", "--schema", "schema.json", "--out", "issuer.pub.json"]);
+    let bob = ok(&dir, &["issue", "--dir", "issuer", "--values", "bob.json", "--out", "bob.cred.json"]);
+    ok(&dir, &[&["holder", "import", "--wallet", "bob.wallet", "--cred", "bob.cred.json"], &ISSUER[..]].concat());
+    ok(&dir, &["acc", "publish", "--dir", "issuer", "--out", "old.json"]);
+    ok(&dir, &["revoke", "--dir", "issuer", "--id", bob.trim()]);
+    ok(&dir, &["acc", "publish", "--dir", "issuer", "--out", "acc.json"]);
+
+    // the request pins no epoch; bob answers with the accumulator from before
+    // his revocation and the verifier checks against the latest one
+    let args = [&["request", "--verifier", "https://verifier.example"], &ISSUER[..]].concat();
+    ok(&dir, &[&args[..], &["--reveal", "name", "--out", "request.json"]].concat());
+    let args = [&["holder", "present", "--wallet", "bob.wallet", "--acc", "old.json"], &ISSUER[..]].concat();
+    ok(&dir, &[&args[..], &["--request", "request.json", "--out", "presentation.json"]].concat());
+    assert!(String::from_utf8(verify(&dir).stderr).unwrap().contains("EpochMismatch"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,205 @@
+use ark_bn254::{Bn254, Fr};
+use ark_ff::UniformRand;
+use ark_groth16::{Groth16, Proof, VerifyingKey};
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+use accumulator_impl::schema::exam_candidate_schema;
+use prover::circuit::CubeCircuit;
+use prover::export_proof::{proof_to_snarkjs, public_inputs_to_snarkjs, vk_to_snarkjs};
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("vac_server_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn vac(dir: &Path, args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_vac"));
+    command.args(args).current_dir(dir).env("VAC_PASSPHRASE", "pass");
+    command
+}
+
+fn ok(dir: &Path, args: &[&str]) -> String {
+    let output = vac(dir, args).output().unwrap();
+    assert!(output.status.success(), "vac {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+// `vac serve` on a fresh issuer directory, on a free localhost port
+struct Server {
+    child: Child,
+    address: String,
+}
+
+fn init_issuer(dir: &Path) {
+    std::fs::write(dir.join("schema.json"), exam_candidate_schema().to_json()).unwrap();
+    ok(dir, &["issuer", "init", "--dir", "issuer", "--schema", "schema.json", "--out", "issuer.pub.json"]);
+}
+
+impl Server {
+    // serves the issuer directory made by init_issuer
+    fn start(dir: &Path) -> Server {
+        let mut child = vac(dir, &["serve", "--dir", "issuer", "--listen", "127.0.0.1:0"]).stdout(Stdio::piped()).spawn().unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap()).read_line(&mut line).unwrap();
+        let address = line.trim().strip_prefix("listening on http://").expect("server did not start").to_string();
+        Server { child, address }
+    }
+
+    // One request per connection; returns the status and the JSON body
+    fn call(&self, method: &str, path: &str, body: Option<&Value>) -> (u16, Value) {
+        let body = body.map_or(String::new(), |b| b.to_string());
+        let mut stream = TcpStream::connect(&self.address).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            self.address,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap_or(Value::Null))
+    }
+
+    fn get(&self, path: &str) -> (u16, Value) {
+        self.call("GET", path, None)
+    }
+
+    fn post(&self, path: &str, body: Value) -> (u16, Value) {
+        self.call("POST", path, Some(&body))
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn values(name: &str, score: u64) -> Value {
+    json!({ "name": name, "dob": "2004-11-15", "age": 21, "score": score, "candidate_id": 245 })
+}
+
+fn save(dir: &Path, file: &str, value: &Value) {
+    std::fs::write(dir.join(file), value.to_string()).unwrap();
+}
+
+// fetches a request from the server and answers it from the wallet
+fn present(dir: &Path, server: &Server, wallet: &str) -> Value {
+    let (_, request) = server.post("/requests", json!({ "reveal": ["name"], "predicates": [{ "attribute": "score", "min": 60 }] }));
+    save(dir, "request.json", &request);
+    let (_, acc) = server.get("/accumulator/updates");
+    save(dir, "acc.json", &acc);
+    let args = ["holder", "present", "--wallet", wallet, "--issuer", "issuer.pub.json", "--acc", "acc.json"];
+    ok(dir, &[&args[..], &["--request", "request.json", "--out", "presentation.json"]].concat());
+    serde_json::from_str(&std::fs::read_to_string(dir.join("presentation.json")).unwrap()).unwrap()
+}
+
+// a proof for the cube circuit, with its verifying key and public output
+fn cube_proof(rng: &mut StdRng) -> (CubeCircuit<Fr>, VerifyingKey<Bn254>, Proof<Bn254>, Fr) {
+    let x = Fr::rand(rng);
+    let y = x * x * x + x + Fr::from(5u64);
+    let circuit = CubeCircuit { x: Some(x), y: Some(y) };
+    let (pk, vk) = Groth16::<Bn254>::setup(circuit.clone(), rng).unwrap();
+    let proof = Groth16::<Bn254>::prove(&pk, circuit.clone(), rng).unwrap();
+    (circuit, vk, proof, y)
+}
+
+#[test]
+fn issue_verify_revoke_over_http() {
+    let dir = scratch_dir("end_to_end");
+    init_issuer(&dir);
+    let server = Server::start(&dir);
+    let (status, issuer) = server.get("/issuer");
+    assert_eq!(status, 200);
+    assert_eq!(issuer, serde_json::from_str::<Value>(&std::fs::read_to_string(dir.join("issuer.pub.json")).unwrap()).unwrap());
+
+    let (status, alice) = server.post("/credentials", json!({ "values": values("Alice", 75) }));
+    assert_eq!(status, 201);
+    save(&dir, "alice.cred.json", &alice);
+    let (_, bob) = server.post("/credentials", json!({ "values": values("Bob", 80), "metadata": { "holder": "bob" } }));
+    let (status, error) = server.post("/credentials", json!({ "values": { "name": "Eve", "score": "high" } }));
+    assert_eq!(status, 400);
+    assert!(error["error"].is_string());
+    ok(&dir, &["holder", "import", "--wallet", "alice.wallet", "--issuer", "issuer.pub.json", "--cred", "alice.cred.json"]);
+
+    let presentation = present(&dir, &server, "alice.wallet");
+    assert_eq!(server.post("/presentations/verify", presentation.clone()), (200, json!({ "valid": true })));
+    let (_, replayed) = server.post("/presentations/verify", presentation);
+    assert_eq!(replayed["valid"], false);
+    assert!(replayed["reason"].as_str().unwrap().contains("Replayed"));
+
+    let bob_id = bob["id"].as_str().unwrap();
+    assert_eq!(server.post(&format!("/credentials/{}/revoke", bob_id), json!({})), (200, json!({ "id": bob_id, "epoch": 1 })));
+    assert_eq!(server.post(&format!("/credentials/{}/revoke", bob_id), json!({})).0, 409);
+    assert_eq!(server.post("/credentials/cred-nobody/revoke", json!({})).0, 404);
+    let (_, checkpoint) = server.get("/accumulator");
+    assert_eq!(checkpoint["epoch"], 1);
+    assert_eq!(server.get("/accumulator/updates?since=0").1["updates"].as_array().unwrap().len(), 1);
+    assert!(server.get("/accumulator/updates?since=1").1["updates"].as_array().unwrap().is_empty());
+
+    // alice catches up from the log before she can answer again
+    save(&dir, "acc.json", &server.get("/accumulator/updates?since=0").1);
+    ok(&dir, &["holder", "update", "--wallet", "alice.wallet", "--issuer", "issuer.pub.json", "--acc", "acc.json"]);
+    let presentation = present(&dir, &server, "alice.wallet");
+    assert_eq!(server.post("/presentations/verify", presentation).1["valid"], true);
+
+    // a presentation for a request this verifier never made is refused
+    let mut forged = present(&dir, &server, "alice.wallet");
+    forged["nonce"] = json!("00".repeat(32));
+    assert_eq!(server.post("/presentations/verify", forged).1["valid"], false);
+
+    // no groth16_vk.json in the issuer directory
+    let (_, _, proof, y) = cube_proof(&mut StdRng::seed_from_u64(0u64));
+    let body = json!({ "public": public_inputs_to_snarkjs(&[y]), "proof": proof_to_snarkjs(&proof) });
+    assert_eq!(server.post("/groth16/verify", body).0, 404);
+
+    drop(server);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn groth16_proofs_over_http() {
+    let dir = scratch_dir("groth16");
+    init_issuer(&dir);
+    let rng = &mut StdRng::seed_from_u64(0u64);
+    let (circuit, vk, proof, y) = cube_proof(rng);
+    save(&dir, "issuer/groth16_vk.json", &serde_json::to_value(vk_to_snarkjs(&vk)).unwrap());
+    let server = Server::start(&dir);
+
+    let body = |public: Fr| json!({ "public": public_inputs_to_snarkjs(&[public]), "proof": proof_to_snarkjs(&proof) });
+    assert_eq!(server.post("/groth16/verify", body(y)), (200, json!({ "valid": true })));
+    assert_eq!(server.post("/groth16/verify", body(y + Fr::from(1u64))).1["valid"], false);
+
+    // a key sent with the proof is refused, so one that accepts a forged
+    // statement cannot be swapped in
+    let (other_pk, other_vk) = Groth16::<Bn254>::setup(circuit.clone(), rng).unwrap();
+    let other_proof = Groth16::<Bn254>::prove(&other_pk, circuit, rng).unwrap();
+    let mut with_vk = body(y);
+    with_vk["vk"] = serde_json::to_value(vk_to_snarkjs(&other_vk)).unwrap();
+    with_vk["proof"] = serde_json::to_value(proof_to_snarkjs(&other_proof)).unwrap();
+    assert_eq!(server.post("/groth16/verify", with_vk).0, 422);
+    let mut other = body(y);
+    other["proof"] = serde_json::to_value(proof_to_snarkjs(&other_proof)).unwrap();
+    assert_eq!(server.post("/groth16/verify", other).1["valid"], false);
+    let mut too_many = body(y);
+    too_many["public"] = json!(["1", "2"]);
+    assert_eq!(server.post("/groth16/verify", too_many).0, 400);
+
+    drop(server);
+    std::fs::remove_dir_all(&dir).unwrap();
+}